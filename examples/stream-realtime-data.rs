@@ -100,7 +100,7 @@ async fn main() {
   let () = stream
     // Stop after receiving and printing 50 updates.
     .take(50)
-    .map_err(Error::WebSocket)
+    .map_err(Error::from)
     .try_for_each(|result| async { result.map(|data| println!("{data:?}")).map_err(Error::Json) })
     .await
     .unwrap();
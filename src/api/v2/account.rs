@@ -4,6 +4,7 @@
 use std::ops::Deref;
 
 use chrono::DateTime;
+use chrono::NaiveDate;
 use chrono::Utc;
 
 use num_decimal::Num;
@@ -64,8 +65,32 @@ pub enum Status {
 }
 
 
+/// An enumeration of the various states of an account's crypto
+/// trading ability.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum CryptoStatus {
+  /// Crypto trading has not been enabled for the account.
+  #[serde(rename = "INACTIVE")]
+  Inactive,
+  /// The crypto trading agreement has been submitted and is awaiting
+  /// approval.
+  #[serde(rename = "APPROVAL_PENDING")]
+  ApprovalPending,
+  /// The account is active for crypto trading.
+  #[serde(rename = "ACTIVE")]
+  Active,
+  /// The submission for crypto trading failed.
+  #[serde(rename = "SUBMISSION_FAILED")]
+  SubmissionFailed,
+  /// Any other crypto status that we have not accounted for.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
 /// An object as returned by the /v2/account endpoint.
-// TODO: The `sma` field is not yet hooked up.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Account {
   /// Account ID.
@@ -139,16 +164,67 @@ pub struct Account {
   /// Maintenance margin requirement (this value is continuously updated).
   #[serde(rename = "maintenance_margin")]
   pub maintenance_margin: Num,
+  /// The value of the special memorandum account, if reported.
+  #[serde(rename = "sma", default)]
+  pub sma: Option<Num>,
+  /// The buying power under Regulation T (the account's excess equity
+  /// multiplied by the Reg-T margin multiplier), if reported.
+  #[serde(rename = "regt_buying_power", default)]
+  pub regt_buying_power: Option<Num>,
+  /// The buying power available for securities that are not
+  /// marginable, if reported.
+  #[serde(rename = "non_marginable_buying_power", default)]
+  pub non_marginable_buying_power: Option<Num>,
   /// The current number of day trades that have been made in the last
   /// five trading days (including today).
   #[serde(rename = "daytrade_count")]
   pub daytrade_count: u64,
+  /// Maintenance margin requirement as of the previous trading day at
+  /// 16:00:00 ET.
+  #[serde(rename = "last_maintenance_margin")]
+  pub last_maintenance_margin: Option<Num>,
+  /// The date of the balances (e.g., `last_equity`) that serve as the
+  /// baseline for intraday values.
+  #[serde(rename = "balance_asof")]
+  pub balance_asof: Option<NaiveDate>,
+  /// The status of the account's crypto trading ability, if reported.
+  #[serde(rename = "crypto_status", default)]
+  pub crypto_status: Option<CryptoStatus>,
+  /// The crypto fee tier of the account, if reported.
+  #[serde(rename = "crypto_tier", default)]
+  pub crypto_tier: Option<u8>,
+  /// The options trading level the account is approved for, if
+  /// reported.
+  ///
+  /// See [`order::required_options_level`][crate::api::v2::order::required_options_level]
+  /// for the meaning of the individual levels.
+  #[serde(rename = "options_approved_level", default)]
+  pub options_approved_level: Option<u8>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl Account {
+  /// Calculate the change in equity since the previous trading day's
+  /// close.
+  ///
+  /// The first element of the returned pair is the absolute change,
+  /// i.e., `equity - last_equity`. The second is the change as a
+  /// percentage of `last_equity`, which is `None` if `last_equity` is
+  /// zero.
+  pub fn todays_change(&self) -> (Num, Option<Num>) {
+    let change = &self.equity - &self.last_equity;
+    let percent = if self.last_equity.is_zero() {
+      None
+    } else {
+      Some(&change * 100 / &self.last_equity)
+    };
+    (change, percent)
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/account endpoint.
@@ -233,6 +309,128 @@ mod tests {
     assert_eq!(acc.daytrade_count, 0);
   }
 
+  /// Check that we can parse the intraday baseline fields of an
+  /// account and calculate today's change in equity.
+  #[test]
+  fn parse_account_todays_change() {
+    let json = r#"{
+  "id": "904837e3-3b76-47ec-b432-046db621571b",
+  "status": "ACTIVE",
+  "currency": "USD",
+  "buying_power": "10000.0",
+  "cash": "1000.00",
+  "pattern_day_trader": false,
+  "trade_suspended_by_user": false,
+  "trading_blocked": false,
+  "transfers_blocked": false,
+  "account_blocked": false,
+  "created_at": "2018-10-01T13:35:25Z",
+  "shorting_enabled": true,
+  "multiplier": "2",
+  "long_market_value": "4250.00",
+  "short_market_value": "0",
+  "equity": "5250.00",
+  "last_equity": "5000.00",
+  "initial_margin": "2125.00",
+  "maintenance_margin": "1275.00",
+  "last_maintenance_margin": "1200.00",
+  "daytrade_count": 0,
+  "balance_asof": "2023-09-28"
+}"#;
+
+    let mut acc = from_json::<Account>(json).unwrap();
+    assert_eq!(acc.last_maintenance_margin, Some(Num::from(1200)));
+    assert_eq!(
+      acc.balance_asof,
+      Some(NaiveDate::from_ymd_opt(2023, 9, 28).unwrap())
+    );
+    assert_eq!(acc.todays_change(), (Num::from(250), Some(Num::from(5))));
+
+    acc.last_equity = Num::from(0);
+    assert_eq!(acc.todays_change(), (Num::from(5250), None));
+  }
+
+  /// Check that we can parse the crypto related fields of an account.
+  #[test]
+  fn parse_crypto_account() {
+    let json = r#"{
+  "id": "904837e3-3b76-47ec-b432-046db621571b",
+  "status": "ACTIVE",
+  "crypto_status": "ACTIVE",
+  "crypto_tier": 1,
+  "options_approved_level": 3,
+  "currency": "USD",
+  "buying_power": "10000.0",
+  "cash": "1000.00",
+  "pattern_day_trader": false,
+  "trade_suspended_by_user": false,
+  "trading_blocked": false,
+  "transfers_blocked": false,
+  "account_blocked": false,
+  "created_at": "2018-10-01T13:35:25Z",
+  "shorting_enabled": true,
+  "multiplier": "2",
+  "long_market_value": "0",
+  "short_market_value": "0",
+  "equity": "1000.00",
+  "last_equity": "1000.00",
+  "initial_margin": "0",
+  "maintenance_margin": "0",
+  "daytrade_count": 0
+}"#;
+
+    let acc = from_json::<Account>(json).unwrap();
+    assert_eq!(acc.crypto_status, Some(CryptoStatus::Active));
+    assert_eq!(acc.crypto_tier, Some(1));
+    assert_eq!(acc.options_approved_level, Some(3));
+
+    let json = json.replace(
+      r#""crypto_status": "ACTIVE""#,
+      r#""crypto_status": "FOOBAR""#,
+    );
+    let acc = from_json::<Account>(&json).unwrap();
+    assert_eq!(acc.crypto_status, Some(CryptoStatus::Unknown));
+  }
+
+  /// Check that we can parse the buying power related fields of a
+  /// margin account.
+  #[test]
+  fn parse_margin_account_buying_power() {
+    let json = r#"{
+  "id": "904837e3-3b76-47ec-b432-046db621571b",
+  "status": "ACTIVE",
+  "currency": "USD",
+  "buying_power": "262113.632",
+  "regt_buying_power": "262113.632",
+  "non_marginable_buying_power": "131056.82",
+  "sma": "135532.5",
+  "cash": "-23140.2",
+  "pattern_day_trader": false,
+  "trade_suspended_by_user": false,
+  "trading_blocked": false,
+  "transfers_blocked": false,
+  "account_blocked": false,
+  "created_at": "2018-10-01T13:35:25Z",
+  "shorting_enabled": true,
+  "multiplier": "2",
+  "long_market_value": "126660.0",
+  "short_market_value": "0",
+  "equity": "103520.51",
+  "last_equity": "103529.24",
+  "initial_margin": "63330.0",
+  "maintenance_margin": "38000.832",
+  "daytrade_count": 0
+}"#;
+
+    let acc = from_json::<Account>(json).unwrap();
+    assert_eq!(acc.sma, Some(Num::new(1355325, 10)));
+    assert_eq!(acc.regt_buying_power, Some(Num::new(262113632, 1000)));
+    assert_eq!(
+      acc.non_marginable_buying_power,
+      Some(Num::new(13105682, 100))
+    );
+  }
+
   /// Test that we can retrieve information about the account.
   #[test(tokio::test)]
   async fn request_account() {
@@ -1,6 +1,9 @@
 // Copyright (C) 2020-2025 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::BTreeMap;
+use std::ops::Range;
+
 use chrono::DateTime;
 use chrono::NaiveDate;
 use chrono::TimeZone as _;
@@ -18,9 +21,16 @@ use crate::api::v2::de::TaggedContentVisitor;
 use crate::api::v2::order;
 use crate::util::abs_num_from_str;
 use crate::util::enum_slice_to_str;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
+/// The maximum number of activities Alpaca reports in a single
+/// response.
+const MAX_PAGE_SIZE: usize = 100;
+
+
 /// Deserialize a `DateTime<Utc>` from a simple date.
 fn datetime_from_date_str<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
@@ -148,6 +158,25 @@ pub enum ActivityType {
   Unknown,
 }
 
+impl ActivityType {
+  /// Check whether the activity type represents a dividend payment or
+  /// an adjustment thereof (e.g., withheld taxes or fees).
+  pub fn is_dividend(&self) -> bool {
+    matches!(
+      self,
+      Self::Dividend
+        | Self::CapitalGainLongTerm
+        | Self::CapitalGainShortTerm
+        | Self::DividendFee
+        | Self::DividendAdjusted
+        | Self::DividendAdjustedNraWithheld
+        | Self::DividendReturnOfCapital
+        | Self::DividendAdjustedTefraWithheld
+        | Self::DividendTaxExtempt
+    )
+  }
+}
+
 
 /// An enumeration describing the side of a trade activity.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
@@ -176,6 +205,15 @@ pub struct TradeActivity {
   /// The time at which the execution occurred.
   #[serde(rename = "transaction_time")]
   pub transaction_time: DateTime<Utc>,
+  /// The date on which the trade settles, i.e., on which the
+  /// associated cash becomes available for settled-cash purposes.
+  ///
+  /// Alpaca does not report this date for all fill activities. For
+  /// such cases the settlement date has to be derived from
+  /// `transaction_time` and the applicable settlement cycle (T+1 for
+  /// US equities).
+  #[serde(rename = "date", default)]
+  pub date: Option<NaiveDate>,
   /// The traded symbol.
   #[serde(rename = "symbol")]
   pub symbol: String,
@@ -362,6 +400,34 @@ impl<'de> Deserialize<'de> for Activity {
 }
 
 
+/// Sum up the net dividend income per symbol over the given date
+/// range.
+///
+/// All dividend related activities (see [`ActivityType::is_dividend`])
+/// dated within `range` are considered, meaning that withheld taxes
+/// and fees reduce the reported income. Activities not associated
+/// with a symbol are ignored.
+pub fn dividend_income<'a, I>(activities: I, range: Range<NaiveDate>) -> BTreeMap<String, Num>
+where
+  I: IntoIterator<Item = &'a Activity>,
+{
+  activities
+    .into_iter()
+    .filter_map(|activity| match activity {
+      Activity::Trade(..) => None,
+      Activity::NonTrade(non_trade) => Some(non_trade),
+    })
+    .filter(|non_trade| non_trade.type_.is_dividend())
+    .filter(|non_trade| range.contains(&non_trade.date.date_naive()))
+    .fold(BTreeMap::new(), |mut income, non_trade| {
+      if let Some(symbol) = &non_trade.symbol {
+        *income.entry(symbol.clone()).or_default() += &non_trade.net_amount;
+      }
+      income
+    })
+}
+
+
 /// The direction in which account activities are reported.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
 pub enum Direction {
@@ -400,6 +466,11 @@ pub struct ActivityReq {
   /// The response will contain only activities dated after this time.
   #[serde(rename = "after")]
   pub after: Option<DateTime<Utc>>,
+  /// The response will contain only activities for this date.
+  ///
+  /// This filter cannot be combined with `until` or `after`.
+  #[serde(rename = "date")]
+  pub date: Option<NaiveDate>,
   /// The maximum number of entries to return in the response.
   ///
   /// The default and maximum value is 100.
@@ -436,12 +507,55 @@ Endpoint! {
 }
 
 
+/// Retrieve all activities of the given date, paging through results
+/// in chunks of at most `page_size`.
+async fn activities_for_paged(
+  client: &Client,
+  date: NaiveDate,
+  page_size: usize,
+) -> Result<Vec<Activity>, RequestError<GetError>> {
+  let mut request = ActivityReq {
+    date: Some(date),
+    page_size: Some(page_size),
+    ..Default::default()
+  };
+  let mut activities = Vec::new();
+
+  loop {
+    let page = client.issue::<Get>(&request).await?;
+    let count = page.len();
+    activities.extend(page);
+
+    match activities.last() {
+      Some(last) if count >= page_size => request.page_token = Some(last.id().to_string()),
+      _ => break,
+    }
+  }
+  Ok(activities)
+}
+
+
+/// Retrieve all activities (of all types) of the given date, most
+/// recent first.
+///
+/// Filtering by date happens on the server side and all pages of the
+/// result are retrieved.
+pub async fn activities_for(
+  client: &Client,
+  date: NaiveDate,
+) -> Result<Vec<Activity>, RequestError<GetError>> {
+  activities_for_paged(client, date, MAX_PAGE_SIZE).await
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use chrono::Duration;
 
+  use http::StatusCode;
+
   use serde_json::from_str as from_json;
 
   use test_log::test;
@@ -449,7 +563,7 @@ mod tests {
   use uuid::Uuid;
 
   use crate::api_info::ApiInfo;
-  use crate::Client;
+  use crate::mock::Server;
 
 
   #[test]
@@ -481,6 +595,38 @@ mod tests {
     assert_eq!(trade.cumulative_quantity, Num::new(3, 2));
     assert_eq!(trade.unfilled_quantity, Num::from(0));
     assert_eq!(trade.price, Num::new(163, 100));
+    assert_eq!(trade.date, None);
+  }
+
+  /// Check that we can parse a fill activity that reports its
+  /// settlement date in addition to the execution time.
+  #[test]
+  fn parse_trade_activity_with_settle_date() {
+    let response = r#"{
+  "activity_type": "FILL",
+  "cum_qty": "10",
+  "id": "20240605155105591::c6bb8b86-a4be-4c4e-9e40-c0f79cdb2f7b",
+  "leaves_qty": "0",
+  "price": "192.25",
+  "qty": "10",
+  "side": "sell",
+  "symbol": "AAPL",
+  "transaction_time": "2024-06-05T19:51:05.591Z",
+  "date": "2024-06-06",
+  "order_id": "904837e3-3b76-47ec-b432-046db621571b",
+  "type": "fill"
+}"#;
+
+    let trade = from_json::<Activity>(response)
+      .unwrap()
+      .into_trade()
+      .unwrap();
+
+    assert_eq!(
+      trade.transaction_time,
+      DateTime::parse_from_rfc3339("2024-06-05T19:51:05.591Z").unwrap()
+    );
+    assert_eq!(trade.date, NaiveDate::from_ymd_opt(2024, 6, 6));
   }
 
   #[test]
@@ -538,6 +684,80 @@ mod tests {
     assert_eq!(non_trade.per_share_amount, Some(Num::new(108783, 1000000)));
   }
 
+  /// Check that we can sum up the net dividend income per symbol.
+  #[test]
+  fn sum_dividend_income() {
+    let response = r#"[
+  {"id":"1","activity_type":"DIV","date":"2020-01-02","net_amount":"21.97",
+   "symbol":"SPY","qty":"201.96","per_share_amount":"0.108783"},
+  {"id":"2","activity_type":"DIVNRA","date":"2020-01-02","net_amount":"-3.29",
+   "symbol":"SPY","qty":"201.96","per_share_amount":"0.016317"},
+  {"id":"3","activity_type":"DIV","date":"2020-01-15","net_amount":"1.02",
+   "symbol":"T","qty":"2","per_share_amount":"0.51"},
+  {"id":"4","activity_type":"CSD","date":"2020-01-20","net_amount":"1000"},
+  {"id":"5","activity_type":"DIV","date":"2020-02-01","net_amount":"5.00",
+   "symbol":"T","qty":"10","per_share_amount":"0.5"},
+  {"activity_type":"FILL","cum_qty":"1","id":"6","leaves_qty":"0",
+   "price":"1.63","qty":"1","side":"buy","symbol":"T",
+   "transaction_time":"2020-01-03T15:34:06.977Z",
+   "order_id":"904837e3-3b76-47ec-b432-046db621571b","type":"fill"}
+]"#;
+
+    let activities = from_json::<Vec<Activity>>(response).unwrap();
+    let dividend = activities[2].clone().into_non_trade().unwrap();
+    assert_eq!(dividend.symbol.as_deref(), Some("T"));
+    assert_eq!(dividend.quantity, Some(Num::from(2)));
+    assert_eq!(dividend.per_share_amount, Some(Num::new(51, 100)));
+    assert_eq!(dividend.net_amount, Num::new(102, 100));
+
+    let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+    let end = NaiveDate::from_ymd_opt(2020, 2, 1).unwrap();
+    let income = dividend_income(&activities, start..end);
+    assert_eq!(income.len(), 2);
+    assert_eq!(income["SPY"], Num::new(1868, 100));
+    assert_eq!(income["T"], Num::new(102, 100));
+  }
+
+  /// Check that we can retrieve all activities of a single date,
+  /// paging through the results.
+  #[test(tokio::test)]
+  async fn activities_for_date() {
+    let server = Server::serve(|request| {
+      let body = if request.contains("page_token=2") {
+        r#"[
+  {"id":"1","activity_type":"CSD","date":"2024-06-05","net_amount":"1000"}
+]"#
+      } else {
+        r#"[
+  {"activity_type":"FILL","cum_qty":"1","id":"3","leaves_qty":"0",
+   "price":"1.63","qty":"1","side":"buy","symbol":"T",
+   "transaction_time":"2024-06-05T15:34:06.977Z",
+   "order_id":"904837e3-3b76-47ec-b432-046db621571b","type":"fill"},
+  {"id":"2","activity_type":"DIV","date":"2024-06-05","net_amount":"1.02",
+   "symbol":"T","qty":"2","per_share_amount":"0.51"}
+]"#
+      };
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let date = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+    let activities = activities_for_paged(&client, date, 2).await.unwrap();
+    let ids = activities.iter().map(Activity::id).collect::<Vec<_>>();
+    assert_eq!(ids, ["3", "2", "1"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(
+      requests
+        .iter()
+        .all(|request| request.contains("date=2024-06-05"))
+    );
+    assert!(!requests[0].contains("page_token"));
+    assert!(requests[1].contains("page_token=2"));
+  }
+
   #[test(tokio::test)]
   async fn retrieve_some_activities() {
     let api_info = ApiInfo::from_env().unwrap();
@@ -24,6 +24,22 @@ pub enum TradeConfirmation {
 }
 
 
+/// An enum representing the possible day trade buying power checks.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum DayTradeBuyingPowerCheck {
+  /// Check day trade buying power on both order entry and exit.
+  #[serde(rename = "both")]
+  Both,
+  /// Check day trade buying power on order entry only.
+  #[serde(rename = "entry")]
+  Entry,
+  /// Check day trade buying power on order exit only.
+  #[serde(rename = "exit")]
+  Exit,
+}
+
+
 /// A response as returned by the /v2/account/configurations endpoint.
 // TODO: Not all fields are hooked up yet.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -37,12 +53,57 @@ pub struct Configuration {
   /// If enabled, the account can only submit buy orders.
   #[serde(rename = "no_shorting")]
   pub no_shorting: bool,
+  /// When day trade buying power is checked.
+  #[serde(rename = "dtbp_check")]
+  pub dtbp_check: DayTradeBuyingPowerCheck,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A PATCH request to be made to the /v2/account/configurations
+/// endpoint.
+///
+/// Only settings that are not `None` are changed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChangeReq {
+  /// See `Configuration::trade_confirmation`.
+  #[serde(
+    rename = "trade_confirm_email",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub trade_confirmation: Option<TradeConfirmation>,
+  /// See `Configuration::trading_suspended`.
+  #[serde(rename = "suspend_trade", skip_serializing_if = "Option::is_none")]
+  pub trading_suspended: Option<bool>,
+  /// See `Configuration::no_shorting`.
+  #[serde(rename = "no_shorting", skip_serializing_if = "Option::is_none")]
+  pub no_shorting: Option<bool>,
+  /// See `Configuration::dtbp_check`.
+  #[serde(rename = "dtbp_check", skip_serializing_if = "Option::is_none")]
+  pub dtbp_check: Option<DayTradeBuyingPowerCheck>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl From<Configuration> for ChangeReq {
+  /// Create a request changing all settings to the values of the
+  /// provided configuration.
+  fn from(config: Configuration) -> Self {
+    Self {
+      trade_confirmation: Some(config.trade_confirmation),
+      trading_suspended: Some(config.trading_suspended),
+      no_shorting: Some(config.no_shorting),
+      dtbp_check: Some(config.dtbp_check),
+      _non_exhaustive: (),
+    }
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the
@@ -64,7 +125,7 @@ Endpoint! {
 Endpoint! {
   /// The representation of a PATCH request to the
   /// /v2/account/configurations endpoint.
-  pub Change(Configuration),
+  pub Change(ChangeReq),
   Ok => Configuration, [
     /// The account configuration was updated successfully.
     /* 200 */ OK,
@@ -117,6 +178,43 @@ mod tests {
     assert_eq!(config.trade_confirmation, TradeConfirmation::Email);
     assert!(!config.trading_suspended);
     assert!(!config.no_shorting);
+    assert_eq!(config.dtbp_check, DayTradeBuyingPowerCheck::Entry);
+  }
+
+  /// Check that only the settings to change are serialized in a
+  /// `ChangeReq`.
+  #[test]
+  fn serialize_change_request() {
+    let request = ChangeReq {
+      trading_suspended: Some(true),
+      ..Default::default()
+    };
+    let json = to_json(&request).unwrap();
+    assert_eq!(json, br#"{"suspend_trade":true}"#);
+
+    let config = Configuration {
+      trade_confirmation: TradeConfirmation::None,
+      trading_suspended: false,
+      no_shorting: true,
+      dtbp_check: DayTradeBuyingPowerCheck::Both,
+      _non_exhaustive: (),
+    };
+    let json = to_json(&ChangeReq::from(config)).unwrap();
+    let expected = r#"{"trade_confirm_email":"none","suspend_trade":false,"no_shorting":true,"dtbp_check":"both"}"#;
+    assert_eq!(json, expected.as_bytes());
+  }
+
+  /// Check that both trade confirmation settings map to the values
+  /// used by Alpaca.
+  #[test]
+  fn serialize_deserialize_trade_confirmation() {
+    for (confirmation, json) in [
+      (TradeConfirmation::Email, r#""all""#),
+      (TradeConfirmation::None, r#""none""#),
+    ] {
+      assert_eq!(to_json(&confirmation).unwrap(), json.as_bytes());
+      assert_eq!(from_json::<TradeConfirmation>(json).unwrap(), confirmation);
+    }
   }
 
   #[test(tokio::test)]
@@ -136,11 +234,16 @@ mod tests {
       trade_confirmation: new_confirmation,
       ..config
     };
-    let change_result = client.issue::<Change>(&changed).await;
+    let request = ChangeReq {
+      trade_confirmation: Some(new_confirmation),
+      ..Default::default()
+    };
+    let change_result = client.issue::<Change>(&request).await;
     // Also retrieve the configuration again.
     let get_result = client.issue::<Get>(&()).await;
     // Revert back to the original setting.
-    let reverted = client.issue::<Change>(&config).await.unwrap();
+    let request = ChangeReq::from(config);
+    let reverted = client.issue::<Change>(&request).await.unwrap();
 
     assert_eq!(change_result.unwrap(), changed);
     assert_eq!(get_result.unwrap(), changed);
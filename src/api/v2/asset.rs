@@ -12,9 +12,15 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde::Serializer;
 
+use thiserror::Error;
+
 use uuid::Error as UuidError;
 use uuid::Uuid;
 
+use crate::api::v2::account;
+use crate::api::v2::account_config;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -31,6 +37,22 @@ impl Deref for Id {
   }
 }
 
+impl Display for Id {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    Display::fmt(&self.0.as_hyphenated(), fmt)
+  }
+}
+
+impl FromStr for Id {
+  type Err = UuidError;
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Uuid::parse_str(s).map(Self)
+  }
+}
+
 
 /// An enumeration of the various asset classes available.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -341,7 +363,14 @@ pub struct Asset {
   /// The asset's symbol.
   #[serde(rename = "symbol")]
   pub symbol: String,
+  /// The name of the asset.
+  #[serde(rename = "name", default)]
+  pub name: String,
   /// The asset's status.
+  ///
+  /// Assets that got delisted or are otherwise no longer traded are
+  /// reported as [`Status::Inactive`] and are only included in asset
+  /// listings when explicitly requested.
   #[serde(rename = "status")]
   pub status: Status,
   /// Whether the asset is tradable on Alpaca or not.
@@ -361,6 +390,10 @@ pub struct Asset {
   /// Whether the asset is fractionable or not.
   #[serde(rename = "fractionable")]
   pub fractionable: bool,
+  /// Additional attributes of the asset (e.g., "ptp_no_exception" or
+  /// "has_options").
+  #[serde(rename = "attributes", default)]
+  pub attributes: Vec<String>,
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
@@ -386,6 +419,105 @@ Endpoint! {
 }
 
 
+/// A reason preventing an asset from being shorted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ShortBlocker {
+  /// Shorting is not enabled for the account, e.g., because it is not
+  /// a margin account or its equity is too low.
+  AccountShortingDisabled,
+  /// Shorting was disabled through the account configuration (the
+  /// `no_shorting` setting).
+  NoShortingConfigured,
+  /// The asset is not tradable on Alpaca.
+  NotTradable,
+  /// The asset is not shortable.
+  NotShortable,
+  /// The asset is not easy-to-borrow.
+  NotEasyToBorrow,
+}
+
+
+/// A verdict on whether an asset can be shorted, as reported by
+/// [`can_short`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Shortability {
+  /// The reasons preventing the asset from being shorted. An empty
+  /// list means that shorting is possible.
+  pub blockers: Vec<ShortBlocker>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl Shortability {
+  /// Combine the relevant account and asset flags into a verdict.
+  fn evaluate(shorting_enabled: bool, no_shorting: bool, asset: &Asset) -> Self {
+    let checks = [
+      (!shorting_enabled, ShortBlocker::AccountShortingDisabled),
+      (no_shorting, ShortBlocker::NoShortingConfigured),
+      (!asset.tradable, ShortBlocker::NotTradable),
+      (!asset.shortable, ShortBlocker::NotShortable),
+      (!asset.easy_to_borrow, ShortBlocker::NotEasyToBorrow),
+    ];
+
+    Self {
+      blockers: checks
+        .into_iter()
+        .filter_map(|(blocked, blocker)| blocked.then_some(blocker))
+        .collect(),
+      _non_exhaustive: (),
+    }
+  }
+
+  /// Check whether the asset can be shorted.
+  #[inline]
+  pub fn is_shortable(&self) -> bool {
+    self.blockers.is_empty()
+  }
+}
+
+
+/// An error as reported by [`can_short`].
+#[derive(Debug, Error)]
+pub enum CanShortError {
+  /// Failed to retrieve the account.
+  #[error("failed to retrieve account")]
+  Account(#[source] RequestError<account::GetError>),
+  /// Failed to retrieve the account configuration.
+  #[error("failed to retrieve account configuration")]
+  Configuration(#[source] RequestError<account_config::GetError>),
+  /// Failed to retrieve the asset.
+  #[error("failed to retrieve asset")]
+  Asset(#[source] RequestError<GetError>),
+}
+
+
+/// Check whether the asset identified by `symbol` can currently be
+/// shorted, taking into account both the account (and its
+/// configuration) and the asset itself.
+pub async fn can_short(client: &Client, symbol: &Symbol) -> Result<Shortability, CanShortError> {
+  let account = client
+    .issue::<account::Get>(&())
+    .await
+    .map_err(CanShortError::Account)?;
+  let config = client
+    .issue::<account_config::Get>(&())
+    .await
+    .map_err(CanShortError::Configuration)?;
+  let asset = client
+    .issue::<Get>(symbol)
+    .await
+    .map_err(CanShortError::Asset)?;
+
+  Ok(Shortability::evaluate(
+    account.shorting_enabled,
+    config.no_shorting,
+    &asset,
+  ))
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -494,6 +626,73 @@ mod tests {
     assert!(asset.easy_to_borrow);
   }
 
+  /// Check that we arrive at the expected shortability verdicts for a
+  /// shortable and a non-shortable asset.
+  #[test]
+  fn shortability_verdict() {
+    let response = r#"{
+  "id": "904837e3-3b76-47ec-b432-046db621571b",
+  "class": "us_equity",
+  "exchange": "NASDAQ",
+  "symbol": "AAPL",
+  "status": "active",
+  "tradable": true,
+  "marginable": true,
+  "shortable": true,
+  "easy_to_borrow": true,
+  "fractionable": true
+}"#;
+    let mut asset = from_json::<Asset>(response).unwrap();
+
+    let verdict = Shortability::evaluate(true, false, &asset);
+    assert!(verdict.is_shortable());
+
+    let verdict = Shortability::evaluate(false, true, &asset);
+    assert!(!verdict.is_shortable());
+    assert_eq!(
+      verdict.blockers,
+      vec![
+        ShortBlocker::AccountShortingDisabled,
+        ShortBlocker::NoShortingConfigured
+      ]
+    );
+
+    asset.shortable = false;
+    asset.easy_to_borrow = false;
+    let verdict = Shortability::evaluate(true, false, &asset);
+    assert_eq!(
+      verdict.blockers,
+      vec![ShortBlocker::NotShortable, ShortBlocker::NotEasyToBorrow]
+    );
+  }
+
+  /// Check that we can parse an inactive (delisted) asset object.
+  #[test]
+  fn parse_inactive_asset() {
+    let response = r#"{
+  "id": "69b15845-7c63-4586-b274-1cfdfe9df3d8",
+  "class": "us_equity",
+  "exchange": "NYSE",
+  "symbol": "TWTR",
+  "name": "Twitter, Inc. Common Stock",
+  "status": "inactive",
+  "tradable": false,
+  "marginable": false,
+  "maintenance_margin_requirement": 100,
+  "shortable": false,
+  "easy_to_borrow": false,
+  "fractionable": false,
+  "attributes": []
+}"#;
+
+    let asset = from_json::<Asset>(response).unwrap();
+    assert_eq!(asset.symbol, "TWTR");
+    assert_eq!(asset.name, "Twitter, Inc. Common Stock");
+    assert_eq!(asset.status, Status::Inactive);
+    assert!(!asset.tradable);
+    assert_eq!(asset.attributes, Vec::<String>::new());
+  }
+
   /// Verify that we can parse an asset object with an unknown exchange.
   #[test]
   fn parse_with_unknown_exchange() {
@@ -528,6 +727,20 @@ mod tests {
     assert_eq!(from_json::<Asset>(&json).unwrap(), asset);
   }
 
+  /// Check that we can parse, display, and serialize an `Id`.
+  #[test]
+  fn id_round_trip() {
+    let string = "904837e3-3b76-47ec-b432-046db621571b";
+    let id = Id::from_str(string).unwrap();
+    assert_eq!(id.to_string(), string);
+
+    let json = to_json(&id).unwrap();
+    assert_eq!(json, format!(r#""{string}""#));
+    assert_eq!(from_json::<Id>(&json).unwrap(), id);
+
+    assert!(Id::from_str("AAPL").is_err());
+  }
+
   /// Check that we can create a `Symbol` from an `Id`.
   #[test]
   fn symbol_from_id() {
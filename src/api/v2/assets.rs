@@ -8,6 +8,8 @@ use serde_urlencoded::to_string as to_query;
 use crate::api::v2::asset::Asset;
 use crate::api::v2::asset::Class;
 use crate::api::v2::asset::Status;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -47,6 +49,64 @@ Endpoint! {
 }
 
 
+/// A filter for selecting tradable assets.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Filter {
+  /// The status of assets to select.
+  pub status: Status,
+  /// The asset class of which to select assets.
+  pub class: Class,
+  /// If set, only select assets that are (or are not) fractionable.
+  pub fractionable: Option<bool>,
+  /// If set, only select assets that are (or are not) shortable.
+  pub shortable: Option<bool>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl Filter {
+  /// Check whether the given asset is tradable and matches the filter.
+  pub fn matches(&self, asset: &Asset) -> bool {
+    asset.tradable
+      && asset.status == self.status
+      && asset.class == self.class
+      && self
+        .fractionable
+        .is_none_or(|fractionable| asset.fractionable == fractionable)
+      && self
+        .shortable
+        .is_none_or(|shortable| asset.shortable == shortable)
+  }
+}
+
+
+/// Retrieve the symbols of all tradable assets matching the provided
+/// filter.
+///
+/// This function is a short-hand for issuing a [`List`] request and
+/// applying [`Filter::matches`] to the reported assets.
+pub async fn tradable_symbols(
+  client: &Client,
+  filter: &Filter,
+) -> Result<Vec<String>, RequestError<ListError>> {
+  let request = ListReq {
+    status: filter.status,
+    class: filter.class,
+    ..Default::default()
+  };
+
+  let symbols = client
+    .issue::<List>(&request)
+    .await?
+    .into_iter()
+    .filter(|asset| filter.matches(asset))
+    .map(|asset| asset.symbol)
+    .collect();
+  Ok(symbols)
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -54,6 +114,8 @@ mod tests {
   use serde_json::from_slice as from_json;
   use serde_json::to_vec as to_json;
 
+  use http_endpoint::Endpoint;
+
   use test_log::test;
 
   use crate::api::v2::asset::Exchange;
@@ -75,6 +137,67 @@ mod tests {
   }
 
 
+  /// Check that inactive assets can be requested.
+  #[test]
+  fn inactive_query() {
+    let request = ListReq {
+      status: Status::Inactive,
+      ..Default::default()
+    };
+
+    let query = <List as Endpoint>::query(&request).unwrap().unwrap();
+    assert_eq!(query, "status=inactive&asset_class=us_equity");
+  }
+
+  /// Check that we can filter assets by fractionability.
+  #[test]
+  fn filter_fractionable() {
+    let json = br#"[
+  {"id":"904837e3-3b76-47ec-b432-046db621571b","class":"us_equity",
+   "exchange":"NASDAQ","symbol":"AAPL","status":"active","tradable":true,
+   "marginable":true,"shortable":true,"easy_to_borrow":true,"fractionable":true},
+  {"id":"904837e3-3b76-47ec-b432-046db621571c","class":"us_equity",
+   "exchange":"NYSE","symbol":"BRK.A","status":"active","tradable":true,
+   "marginable":true,"shortable":true,"easy_to_borrow":true,"fractionable":false},
+  {"id":"904837e3-3b76-47ec-b432-046db621571d","class":"us_equity",
+   "exchange":"NASDAQ","symbol":"XYZ","status":"active","tradable":false,
+   "marginable":false,"shortable":false,"easy_to_borrow":false,"fractionable":true},
+  {"id":"904837e3-3b76-47ec-b432-046db621571e","class":"us_equity",
+   "exchange":"NYSE","symbol":"GME","status":"active","tradable":true,
+   "marginable":true,"shortable":false,"easy_to_borrow":false,"fractionable":true}
+]"#;
+    let assets = from_json::<Vec<Asset>>(json).unwrap();
+    let select = |filter: Filter| {
+      assets
+        .iter()
+        .filter(|asset| filter.matches(asset))
+        .map(|asset| asset.symbol.as_str())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(select(Filter::default()), vec!["AAPL", "BRK.A", "GME"]);
+
+    let filter = Filter {
+      fractionable: Some(true),
+      ..Default::default()
+    };
+    assert_eq!(select(filter), vec!["AAPL", "GME"]);
+
+    let filter = Filter {
+      fractionable: Some(true),
+      shortable: Some(true),
+      ..Default::default()
+    };
+    assert_eq!(select(filter), vec!["AAPL"]);
+
+    let filter = Filter {
+      fractionable: Some(false),
+      ..Default::default()
+    };
+    assert_eq!(select(filter), vec!["BRK.A"]);
+  }
+
+
   /// Make sure that we can list available US stock assets.
   #[test(tokio::test)]
   async fn list_us_stock_assets() {
@@ -14,6 +14,8 @@ use serde::Serialize;
 use serde::Serializer;
 use serde_urlencoded::to_string as to_query;
 
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -140,6 +142,26 @@ Endpoint! {
 }
 
 
+/// Retrieve the dates of all trading days in the range `[start, end)`.
+///
+/// Days on which the market is closed (weekends and holidays) are not
+/// reported.
+pub async fn trading_days_between(
+  client: &Client,
+  start: NaiveDate,
+  end: NaiveDate,
+) -> Result<Vec<NaiveDate>, RequestError<ListError>> {
+  let request = ListReq::from(start..end);
+  let days = client
+    .issue::<List>(&request)
+    .await?
+    .into_iter()
+    .map(|open_close| open_close.date)
+    .collect();
+  Ok(days)
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -214,4 +236,23 @@ mod tests {
 
     assert_eq!(calendar, expected);
   }
+
+  /// Check that we can retrieve the trading days in a range spanning a
+  /// market holiday.
+  #[test(tokio::test)]
+  async fn trading_days_around_holiday() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    // April 10th 2020 was Good Friday, on which the market was closed.
+    let start = NaiveDate::from_ymd_opt(2020, 4, 6).unwrap();
+    let end = NaiveDate::from_ymd_opt(2020, 4, 15).unwrap();
+    let days = trading_days_between(&client, start, end).await.unwrap();
+
+    let expected = [6, 7, 8, 9, 13, 14]
+      .into_iter()
+      .map(|day| NaiveDate::from_ymd_opt(2020, 4, day).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(days, expected);
+  }
 }
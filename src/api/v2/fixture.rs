@@ -0,0 +1,74 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use serde_json::json;
+use serde_json::Value;
+
+
+/// Override the members of the given JSON object with the provided
+/// ones.
+fn with_members(mut object: Value, members: Value) -> Value {
+  let Value::Object(members) = members else {
+    panic!("fixture members are not a JSON object: {members}")
+  };
+  let () = object.as_object_mut().unwrap().extend(members);
+  object
+}
+
+
+/// Create the JSON representation of a new market order to buy ten
+/// shares of AAPL, with the given members overriding the defaults.
+pub(crate) fn order_json(members: Value) -> Value {
+  let order = json!({
+    "id": "904837e3-3b76-47ec-b432-046db621571b",
+    "client_order_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "created_at": "2018-10-05T05:48:59Z",
+    "updated_at": "2018-10-05T05:48:59Z",
+    "submitted_at": "2018-10-05T05:48:59Z",
+    "filled_at": null,
+    "expired_at": null,
+    "canceled_at": null,
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "asset_class": "us_equity",
+    "qty": "10",
+    "filled_qty": "0",
+    "type": "market",
+    "order_class": "simple",
+    "side": "buy",
+    "time_in_force": "day",
+    "limit_price": null,
+    "stop_price": null,
+    "filled_avg_price": null,
+    "status": "new",
+    "extended_hours": false,
+    "legs": null,
+  });
+  with_members(order, members)
+}
+
+
+/// Create the JSON representation of a long position of ten shares of
+/// AAPL, with the given members overriding the defaults.
+pub(crate) fn position_json(members: Value) -> Value {
+  let position = json!({
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "qty": "10",
+    "qty_available": "10",
+    "avg_entry_price": "100.0",
+    "side": "long",
+    "market_value": "1000.0",
+    "cost_basis": "1000.0",
+    "unrealized_pl": "0",
+    "unrealized_plpc": "0",
+    "unrealized_intraday_pl": "0",
+    "unrealized_intraday_plpc": "0",
+    "current_price": "100.0",
+    "lastday_price": "100.0",
+    "change_today": "0",
+  });
+  with_members(position, members)
+}
@@ -26,6 +26,7 @@ pub mod position;
 /// Functionality for listing open positions.
 pub mod positions;
 /// Definitions for trade related updates.
+#[cfg(feature = "streaming")]
 pub mod updates;
 /// Definitions surrounding watchlists.
 pub mod watchlist;
@@ -34,5 +35,7 @@ pub mod watchlists;
 
 mod de;
 
+#[cfg(test)]
+pub(crate) mod fixture;
 #[cfg(test)]
 mod order_util;
@@ -1,8 +1,14 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 use std::ops::Deref;
 use std::ops::Not;
+use std::str::FromStr;
+use std::time::Duration;
 
 use chrono::DateTime;
 use chrono::Utc;
@@ -16,17 +22,33 @@ use serde::de::IntoDeserializer;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
-use serde_json::from_slice as from_json;
+use serde_json::to_value as to_json_value;
 use serde_json::to_vec as to_json;
 use serde_urlencoded::to_string as to_query;
 
+use thiserror::Error;
+
+use tokio::time::sleep;
+use tokio::time::Instant;
+
+use uuid::Error as UuidError;
 use uuid::Uuid;
 
 use crate::api::v2::asset;
+use crate::endpoint::ApiError;
+use crate::endpoint::ConversionError;
+use crate::time_source::SystemClock;
+use crate::time_source::TimeSource;
 use crate::util::vec_from_str;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
+/// The error code used by Alpaca for reporting invalid input.
+const ERROR_CODE_INVALID_INPUT: u64 = 40010001;
+
+
 /// An ID uniquely identifying an order.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Id(pub Uuid);
@@ -40,6 +62,22 @@ impl Deref for Id {
   }
 }
 
+impl Display for Id {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    Display::fmt(&self.0.as_hyphenated(), fmt)
+  }
+}
+
+impl FromStr for Id {
+  type Err = UuidError;
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Uuid::parse_str(s).map(Self)
+  }
+}
+
 
 /// The status an order can have.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -186,6 +224,10 @@ pub enum Class {
   /// entry order.
   #[serde(rename = "oto")]
   OneTriggersOther,
+  /// A multi-leg order, e.g., an options spread, with each leg being
+  /// described by a [`Leg`] object.
+  #[serde(rename = "mleg")]
+  MultiLeg,
 }
 
 impl Default for Class {
@@ -196,6 +238,76 @@ impl Default for Class {
 }
 
 
+/// The intent of an order with respect to the position it affects.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum PositionIntent {
+  /// Buy to open a new or increase an existing long position.
+  #[serde(rename = "buy_to_open")]
+  BuyToOpen,
+  /// Buy to close or reduce an existing short position.
+  #[serde(rename = "buy_to_close")]
+  BuyToClose,
+  /// Sell to open a new or increase an existing short position.
+  #[serde(rename = "sell_to_open")]
+  SellToOpen,
+  /// Sell to close or reduce an existing long position.
+  #[serde(rename = "sell_to_close")]
+  SellToClose,
+}
+
+
+/// A single leg of a multi-leg order.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Leg {
+  /// The symbol of the asset traded by the leg.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The side the leg is on.
+  #[serde(rename = "side")]
+  pub side: Side,
+  /// The intent of the leg with respect to the position it affects.
+  #[serde(rename = "position_intent", skip_serializing_if = "Option::is_none")]
+  pub position_intent: Option<PositionIntent>,
+  /// The ratio quantity of the leg, relative to the other legs of the
+  /// order. Has to be a positive integer.
+  #[serde(rename = "ratio_qty")]
+  pub ratio_quantity: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`Leg`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct LegInit {
+  /// See `Leg::position_intent`.
+  pub position_intent: Option<PositionIntent>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl LegInit {
+  /// Create a [`Leg`] from a `LegInit`.
+  pub fn init<S>(self, symbol: S, side: Side, ratio_quantity: Num) -> Leg
+  where
+    S: Into<String>,
+  {
+    Leg {
+      symbol: symbol.into(),
+      side,
+      position_intent: self.position_intent,
+      ratio_quantity,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
 /// The type of an order.
 // Note that we currently do not support `stop_limit` orders.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -207,7 +319,8 @@ pub enum Type {
   /// A limit order.
   #[serde(rename = "limit")]
   Limit,
-  /// A stop on quote order.
+  /// A stop on quote order, which becomes a market order once the
+  /// stop price is reached.
   #[serde(rename = "stop")]
   Stop,
   /// A stop limit order.
@@ -236,6 +349,10 @@ pub enum TimeInForce {
   Day,
   /// The order is only executed if the entire order quantity can
   /// be filled, otherwise the order is canceled.
+  ///
+  /// Alpaca does not support all-or-none orders that rest on the book
+  /// until they can be filled in full. Fill-or-kill is the closest
+  /// primitive for preventing partial fills.
   #[serde(rename = "fok")]
   FillOrKill,
   /// The order requires all or part of the order to be executed
@@ -253,6 +370,10 @@ pub enum TimeInForce {
   /// auction. Any unfilled orders after the close will be canceled.
   #[serde(rename = "cls")]
   UntilMarketClose,
+  /// The order is good until the date and time specified in
+  /// [`CreateReq::expires_at`] (good-till-date).
+  #[serde(rename = "gtd")]
+  UntilDate,
 }
 
 impl Default for TimeInForce {
@@ -299,47 +420,78 @@ impl From<TakeProfit> for TakeProfitSerde {
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename = "stop_loss")]
 struct StopLossSerde {
-  #[serde(rename = "stop_price")]
-  stop_price: Num,
+  #[serde(rename = "stop_price", skip_serializing_if = "Option::is_none")]
+  stop_price: Option<Num>,
   #[serde(rename = "limit_price", skip_serializing_if = "Option::is_none")]
   limit_price: Option<Num>,
+  #[serde(rename = "trail_price", skip_serializing_if = "Option::is_none")]
+  trail_price: Option<Num>,
+  #[serde(rename = "trail_percent", skip_serializing_if = "Option::is_none")]
+  trail_percent: Option<Num>,
 }
 
 
 /// The stop loss part of a bracket, one-cancels-other, or
 /// one-triggers-other order.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-#[serde(from = "StopLossSerde", into = "StopLossSerde")]
+#[serde(try_from = "StopLossSerde", into = "StopLossSerde")]
 #[non_exhaustive]
 pub enum StopLoss {
   /// The stop loss price to use.
   Stop(Num),
   /// The stop loss and stop limit price to use.
   StopLimit(Num, Num),
+  /// A trailing stop loss, trailing the high water mark by the given
+  /// dollar offset.
+  TrailPrice(Num),
+  /// A trailing stop loss, trailing the high water mark by the given
+  /// percentage.
+  TrailPercent(Num),
 }
 
-impl From<StopLossSerde> for StopLoss {
-  fn from(other: StopLossSerde) -> Self {
-    if let Some(limit_price) = other.limit_price {
-      Self::StopLimit(other.stop_price, limit_price)
-    } else {
-      Self::Stop(other.stop_price)
+impl TryFrom<StopLossSerde> for StopLoss {
+  type Error = Str;
+
+  fn try_from(other: StopLossSerde) -> Result<Self, Self::Error> {
+    match (
+      other.stop_price,
+      other.limit_price,
+      other.trail_price,
+      other.trail_percent,
+    ) {
+      (Some(stop_price), None, None, None) => Ok(Self::Stop(stop_price)),
+      (Some(stop_price), Some(limit_price), None, None) => {
+        Ok(Self::StopLimit(stop_price, limit_price))
+      },
+      (None, None, Some(trail_price), None) => Ok(Self::TrailPrice(trail_price)),
+      (None, None, None, Some(trail_percent)) => Ok(Self::TrailPercent(trail_percent)),
+      (_, _, Some(..), Some(..)) => {
+        Err("trail price and trail percent are mutually exclusive".into())
+      },
+      _ => Err("stop loss requires either a stop price or a trail".into()),
     }
   }
 }
 
 impl From<StopLoss> for StopLossSerde {
   fn from(other: StopLoss) -> Self {
+    let mut serde = Self {
+      stop_price: None,
+      limit_price: None,
+      trail_price: None,
+      trail_percent: None,
+    };
+
     match other {
-      StopLoss::Stop(stop_price) => Self {
-        stop_price,
-        limit_price: None,
-      },
-      StopLoss::StopLimit(stop_price, limit_price) => Self {
-        stop_price,
-        limit_price: Some(limit_price),
+      StopLoss::Stop(stop_price) => serde.stop_price = Some(stop_price),
+      StopLoss::StopLimit(stop_price, limit_price) => {
+        serde.stop_price = Some(stop_price);
+        serde.limit_price = Some(limit_price);
       },
+      StopLoss::TrailPrice(trail_price) => serde.trail_price = Some(trail_price),
+      StopLoss::TrailPercent(trail_percent) => serde.trail_percent = Some(trail_percent),
     }
+    serde
   }
 }
 
@@ -383,6 +535,76 @@ impl Amount {
 }
 
 
+/// The maximum number of decimal places of a price of at least one
+/// dollar.
+const PRICE_PRECISION: u32 = 2;
+/// The maximum number of decimal places of a price below one dollar.
+const SUB_DOLLAR_PRICE_PRECISION: u32 = 4;
+/// The maximum number of decimal places of a (fractional) quantity.
+const QUANTITY_PRECISION: u32 = 9;
+
+
+/// The mode used for rounding prices and quantities.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RoundingMode {
+  /// Round to the nearest value, with values exactly half way between
+  /// two being rounded away from zero. This is the convention used by
+  /// US exchanges.
+  #[default]
+  HalfUp,
+  /// Round to the nearest value, with values exactly half way between
+  /// two being rounded to the even one ("bankers rounding").
+  HalfEven,
+  /// Round towards zero, i.e., truncate.
+  TowardZero,
+}
+
+impl RoundingMode {
+  /// Round `value` to `precision` decimal places.
+  fn round(self, value: &Num, precision: u32) -> Num {
+    let factor = Num::from(10u64.pow(precision));
+    let scaled = value * &factor;
+    let rounded = match self {
+      Self::HalfUp => {
+        let truncated = scaled.trunc();
+        let fraction = &scaled - &truncated;
+        if fraction.is_negative() && fraction <= Num::new(-1, 2) {
+          truncated - 1
+        } else if fraction.is_positive() && fraction >= Num::new(1, 2) {
+          truncated + 1
+        } else {
+          truncated
+        }
+      },
+      Self::HalfEven => scaled.round(),
+      Self::TowardZero => scaled.trunc(),
+    };
+    rounded / factor
+  }
+}
+
+
+/// Round a limit or stop price to the precision accepted by Alpaca,
+/// i.e., two decimal places for prices of at least one dollar and four
+/// for those below, using the provided rounding mode.
+pub fn round_price(price: &Num, mode: RoundingMode) -> Num {
+  let precision = if price >= &Num::from(1) {
+    PRICE_PRECISION
+  } else {
+    SUB_DOLLAR_PRICE_PRECISION
+  };
+  mode.round(price, precision)
+}
+
+
+/// Round a (fractional) quantity to the precision accepted by Alpaca,
+/// i.e., nine decimal places, using the provided rounding mode.
+pub fn round_quantity(quantity: &Num, mode: RoundingMode) -> Num {
+  mode.round(quantity, QUANTITY_PRECISION)
+}
+
+
 /// A helper for initializing `CreateReq` objects.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct CreateReqInit {
@@ -408,6 +630,10 @@ pub struct CreateReqInit {
   pub extended_hours: bool,
   /// See `CreateReq::client_order_id`.
   pub client_order_id: Option<String>,
+  /// See `CreateReq::expires_at`.
+  pub expires_at: Option<DateTime<Utc>>,
+  /// See `CreateReq::legs`.
+  pub legs: Vec<Leg>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
@@ -439,9 +665,23 @@ impl CreateReqInit {
       client_order_id: self.client_order_id,
       trail_price: self.trail_price,
       trail_percent: self.trail_percent,
+      expires_at: self.expires_at,
+      legs: self.legs,
       _non_exhaustive: (),
     }
   }
+
+  /// Create a `CreateReq` for a multi-leg order from a
+  /// `CreateReqInit`.
+  ///
+  /// Multi-leg orders are described by their [`legs`][Self::legs]
+  /// and do not have a symbol or side of their own. The order class is
+  /// set to [`Class::MultiLeg`].
+  pub fn init_multi_leg(self, amount: Amount) -> CreateReq {
+    let mut request = self.init(String::new(), Side::Buy, amount);
+    request.class = Class::MultiLeg;
+    request
+  }
 }
 
 
@@ -449,12 +689,16 @@ impl CreateReqInit {
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CreateReq {
   /// Symbol or asset ID to identify the asset to trade.
+  ///
+  /// Not submitted for multi-leg orders.
   #[serde(rename = "symbol")]
   pub symbol: asset::Symbol,
   /// Amount of shares to trade.
   #[serde(flatten)]
   pub amount: Amount,
   /// The side the order is on.
+  ///
+  /// Not submitted for multi-leg orders.
   #[serde(rename = "side")]
   pub side: Side,
   /// The order class.
@@ -467,10 +711,10 @@ pub struct CreateReq {
   #[serde(rename = "time_in_force")]
   pub time_in_force: TimeInForce,
   /// The limit price.
-  #[serde(rename = "limit_price")]
+  #[serde(rename = "limit_price", skip_serializing_if = "Option::is_none")]
   pub limit_price: Option<Num>,
   /// The stop price.
-  #[serde(rename = "stop_price")]
+  #[serde(rename = "stop_price", skip_serializing_if = "Option::is_none")]
   pub stop_price: Option<Num>,
   /// The dollar value away from the high water mark.
   #[serde(rename = "trail_price")]
@@ -496,15 +740,215 @@ pub struct CreateReq {
   /// passed along by Alpaca. It can be used for associating additional
   /// information with an order, from the client.
   ///
-  /// The documented maximum length is 48 characters.
+  /// The documented maximum length is [`MAX_CLIENT_ORDER_ID_LEN`]
+  /// characters. See [`ClientOrderIdGenerator`] for a way to generate
+  /// unique IDs.
   #[serde(rename = "client_order_id")]
   pub client_order_id: Option<String>,
+  /// The time at which the order expires.
+  ///
+  /// This field is required for orders with a time in force of
+  /// [`TimeInForce::UntilDate`] and must not be set otherwise.
+  #[serde(rename = "expires_at", skip_serializing_if = "Option::is_none")]
+  pub expires_at: Option<DateTime<Utc>>,
+  /// The legs of a multi-leg order (see [`Class::MultiLeg`]).
+  #[serde(rename = "legs", default, skip_serializing_if = "Vec::is_empty")]
+  pub legs: Vec<Leg>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl CreateReq {
+  /// Check that an account approved for the given options trading
+  /// level (see [`Account::options_approved_level`][crate::api::v2::account::Account::options_approved_level])
+  /// is permitted to submit this order.
+  ///
+  /// Only multi-leg orders are checked, based on the level reported by
+  /// [`required_options_level`] for their legs.
+  pub fn check_options_level(&self, approved: u8) -> Result<(), OptionsLevelError> {
+    if self.class != Class::MultiLeg {
+      return Ok(())
+    }
+
+    let required = required_options_level(&self.legs);
+    if required > approved {
+      Err(OptionsLevelError { required, approved })
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Check the request for invalid combinations of parameters.
+  fn validate(&self) -> Result<(), Str> {
+    if self.trail_price.is_some() && self.trail_percent.is_some() {
+      return Err("trail price and trail percent are mutually exclusive".into())
+    }
+
+    if self.type_ == Type::Market && self.stop_price.is_some() {
+      return Err("a stop price is not supported for market orders".into())
+    }
+
+    if self.extended_hours && (self.type_ != Type::Limit || self.time_in_force != TimeInForce::Day)
+    {
+      return Err("extended hours are only supported for limit orders good for the day".into())
+    }
+
+    match (self.time_in_force, &self.expires_at) {
+      (TimeInForce::UntilDate, None) => {
+        return Err("good-till-date orders require an expiration time".into())
+      },
+      (TimeInForce::UntilDate, Some(..)) | (_, None) => (),
+      (_, Some(..)) => {
+        return Err("an expiration time is only supported for good-till-date orders".into())
+      },
+    }
+
+    for leg in &self.legs {
+      let ratio = &leg.ratio_quantity;
+      if !ratio.is_positive() || !ratio.fract().is_zero() {
+        return Err(
+          format!(
+            "ratio quantity of leg {} is not a positive integer: {ratio}",
+            leg.symbol
+          )
+          .into(),
+        )
+      }
+    }
+    Ok(())
+  }
+}
+
+
+/// Determine the options trading level an account has to be approved
+/// for in order to submit a multi-leg order with the given legs.
+///
+/// Alpaca grants the following levels:
+/// - 1: selling covered calls and cash-secured puts
+/// - 2: additionally buying calls and puts
+/// - 3: additionally trading spreads
+///
+/// Legs closing a position do not require more than level 1. Legs
+/// without a position intent are assumed to open a position. Short
+/// legs are assumed to be covered by the long legs of the same order,
+/// i.e., to form a spread, as uncovered options cannot be traded on
+/// any level.
+pub fn required_options_level(legs: &[Leg]) -> u8 {
+  let opening = legs.iter().filter(|leg| {
+    !matches!(
+      leg.position_intent,
+      Some(PositionIntent::BuyToClose | PositionIntent::SellToClose)
+    )
+  });
+
+  opening.fold(1, |level, leg| match leg.side {
+    Side::Buy => level.max(2),
+    Side::Sell => level.max(3),
+  })
+}
+
+
+/// An error indicating that the options trading level approved for an
+/// account is insufficient for submitting an order.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+#[error("order requires options trading level {required} but level {approved} is approved")]
+pub struct OptionsLevelError {
+  /// The options trading level required by the order.
+  pub required: u8,
+  /// The options trading level approved for the account.
+  pub approved: u8,
+}
+
+
+/// The maximum length of a client order ID, in characters.
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 48;
+
+/// The number of characters a [`ClientOrderIdGenerator`] appends to
+/// the prefix: a separator, an eleven digit hexadecimal timestamp, a
+/// second separator, and an eight digit random hexadecimal suffix.
+const CLIENT_ORDER_ID_SUFFIX_LEN: usize = 1 + 11 + 1 + 8;
+
+
+/// A generator of unique client order IDs sharing a common prefix.
+///
+/// Generated IDs have the form `<prefix>-<timestamp>-<random>`, where
+/// `<timestamp>` is the current time in milliseconds since the Unix
+/// epoch and `<random>` is a random value, both hex encoded. Because
+/// all IDs start with the prefix, orders submitted with them can be
+/// found using [`orders::list_by_prefix`][crate::api::v2::orders::list_by_prefix].
+///
+/// The current time is retrieved from the provided [`TimeSource`], which
+/// defaults to the system clock.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClientOrderIdGenerator<C = SystemClock> {
+  /// The prefix of all generated IDs.
+  prefix: String,
+  /// The clock providing the time stamps of generated IDs.
+  clock: C,
+}
+
+impl ClientOrderIdGenerator {
+  /// Create a new generator for IDs starting with `prefix`.
+  ///
+  /// The prefix has to be short enough for generated IDs to stay
+  /// within [`MAX_CLIENT_ORDER_ID_LEN`].
+  pub fn new(prefix: impl Into<String>) -> Result<Self, InvalidPrefixError> {
+    Self::with_clock(prefix, SystemClock)
+  }
+}
+
+impl<C> ClientOrderIdGenerator<C>
+where
+  C: TimeSource,
+{
+  /// Create a new generator for IDs starting with `prefix`, using
+  /// `clock` for retrieving the current time.
+  ///
+  /// The prefix has to be short enough for generated IDs to stay
+  /// within [`MAX_CLIENT_ORDER_ID_LEN`].
+  pub fn with_clock(prefix: impl Into<String>, clock: C) -> Result<Self, InvalidPrefixError> {
+    let prefix = prefix.into();
+    if prefix.chars().count() > MAX_CLIENT_ORDER_ID_LEN - CLIENT_ORDER_ID_SUFFIX_LEN {
+      return Err(InvalidPrefixError(prefix))
+    }
+    Ok(Self { prefix, clock })
+  }
+
+  /// Retrieve the prefix of all generated IDs.
+  #[inline]
+  pub fn prefix(&self) -> &str {
+    &self.prefix
+  }
+
+  /// Generate a new client order ID.
+  pub fn generate(&self) -> String {
+    let millis = self.clock.now().timestamp_millis().max(0);
+    let random = Uuid::new_v4().as_u128() as u32;
+    format!("{}-{millis:011x}-{random:08x}", self.prefix)
+  }
+}
+
+
+/// An error indicating that a prefix is too long to be used for the
+/// generation of client order IDs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidPrefixError(String);
+
+impl Display for InvalidPrefixError {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    write!(
+      fmt,
+      "client order ID prefix '{}' exceeds maximum length of {} characters",
+      self.0,
+      MAX_CLIENT_ORDER_ID_LEN - CLIENT_ORDER_ID_SUFFIX_LEN
+    )
+  }
+}
+
+impl StdError for InvalidPrefixError {}
+
 
 /// A PATCH request to be made to the /v2/orders/{order-id} endpoint.
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -525,6 +969,10 @@ pub struct ChangeReq {
   #[serde(rename = "trail")]
   pub trail: Option<Num>,
   /// Client unique order ID (free form string).
+  ///
+  /// The ID is assigned to the replacement order. It has to be set (and
+  /// be unique) for [`change_idempotent`] to be able to detect whether
+  /// a change took effect.
   #[serde(rename = "client_order_id")]
   pub client_order_id: Option<String>,
   /// The type is non-exhaustive and open to extension.
@@ -551,9 +999,27 @@ where
 }
 
 
+/// The slippage of an order's fill price versus a reference price.
+///
+/// Slippage is signed by side such that a positive value is
+/// unfavorable: it indicates that a buy order was filled above or a
+/// sell order below the reference price.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slippage {
+  /// The absolute slippage, per unit.
+  pub absolute: Num,
+  /// The slippage relative to the reference price, in basis points.
+  pub basis_points: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
 /// A single order as returned by the /v2/orders endpoint on a GET
 /// request.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "OrderRepr")]
 pub struct Order {
   /// The order's ID.
   #[serde(rename = "id")]
@@ -621,9 +1087,21 @@ pub struct Order {
   /// The percent value away from the high water mark.
   #[serde(rename = "trail_percent")]
   pub trail_percent: Option<Num>,
+  /// The high water mark of a trailing stop order, i.e., the highest
+  /// (for sell orders) or lowest (for buy orders) price observed since
+  /// the order was submitted. The stop price moves with it.
+  #[serde(rename = "hwm", default)]
+  pub high_water_mark: Option<Num>,
   /// The average price at which the order was filled.
   #[serde(rename = "filled_avg_price")]
   pub average_fill_price: Option<Num>,
+  /// The intent of the order with respect to the position it affects.
+  #[serde(rename = "position_intent", default)]
+  pub position_intent: Option<PositionIntent>,
+  /// The ratio quantity of the order, if it is a leg of a multi-leg
+  /// order.
+  #[serde(rename = "ratio_qty", default)]
+  pub ratio_quantity: Option<Num>,
   /// If true, the order is eligible for execution outside regular
   /// trading hours.
   #[serde(rename = "extended_hours")]
@@ -641,6 +1119,380 @@ pub struct Order {
 }
 
 
+/// The raw representation of an [`Order`] as reported by Alpaca.
+///
+/// Depending on the API version and endpoint, Alpaca reports the order
+/// type as `type`, as `order_type`, or as both. A plain alias would
+/// reject the latter, so we accept either key here and resolve the type
+/// when converting into an [`Order`], preferring `type`.
+#[derive(Deserialize)]
+struct OrderRepr {
+  #[serde(rename = "id")]
+  id: Id,
+  #[serde(rename = "client_order_id")]
+  client_order_id: String,
+  #[serde(rename = "status")]
+  status: Status,
+  #[serde(rename = "created_at")]
+  created_at: DateTime<Utc>,
+  #[serde(rename = "updated_at")]
+  updated_at: Option<DateTime<Utc>>,
+  #[serde(rename = "submitted_at")]
+  submitted_at: Option<DateTime<Utc>>,
+  #[serde(rename = "filled_at")]
+  filled_at: Option<DateTime<Utc>>,
+  #[serde(rename = "expired_at")]
+  expired_at: Option<DateTime<Utc>>,
+  #[serde(rename = "canceled_at")]
+  canceled_at: Option<DateTime<Utc>>,
+  #[serde(rename = "asset_class")]
+  asset_class: asset::Class,
+  #[serde(rename = "asset_id")]
+  asset_id: asset::Id,
+  #[serde(rename = "symbol")]
+  symbol: String,
+  #[serde(flatten)]
+  amount: Amount,
+  #[serde(rename = "filled_qty")]
+  filled_quantity: Num,
+  /// The type of order, as reported under the `type` key.
+  #[serde(rename = "type", default)]
+  type_: Option<Type>,
+  /// The type of order, as reported under the `order_type` key.
+  #[serde(rename = "order_type", default)]
+  order_type: Option<Type>,
+  #[serde(rename = "order_class", deserialize_with = "empty_to_default")]
+  class: Class,
+  #[serde(rename = "side")]
+  side: Side,
+  #[serde(rename = "time_in_force")]
+  time_in_force: TimeInForce,
+  #[serde(rename = "limit_price")]
+  limit_price: Option<Num>,
+  #[serde(rename = "stop_price")]
+  stop_price: Option<Num>,
+  #[serde(rename = "trail_price")]
+  trail_price: Option<Num>,
+  #[serde(rename = "trail_percent")]
+  trail_percent: Option<Num>,
+  #[serde(rename = "hwm", default)]
+  high_water_mark: Option<Num>,
+  #[serde(rename = "filled_avg_price")]
+  average_fill_price: Option<Num>,
+  #[serde(rename = "position_intent", default)]
+  position_intent: Option<PositionIntent>,
+  #[serde(rename = "ratio_qty", default)]
+  ratio_quantity: Option<Num>,
+  #[serde(rename = "extended_hours")]
+  extended_hours: bool,
+  #[serde(rename = "legs", deserialize_with = "vec_from_str")]
+  legs: Vec<Order>,
+}
+
+impl TryFrom<OrderRepr> for Order {
+  type Error = &'static str;
+
+  fn try_from(repr: OrderRepr) -> Result<Self, Self::Error> {
+    let OrderRepr {
+      id,
+      client_order_id,
+      status,
+      created_at,
+      updated_at,
+      submitted_at,
+      filled_at,
+      expired_at,
+      canceled_at,
+      asset_class,
+      asset_id,
+      symbol,
+      amount,
+      filled_quantity,
+      type_,
+      order_type,
+      class,
+      side,
+      time_in_force,
+      limit_price,
+      stop_price,
+      trail_price,
+      trail_percent,
+      high_water_mark,
+      average_fill_price,
+      position_intent,
+      ratio_quantity,
+      extended_hours,
+      legs,
+    } = repr;
+
+    Ok(Self {
+      id,
+      client_order_id,
+      status,
+      created_at,
+      updated_at,
+      submitted_at,
+      filled_at,
+      expired_at,
+      canceled_at,
+      asset_class,
+      asset_id,
+      symbol,
+      amount,
+      filled_quantity,
+      type_: type_.or(order_type).ok_or("missing field `type`")?,
+      class,
+      side,
+      time_in_force,
+      limit_price,
+      stop_price,
+      trail_price,
+      trail_percent,
+      high_water_mark,
+      average_fill_price,
+      position_intent,
+      ratio_quantity,
+      extended_hours,
+      legs,
+      _non_exhaustive: (),
+    })
+  }
+}
+
+
+impl Order {
+  /// Calculate the average fill price of the fills on the given side
+  /// of this order and all its legs, weighted by the respective filled
+  /// quantity.
+  ///
+  /// Only the order itself and those legs that are on `side`
+  /// contribute to the result, as averaging buys and sells together is
+  /// meaningless. For a bracket order, for example, the parent's side
+  /// yields the entry price and the opposite side the exit price. Legs
+  /// (or the order itself) that have not been filled at all do not
+  /// contribute either. If nothing has been filled on `side`, `None` is
+  /// returned.
+  pub fn weighted_fill_price(&self, side: Side) -> Option<Num> {
+    fn accumulate(order: &Order, side: Side, total_value: &mut Num, total_quantity: &mut Num) {
+      if order.side == side {
+        if let Some(price) = &order.average_fill_price {
+          if !order.filled_quantity.is_zero() {
+            *total_value += price * &order.filled_quantity;
+            *total_quantity += &order.filled_quantity;
+          }
+        }
+      }
+
+      for leg in &order.legs {
+        accumulate(leg, side, total_value, total_quantity)
+      }
+    }
+
+    let mut total_value = Num::from(0);
+    let mut total_quantity = Num::from(0);
+    let () = accumulate(self, side, &mut total_value, &mut total_quantity);
+
+    if total_quantity.is_zero() {
+      None
+    } else {
+      Some(total_value / total_quantity)
+    }
+  }
+
+  /// Create a [`CreateReq`] for submitting an order identical to this
+  /// one.
+  ///
+  /// Only the tradeable properties of the order (symbol, amount, side,
+  /// class, type, time in force, prices, and legs) are preserved, while
+  /// server assigned ones (such as ID, status, and fill information)
+  /// are dropped. No client order ID is set, as it has to be unique.
+  /// Note that the expiration time of good-till-date orders is not
+  /// reported for orders and has to be set on the resulting request
+  /// explicitly.
+  pub fn to_create_req(&self) -> CreateReq {
+    let mut take_profit = None;
+    let mut stop_loss = None;
+    let mut legs = Vec::new();
+
+    match self.class {
+      Class::Simple => (),
+      Class::MultiLeg => {
+        legs = self
+          .legs
+          .iter()
+          .map(|leg| Leg {
+            symbol: leg.symbol.clone(),
+            side: leg.side,
+            position_intent: leg.position_intent,
+            ratio_quantity: leg.ratio_quantity.clone().unwrap_or_else(|| Num::from(1)),
+            _non_exhaustive: (),
+          })
+          .collect();
+      },
+      Class::Bracket | Class::OneCancelsOther | Class::OneTriggersOther => {
+        for leg in &self.legs {
+          match (leg.type_, &leg.limit_price, &leg.stop_price) {
+            (Type::Limit, Some(limit), _) => take_profit = Some(TakeProfit::Limit(limit.clone())),
+            (Type::Stop, _, Some(stop)) => stop_loss = Some(StopLoss::Stop(stop.clone())),
+            (Type::StopLimit, Some(limit), Some(stop)) => {
+              stop_loss = Some(StopLoss::StopLimit(stop.clone(), limit.clone()))
+            },
+            (Type::TrailingStop, ..) => {
+              stop_loss = match (&leg.trail_price, &leg.trail_percent) {
+                (Some(price), _) => Some(StopLoss::TrailPrice(price.clone())),
+                (None, Some(percent)) => Some(StopLoss::TrailPercent(percent.clone())),
+                (None, None) => stop_loss,
+              }
+            },
+            _ => (),
+          }
+        }
+
+        // For one-cancels-other orders the take profit part is the
+        // order itself.
+        if let (Class::OneCancelsOther, None, Some(limit)) =
+          (self.class, &take_profit, &self.limit_price)
+        {
+          take_profit = Some(TakeProfit::Limit(limit.clone()));
+        }
+      },
+    }
+
+    CreateReq {
+      symbol: asset::Symbol::Sym(self.symbol.clone()),
+      amount: self.amount.clone(),
+      side: self.side,
+      class: self.class,
+      type_: self.type_,
+      time_in_force: self.time_in_force,
+      limit_price: self.limit_price.clone(),
+      // The stop price of a trailing stop order is derived from the
+      // high water mark by the server and must not be submitted.
+      stop_price: if self.type_ == Type::TrailingStop {
+        None
+      } else {
+        self.stop_price.clone()
+      },
+      trail_price: self.trail_price.clone(),
+      trail_percent: self.trail_percent.clone(),
+      take_profit,
+      stop_loss,
+      extended_hours: self.extended_hours,
+      client_order_id: None,
+      expires_at: None,
+      legs,
+      _non_exhaustive: (),
+    }
+  }
+
+  /// Calculate the slippage of this order's average fill price versus
+  /// a reference price.
+  ///
+  /// The reference price is the provided one, if any, and the order's
+  /// limit price otherwise. For market orders, callers should supply
+  /// the price they observed (e.g., the quote) at submission time. If
+  /// the order has not been filled or no reference price is available,
+  /// `None` is returned.
+  pub fn slippage(&self, reference: Option<&Num>) -> Option<Slippage> {
+    let reference = reference.or(self.limit_price.as_ref())?;
+    if reference.is_zero() {
+      return None
+    }
+
+    let fill_price = self.average_fill_price.as_ref()?;
+    let absolute = match self.side {
+      Side::Buy => fill_price - reference,
+      Side::Sell => reference - fill_price,
+    };
+    let basis_points = &absolute * 10000 / reference;
+
+    Some(Slippage {
+      absolute,
+      basis_points,
+      _non_exhaustive: (),
+    })
+  }
+}
+
+
+/// The result of reconciling the execution quantities of an order's
+/// fills against its cumulative filled quantity, as produced by
+/// [`reconcile_fills`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FillReconciliation {
+  /// The number of fills considered.
+  pub fills: usize,
+  /// The number of fills for which no execution quantity was
+  /// reported. These fills do not contribute to `total`.
+  pub unquantified: usize,
+  /// The exact sum of the execution quantities of all fills.
+  pub total: Num,
+  /// The cumulative filled quantity reported for the order.
+  pub reported: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl FillReconciliation {
+  /// Calculate the quantity by which the order's reported filled
+  /// quantity exceeds the sum of the fills' quantities.
+  ///
+  /// A positive value indicates that fills were missed or lacked a
+  /// quantity, a negative one that fills were accounted for more than
+  /// once.
+  #[inline]
+  pub fn discrepancy(&self) -> Num {
+    &self.reported - &self.total
+  }
+
+  /// Check whether the fills account for the order's reported filled
+  /// quantity exactly.
+  ///
+  /// Fills without a quantity make the outcome unverifiable and, hence,
+  /// are considered inconsistent.
+  #[inline]
+  pub fn is_consistent(&self) -> bool {
+    self.unquantified == 0 && self.total == self.reported
+  }
+}
+
+
+/// Aggregate the execution quantities of the fills of a single order
+/// and reconcile them against the order's cumulative filled quantity,
+/// `reported`.
+///
+/// Each fill is represented by its execution quantity, if known. Fill
+/// and partial fill events of an
+/// [`OrderUpdate`][crate::api::v2::updates::OrderUpdate] report it as
+/// `quantity`, while trade activities always carry one. Quantities are
+/// summed as [`Num`] values and, hence, without loss of precision.
+pub fn reconcile_fills<'q, I>(quantities: I, reported: &Num) -> FillReconciliation
+where
+  I: IntoIterator<Item = Option<&'q Num>>,
+{
+  let mut fills = 0;
+  let mut unquantified = 0;
+  let mut total = Num::from(0);
+
+  for quantity in quantities {
+    match quantity {
+      Some(quantity) => total += quantity,
+      None => unquantified += 1,
+    }
+    fills += 1;
+  }
+
+  FillReconciliation {
+    fills,
+    unquantified,
+    total,
+    reported: reported.clone(),
+    _non_exhaustive: (),
+  }
+}
+
+
 Endpoint! {
   /// The representation of a GET request to the /v2/orders/{order-id}
   /// endpoint.
@@ -719,13 +1571,61 @@ Endpoint! {
   }
 
   fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
-    let json = to_json(input)?;
+    let () = input.validate().map_err(ConversionError::InvalidRequest)?;
+
+    let json = if input.class == Class::MultiLeg {
+      // Multi-leg orders are described entirely by their legs and the
+      // top-level symbol and side are not accepted.
+      let mut json = to_json_value(input)?;
+      if let Some(object) = json.as_object_mut() {
+        let _symbol = object.remove("symbol");
+        let _side = object.remove("side");
+      }
+      to_json(&json)?
+    } else {
+      to_json(input)?
+    };
     let bytes = Bytes::from(json);
     Ok(Some(bytes))
   }
 }
 
 
+impl CreateError {
+  /// Check whether the error indicates that the order was rejected
+  /// because its client order ID is already in use.
+  ///
+  /// Alpaca reports this condition using a generic error code, so
+  /// this check also inspects the error message.
+  pub fn is_duplicate_client_order_id(&self) -> bool {
+    match self {
+      Self::InvalidInput(Ok(ApiError {
+        code: Some(ERROR_CODE_INVALID_INPUT),
+        message,
+      })) => message.contains("client_order_id must be unique"),
+      _ => false,
+    }
+  }
+
+  /// Check whether the error indicates that the order was rejected
+  /// because the market is closed.
+  ///
+  /// Such rejections are reported with varying HTTP status and without
+  /// a dedicated error code, so this check inspects the error message.
+  pub fn is_market_closed(&self) -> bool {
+    match self {
+      Self::InvalidInput(Ok(error))
+      | Self::NotPermitted(Ok(error))
+      | Self::UnexpectedStatus(_, Ok(error)) => {
+        let message = error.message.to_ascii_lowercase();
+        message.contains("market is closed") || message.contains("market closed")
+      },
+      _ => false,
+    }
+  }
+}
+
+
 Endpoint! {
   /// The representation of a PATCH request to the /v2/orders/{order-id}
   /// endpoint.
@@ -760,6 +1660,104 @@ Endpoint! {
 }
 
 
+/// Change an order, making sure that the change is applied at most once.
+///
+/// A change request that fails at the transport level (e.g., because
+/// the connection timed out) may or may not have been processed by the
+/// server. In such a case this function looks up the replacement order
+/// by the client order ID specified in `request` and reports it if it
+/// exists. Only if no such order is found is the change request issued
+/// once more.
+///
+/// # Notes
+/// - `request` has to have a unique
+///   [`client_order_id`][ChangeReq::client_order_id] assigned; if none
+///   is set, this function is equivalent to issuing a [`Change`]
+///   request
+pub async fn change_idempotent(
+  client: &Client,
+  id: Id,
+  request: ChangeReq,
+) -> Result<Order, RequestError<ChangeError>> {
+  let client_order_id = request.client_order_id.clone();
+  let input = (id, request);
+
+  match client.issue::<Change>(&input).await {
+    Err(RequestError::Endpoint(err)) => Err(RequestError::Endpoint(err)),
+    Err(err) => {
+      if let Some(client_order_id) = client_order_id {
+        match client.issue::<GetByClientId>(&client_order_id).await {
+          Ok(order) => return Ok(order),
+          Err(RequestError::Endpoint(GetByClientIdError::NotFound(_))) => (),
+          Err(_) => return Err(err),
+        }
+      }
+      client.issue::<Change>(&input).await
+    },
+    result => result,
+  }
+}
+
+
+/// An error as reported by [`poll_until_terminal`].
+#[derive(Debug, Error)]
+pub enum PollError {
+  /// The order did not reach a terminal status in time. The most
+  /// recently retrieved state of the order is included, if any.
+  #[error("order did not reach a terminal status in time")]
+  Timeout(Option<Box<Order>>),
+  /// The order could not be retrieved.
+  #[error("failed to retrieve order")]
+  Request(#[source] RequestError<GetError>),
+}
+
+
+/// Poll the order with the given ID via the REST API, every
+/// `interval`, until it reaches a terminal status (see
+/// [`Status::is_terminal`]) or `timeout` elapsed.
+///
+/// This function provides an alternative to waiting for order updates
+/// via the [`updates`][crate::api::v2::updates] stream, for
+/// environments in which a websocket connection can not be used.
+///
+/// # Notes
+/// - orders are always retrieved from the API, bypassing any cache
+///   configured for the [`Get`] endpoint
+/// - a poll rejected because the rate limit was exceeded is not treated
+///   as an error; polling just continues after `interval` or after the
+///   delay requested via the `Retry-After` header of the response to
+///   that very poll, whichever is longer
+pub async fn poll_until_terminal(
+  client: &Client,
+  id: Id,
+  interval: Duration,
+  timeout: Duration,
+) -> Result<Order, PollError> {
+  let deadline = Instant::now() + timeout;
+  let mut last = None;
+
+  loop {
+    let mut delay = interval;
+    let (result, retry_after) = client.issue_uncached_with_retry_after::<Get>(&id).await;
+    match result {
+      Ok(order) if order.status.is_terminal() => return Ok(order),
+      Ok(order) => last = Some(order),
+      Err(RequestError::Endpoint(GetError::RateLimitExceeded(_))) => {
+        if let Some(retry_after) = retry_after {
+          delay = delay.max(retry_after);
+        }
+      },
+      Err(err) => return Err(PollError::Request(err)),
+    }
+
+    if Instant::now() + delay > deadline {
+      return Err(PollError::Timeout(last.map(Box::new)))
+    }
+    let () = sleep(delay).await;
+  }
+}
+
+
 EndpointNoParse! {
   /// The representation of a DELETE request to the /v2/orders/{order-id}
   /// endpoint.
@@ -791,7 +1789,55 @@ EndpointNoParse! {
   }
 
   fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
-    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+    Self::ApiError::parse(body)
+  }
+}
+
+
+/// The outcome of the attempted cancellation of a single order, as
+/// reported by the DELETE /v2/orders endpoint.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CancelResult {
+  /// The ID of the order that was attempted to be canceled.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// The HTTP status code reported for the cancellation of this order.
+  #[serde(rename = "status")]
+  pub status: u16,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl CancelResult {
+  /// Check whether the order was canceled successfully.
+  #[inline]
+  pub fn is_success(&self) -> bool {
+    (200..300).contains(&self.status)
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a DELETE request to the /v2/orders
+  /// endpoint, canceling all open orders.
+  pub DeleteAll(()),
+  Ok => Vec<CancelResult>, [
+    /// The cancellation of all open orders was attempted. The result of
+    /// each individual cancellation is reported separately.
+    /* 207 */ MULTI_STATUS,
+  ],
+  Err => DeleteAllError, []
+
+  #[inline]
+  fn method() -> Method {
+    Method::DELETE
+  }
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/orders".into()
   }
 }
 
@@ -800,11 +1846,20 @@ EndpointNoParse! {
 mod tests {
   use super::*;
 
-  use std::str::FromStr as _;
+  use std::collections::HashSet;
+  use std::slice;
 
+  use futures::future::join;
   use futures::TryFutureExt;
 
+  use http::StatusCode;
+
+  use http_endpoint::Endpoint;
+
   use serde_json::from_slice as from_json;
+  use serde_json::json;
+  use serde_json::Value;
+  use serde_urlencoded::from_str as from_query;
 
   use test_log::test;
 
@@ -813,12 +1868,52 @@ mod tests {
   use crate::api::v2::asset;
   use crate::api::v2::asset::Exchange;
   use crate::api::v2::asset::Symbol;
+  use crate::api::v2::clock;
+  use crate::api::v2::fixture::order_json;
   use crate::api::v2::order_util::order_aapl;
   use crate::api_info::ApiInfo;
+  use crate::mock::Server;
   use crate::Client;
   use crate::RequestError;
 
 
+  /// Create the JSON representation of a partially filled bracket
+  /// order.
+  fn bracket_order() -> Vec<u8> {
+    let take_profit = order_json(json!({
+      "id": "904837e3-3b76-47ec-b432-046db621571c",
+      "client_order_id": "904837e3-3b76-47ec-b432-046db621571c",
+      "filled_qty": "4",
+      "type": "limit",
+      "order_class": "bracket",
+      "side": "sell",
+      "limit_price": "110.00",
+      "filled_avg_price": "110.00",
+      "status": "partially_filled",
+    }));
+    let stop_loss = order_json(json!({
+      "id": "904837e3-3b76-47ec-b432-046db621571d",
+      "client_order_id": "904837e3-3b76-47ec-b432-046db621571d",
+      "type": "stop",
+      "order_class": "bracket",
+      "side": "sell",
+      "stop_price": "90.00",
+      "status": "held",
+    }));
+    let order = order_json(json!({
+      "filled_at": "2018-10-05T05:48:59Z",
+      "filled_qty": "10",
+      "type": "limit",
+      "order_class": "bracket",
+      "limit_price": "100.00",
+      "filled_avg_price": "100.00",
+      "status": "filled",
+      "legs": [take_profit, stop_loss],
+    }));
+    order.to_string().into_bytes()
+  }
+
+
   /// Check that we can serialize a [`Side`] object.
   #[test]
   fn emit_side() {
@@ -841,6 +1936,184 @@ mod tests {
     assert_eq!(to_json(&Type::Stop).unwrap(), br#""stop""#);
   }
 
+  /// Check that we encode the body of a good-till-date order request
+  /// as expected and reject an invalid combination of time in force
+  /// and expiration time.
+  #[test]
+  fn create_body_good_till_date() {
+    let expires_at = DateTime::from_str("2024-03-01T20:00:00Z").unwrap();
+    let request = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      time_in_force: TimeInForce::UntilDate,
+      expires_at: Some(expires_at),
+      ..Default::default()
+    }
+    .init("SPY", Side::Buy, Amount::quantity(1));
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["time_in_force"], "gtd");
+    assert_eq!(json["expires_at"], "2024-03-01T20:00:00Z");
+
+    let mut invalid = request.clone();
+    invalid.expires_at = None;
+    let err = <Create as Endpoint>::body(&invalid).unwrap_err();
+    assert!(matches!(err, ConversionError::InvalidRequest(..)), "{err}");
+
+    let mut invalid = request;
+    invalid.time_in_force = TimeInForce::Day;
+    let err = <Create as Endpoint>::body(&invalid).unwrap_err();
+    assert!(matches!(err, ConversionError::InvalidRequest(..)), "{err}");
+  }
+
+  /// Check that we encode the legs of multi-leg orders correctly and
+  /// validate their ratio quantities.
+  #[test]
+  fn create_body_multi_leg() {
+    let leg = |symbol, side, ratio| {
+      let intent = match side {
+        Side::Buy => PositionIntent::BuyToOpen,
+        Side::Sell => PositionIntent::SellToOpen,
+      };
+      LegInit {
+        position_intent: Some(intent),
+        ..Default::default()
+      }
+      .init(symbol, side, Num::from(ratio))
+    };
+
+    // A 1:1 vertical call spread.
+    let request = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::new(1, 2)),
+      legs: vec![
+        leg("AAPL250117C00190000", Side::Buy, 1),
+        leg("AAPL250117C00200000", Side::Sell, 1),
+      ],
+      ..Default::default()
+    }
+    .init_multi_leg(Amount::quantity(1));
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["order_class"], "mleg");
+    assert_eq!(json["qty"], "1");
+    assert!(json.get("symbol").is_none());
+    assert!(json.get("side").is_none());
+    assert_eq!(
+      json["legs"],
+      serde_json::json!([
+        {
+          "symbol": "AAPL250117C00190000",
+          "side": "buy",
+          "position_intent": "buy_to_open",
+          "ratio_qty": "1",
+        },
+        {
+          "symbol": "AAPL250117C00200000",
+          "side": "sell",
+          "position_intent": "sell_to_open",
+          "ratio_qty": "1",
+        },
+      ])
+    );
+
+    // A 1:2 ratio spread.
+    let mut request = request;
+    request.legs[1].ratio_quantity = Num::from(2);
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["legs"][0]["ratio_qty"], "1");
+    assert_eq!(json["legs"][1]["ratio_qty"], "2");
+
+    for ratio in [Num::from(0), Num::from(-1), Num::new(3, 2)] {
+      let mut invalid = request.clone();
+      invalid.legs[1].ratio_quantity = ratio;
+      let err = <Create as Endpoint>::body(&invalid).unwrap_err();
+      assert!(matches!(err, ConversionError::InvalidRequest(..)), "{err}");
+    }
+
+    // Requests without legs should not contain the field at all.
+    let request = CreateReqInit::default().init("SPY", Side::Buy, Amount::quantity(1));
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert!(json.get("legs").is_none());
+    assert_eq!(json["symbol"], "SPY");
+    assert_eq!(json["side"], "buy");
+  }
+
+  /// Check that we map the legs of multi-leg orders to the required
+  /// options trading level and check it against the approved one.
+  #[test]
+  fn check_multi_leg_options_level() {
+    let leg = |symbol, side, intent| {
+      LegInit {
+        position_intent: Some(intent),
+        ..Default::default()
+      }
+      .init(symbol, side, Num::from(1))
+    };
+    let buy_call = leg("AAPL250117C00190000", Side::Buy, PositionIntent::BuyToOpen);
+    let sell_call = leg(
+      "AAPL250117C00200000",
+      Side::Sell,
+      PositionIntent::SellToOpen,
+    );
+    let close_call = leg(
+      "AAPL250117C00200000",
+      Side::Sell,
+      PositionIntent::SellToClose,
+    );
+
+    assert_eq!(required_options_level(slice::from_ref(&close_call)), 1);
+    assert_eq!(
+      required_options_level(&[buy_call.clone(), close_call.clone()]),
+      2
+    );
+    assert_eq!(
+      required_options_level(&[buy_call.clone(), sell_call.clone()]),
+      3
+    );
+
+    let spread = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::new(1, 2)),
+      legs: vec![buy_call, sell_call],
+      ..Default::default()
+    }
+    .init_multi_leg(Amount::quantity(1));
+
+    let err = spread.check_options_level(2).unwrap_err();
+    assert_eq!(err.required, 3);
+    assert_eq!(err.approved, 2);
+    let () = spread.check_options_level(3).unwrap();
+
+    // Orders other than multi-leg ones are not checked.
+    let request = CreateReqInit::default().init("SPY", Side::Buy, Amount::quantity(1));
+    let () = request.check_options_level(0).unwrap();
+  }
+
+  /// Check that we can parse the leg of a multi-leg order.
+  #[test]
+  fn parse_multi_leg_order_leg() {
+    let json = order_json(json!({
+      "symbol": "AAPL250117C00200000",
+      "asset_class": "us_option",
+      "qty": "2",
+      "type": "limit",
+      "order_class": "mleg",
+      "side": "sell",
+      "position_intent": "sell_to_open",
+      "ratio_qty": "2",
+    }));
+
+    let order = from_json::<Order>(json.to_string().as_bytes()).unwrap();
+    assert_eq!(order.class, Class::MultiLeg);
+    assert_eq!(order.position_intent, Some(PositionIntent::SellToOpen));
+    assert_eq!(order.ratio_quantity, Some(Num::from(2)));
+  }
+
   /// Make sure that we can serialize and deserialize order legs.
   #[test]
   fn serialize_deserialize_legs() {
@@ -859,6 +2132,140 @@ mod tests {
     let expected = br#"{"stop_price":"13","limit_price":"96"}"#;
     assert_eq!(json, &expected[..]);
     assert_eq!(from_json::<StopLoss>(&json).unwrap(), stop_loss);
+
+    let stop_loss = StopLoss::TrailPercent(Num::from(5));
+    let json = to_json(&stop_loss).unwrap();
+    assert_eq!(json, br#"{"trail_percent":"5"}"#);
+    assert_eq!(from_json::<StopLoss>(&json).unwrap(), stop_loss);
+
+    let json = br#"{"trail_price":"1","trail_percent":"5"}"#;
+    assert!(from_json::<StopLoss>(json).is_err());
+  }
+
+  /// Check that a fill-or-kill order is encoded as such.
+  #[test]
+  fn create_body_fill_or_kill() {
+    let request = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      time_in_force: TimeInForce::FillOrKill,
+      ..Default::default()
+    }
+    .init("SPY", Side::Buy, Amount::quantity(100));
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["time_in_force"], "fok");
+    assert_eq!(json["qty"], "100");
+    assert_eq!(json["limit_price"], "100");
+  }
+
+  /// Check that a stop (market) order is encoded with only a stop
+  /// price and that a stop price is rejected for market orders.
+  #[test]
+  fn create_body_stop_order() {
+    let request = CreateReqInit {
+      type_: Type::Stop,
+      stop_price: Some(Num::from(95)),
+      ..Default::default()
+    }
+    .init("SPY", Side::Sell, Amount::quantity(1));
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["type"], "stop");
+    assert_eq!(json["stop_price"], "95");
+    assert_eq!(json.get("limit_price"), None);
+
+    let request = CreateReqInit {
+      type_: Type::Market,
+      stop_price: Some(Num::from(95)),
+      ..Default::default()
+    }
+    .init("SPY", Side::Sell, Amount::quantity(1));
+
+    let err = <Create as Endpoint>::body(&request).unwrap_err();
+    assert!(err.to_string().contains("market orders"), "{err}");
+  }
+
+  /// Check that an extended hours limit order good for the day is
+  /// encoded properly and that other extended hours orders are
+  /// rejected locally.
+  #[test]
+  fn create_body_extended_hours_limit_order() {
+    let request = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      extended_hours: true,
+      ..Default::default()
+    }
+    .init("SPY", Side::Buy, Amount::quantity(1));
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["type"], "limit");
+    assert_eq!(json["time_in_force"], "day");
+    assert_eq!(json["limit_price"], "100");
+    assert_eq!(json["extended_hours"], true);
+
+    let invalid = [
+      (Type::Stop, TimeInForce::Day),
+      (Type::StopLimit, TimeInForce::Day),
+      (Type::Limit, TimeInForce::UntilCanceled),
+      (Type::Limit, TimeInForce::ImmediateOrCancel),
+    ];
+    for (type_, time_in_force) in invalid {
+      let request = CreateReq {
+        type_,
+        time_in_force,
+        stop_price: Some(Num::from(99)),
+        ..request.clone()
+      };
+      let err = <Create as Endpoint>::body(&request).unwrap_err();
+      assert!(err.to_string().contains("extended hours"), "{err}");
+    }
+  }
+
+  /// Check that a bracket order with a trailing stop loss can be
+  /// created, but that setting both trail price and trail percent is
+  /// rejected locally.
+  #[test]
+  fn create_body_trailing_stop_loss() {
+    let request = CreateReqInit {
+      class: Class::Bracket,
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      take_profit: Some(TakeProfit::Limit(Num::from(110))),
+      stop_loss: Some(StopLoss::TrailPrice(Num::from(2))),
+      ..Default::default()
+    }
+    .init("SPY", Side::Buy, Amount::quantity(1));
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["stop_loss"], serde_json::json!({"trail_price": "2"}));
+
+    let request = CreateReqInit {
+      type_: Type::TrailingStop,
+      trail_price: Some(Num::from(2)),
+      trail_percent: Some(Num::from(5)),
+      ..Default::default()
+    }
+    .init("SPY", Side::Buy, Amount::quantity(1));
+
+    let err = <Create as Endpoint>::body(&request).unwrap_err();
+    assert!(err.to_string().contains("mutually exclusive"), "{err}");
+  }
+
+  /// Check that we can parse, display, and serialize an `Id`.
+  #[test]
+  fn id_round_trip() {
+    let string = "904837e3-3b76-47ec-b432-046db621571b";
+    let id = Id::from_str(string).unwrap();
+    assert_eq!(id.to_string(), string);
+
+    let json = to_json(&id).unwrap();
+    assert_eq!(from_json::<Id>(&json).unwrap(), id);
   }
 
   /// Check that we can parse the `Amount::quantity` variant properly.
@@ -881,6 +2288,37 @@ mod tests {
     assert_eq!(amount, Amount::notional(Num::from_str("15.12").unwrap()));
   }
 
+  /// Check that we can parse an order submitted with a notional
+  /// amount, for which the quantity is reported as `null`.
+  #[test]
+  fn parse_notional_order() {
+    let json = |qty, notional| {
+      let order = order_json(json!({
+        "updated_at": null,
+        "submitted_at": null,
+        "qty": qty,
+        "notional": notional,
+        "status": "accepted",
+      }));
+      order.to_string()
+    };
+
+    let order = from_json::<Order>(json(json!(null), json!("25.5")).as_bytes()).unwrap();
+    assert_eq!(order.amount, Amount::notional(Num::new(255, 10)));
+
+    let order = from_json::<Order>(json(json!("3"), json!(null)).as_bytes()).unwrap();
+    assert_eq!(order.amount, Amount::quantity(3));
+
+    // The amount should round-trip without the `null` counterpart.
+    let json = to_json(&Order {
+      amount: Amount::notional(Num::new(255, 10)),
+      ..order
+    })
+    .unwrap();
+    let order = from_json::<Order>(&json).unwrap();
+    assert_eq!(order.amount, Amount::notional(Num::new(255, 10)));
+  }
+
   /// Verify that we can deserialize and serialize a reference order.
   #[test]
   fn deserialize_serialize_reference_order() {
@@ -965,6 +2403,486 @@ mod tests {
     assert_eq!(order.class, Class::Simple);
   }
 
+  /// Check that we round prices and quantities at a half way boundary
+  /// as per the rounding mode in use.
+  #[test]
+  fn round_with_mode() {
+    let price = |value: &str, mode| round_price(&Num::from_str(value).unwrap(), mode);
+    let num = |value: &str| Num::from_str(value).unwrap();
+
+    assert_eq!(RoundingMode::default(), RoundingMode::HalfUp);
+
+    assert_eq!(price("2.125", RoundingMode::HalfUp), num("2.13"));
+    assert_eq!(price("2.125", RoundingMode::HalfEven), num("2.12"));
+    assert_eq!(price("2.135", RoundingMode::HalfEven), num("2.14"));
+    assert_eq!(price("2.125", RoundingMode::TowardZero), num("2.12"));
+    assert_eq!(price("2.129", RoundingMode::TowardZero), num("2.12"));
+
+    assert_eq!(price("0.12345", RoundingMode::HalfUp), num("0.1235"));
+    assert_eq!(price("0.12345", RoundingMode::HalfEven), num("0.1234"));
+    assert_eq!(price("0.12345", RoundingMode::TowardZero), num("0.1234"));
+
+    let quantity = num("0.0000000015");
+    assert_eq!(
+      round_quantity(&quantity, RoundingMode::HalfUp),
+      num("0.000000002")
+    );
+    assert_eq!(
+      round_quantity(&quantity, RoundingMode::HalfEven),
+      num("0.000000002")
+    );
+    assert_eq!(
+      round_quantity(&quantity, RoundingMode::TowardZero),
+      num("0.000000001")
+    );
+
+    let value = num("-2.125");
+    assert_eq!(RoundingMode::HalfUp.round(&value, 2), num("-2.13"));
+    assert_eq!(RoundingMode::HalfEven.round(&value, 2), num("-2.12"));
+    assert_eq!(RoundingMode::TowardZero.round(&value, 2), num("-2.12"));
+  }
+
+  /// Check that we can deserialize orders reporting their type as
+  /// `type`, as `order_type`, or as both.
+  #[test]
+  fn deserialize_order_type_keys() {
+    let parse = |type_fields: Value| {
+      let mut json = order_json(json!({
+        "updated_at": null,
+        "submitted_at": null,
+        "qty": "15",
+        "limit_price": "107.00",
+        "status": "accepted",
+      }));
+      let object = json.as_object_mut().unwrap();
+      let _type = object.remove("type");
+      let Value::Object(mut type_fields) = type_fields else {
+        unreachable!()
+      };
+      let () = object.append(&mut type_fields);
+      from_json::<Order>(json.to_string().as_bytes())
+    };
+    let order = |type_fields| parse(type_fields).unwrap();
+
+    assert_eq!(order(json!({"type": "limit"})).type_, Type::Limit);
+    assert_eq!(order(json!({"order_type": "limit"})).type_, Type::Limit);
+    assert_eq!(
+      order(json!({"type": null, "order_type": "limit"})).type_,
+      Type::Limit
+    );
+    assert_eq!(
+      order(json!({"order_type": "limit", "type": "stop_limit"})).type_,
+      Type::StopLimit
+    );
+
+    let err = parse(json!({"type": null})).unwrap_err();
+    assert!(err.to_string().contains("missing field `type`"), "{err}");
+
+    // The type should always be serialized as `type`.
+    let json = to_json(&order(json!({"order_type": "limit"}))).unwrap();
+    let order = from_json::<Order>(&json).unwrap();
+    assert_eq!(order.type_, Type::Limit);
+  }
+
+  /// Check that we can calculate the weighted average fill price of a
+  /// partially filled bracket order.
+  #[test]
+  fn weighted_fill_price_partially_filled_bracket() {
+    let order = from_json::<Order>(&bracket_order()).unwrap();
+    assert_eq!(order.legs.len(), 2);
+    assert_eq!(order.legs[0].average_fill_price, Some(Num::from(110)));
+    assert_eq!(order.legs[1].average_fill_price, None);
+    // The entry fill of the parent order.
+    assert_eq!(order.weighted_fill_price(Side::Buy), Some(Num::from(100)));
+    // The exit fills of the take-profit leg; the stop-loss leg did not
+    // fill.
+    assert_eq!(order.weighted_fill_price(Side::Sell), Some(Num::from(110)));
+
+    // Fills of multiple legs on the same side are weighted by quantity.
+    let mut order = order;
+    order.legs[1].filled_quantity = Num::from(6);
+    order.legs[1].average_fill_price = Some(Num::from(95));
+    assert_eq!(order.weighted_fill_price(Side::Sell), Some(Num::from(101)));
+
+    let order = Order {
+      filled_quantity: Num::from(0),
+      average_fill_price: None,
+      legs: Vec::new(),
+      ..order
+    };
+    assert_eq!(order.weighted_fill_price(Side::Buy), None);
+    assert_eq!(order.weighted_fill_price(Side::Sell), None);
+  }
+
+  /// Check that the quantities of many small partial fills add up to
+  /// the order's filled quantity exactly.
+  #[test]
+  fn reconcile_partial_fills() {
+    // Fill the order in 1000 slices of 0.001 shares, which do not sum
+    // up to exactly 1 when using floating point arithmetic.
+    let slice = Num::new(1, 1000);
+    let mut quantities = vec![Some(slice.clone()); 1000];
+    let reported = Num::from(1);
+    assert_ne!((0..1000).map(|_| 0.001f64).sum::<f64>(), 1.0);
+
+    let reconciliation = reconcile_fills(quantities.iter().map(Option::as_ref), &reported);
+    assert_eq!(reconciliation.fills, 1000);
+    assert_eq!(reconciliation.unquantified, 0);
+    assert_eq!(reconciliation.total, Num::from(1));
+    assert_eq!(reconciliation.reported, Num::from(1));
+    assert!(reconciliation.is_consistent());
+    assert_eq!(reconciliation.discrepancy(), Num::from(0));
+
+    // A missed partial fill shows up as a discrepancy.
+    let _quantity = quantities.remove(500);
+    let reconciliation = reconcile_fills(quantities.iter().map(Option::as_ref), &reported);
+    assert_eq!(reconciliation.fills, 999);
+    assert!(!reconciliation.is_consistent());
+    assert_eq!(reconciliation.discrepancy(), slice);
+
+    // So does a fill without a quantity, which is reported as such.
+    let () = quantities.insert(500, None);
+    let reconciliation = reconcile_fills(quantities.iter().map(Option::as_ref), &reported);
+    assert_eq!(reconciliation.fills, 1000);
+    assert_eq!(reconciliation.unquantified, 1);
+    assert!(!reconciliation.is_consistent());
+    assert_eq!(reconciliation.discrepancy(), slice);
+
+    let reconciliation = reconcile_fills([], &Num::from(0));
+    assert_eq!(reconciliation.fills, 0);
+    assert!(reconciliation.is_consistent());
+  }
+
+  /// Check that slippage is signed by side, with unfavorable fills
+  /// resulting in positive values.
+  #[test]
+  fn slippage_signed_by_side() {
+    let json = order_json(json!({
+      "filled_at": "2018-10-05T05:48:59Z",
+      "filled_qty": "10",
+      "filled_avg_price": "100.05",
+      "status": "filled",
+    }));
+
+    let buy = from_json::<Order>(json.to_string().as_bytes()).unwrap();
+    // A market order has no implicit reference price.
+    assert_eq!(buy.slippage(None), None);
+
+    let slippage = buy.slippage(Some(&Num::from(100))).unwrap();
+    assert_eq!(slippage.absolute, Num::new(5, 100));
+    assert_eq!(slippage.basis_points, Num::from(5));
+
+    let sell = Order {
+      side: Side::Sell,
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      average_fill_price: Some(Num::new(9990, 100)),
+      ..buy
+    };
+    let slippage = sell.slippage(None).unwrap();
+    assert_eq!(slippage.absolute, Num::new(1, 10));
+    assert_eq!(slippage.basis_points, Num::from(10));
+
+    // A sell filled above the reference is favorable.
+    let slippage = sell.slippage(Some(&Num::new(9980, 100))).unwrap();
+    assert_eq!(slippage.absolute, Num::new(-1, 10));
+    assert_eq!(slippage.basis_points, Num::new(-10000, 998));
+  }
+
+  /// Check that generated client order IDs are unique, carry the
+  /// prefix, and are within the length limit.
+  #[test]
+  fn generate_client_order_ids() {
+    let generator = ClientOrderIdGenerator::new("momentum").unwrap();
+    assert_eq!(generator.prefix(), "momentum");
+
+    let ids = (0..1000)
+      .map(|_| generator.generate())
+      .collect::<HashSet<_>>();
+    assert_eq!(ids.len(), 1000);
+    for id in &ids {
+      assert!(id.starts_with("momentum-"), "{id}");
+      assert!(id.len() <= MAX_CLIENT_ORDER_ID_LEN, "{id}");
+    }
+
+    let prefix = "x".repeat(MAX_CLIENT_ORDER_ID_LEN - CLIENT_ORDER_ID_SUFFIX_LEN);
+    let generator = ClientOrderIdGenerator::new(prefix).unwrap();
+    assert_eq!(generator.generate().len(), MAX_CLIENT_ORDER_ID_LEN);
+
+    let prefix = "x".repeat(MAX_CLIENT_ORDER_ID_LEN - CLIENT_ORDER_ID_SUFFIX_LEN + 1);
+    let err = ClientOrderIdGenerator::new(prefix).unwrap_err();
+    assert!(err.to_string().contains("exceeds maximum length"), "{err}");
+  }
+
+  /// Check that client order IDs carry the time stamp reported by the
+  /// generator's clock.
+  #[test]
+  fn generate_client_order_ids_with_clock() {
+    let now = DateTime::<Utc>::from_str("2026-01-02T03:04:05.678Z").unwrap();
+    let generator = ClientOrderIdGenerator::with_clock("fixed", move || now).unwrap();
+
+    let id = generator.generate();
+    let (timestamp, _random) = id.strip_prefix("fixed-").unwrap().split_once('-').unwrap();
+    assert_eq!(
+      i64::from_str_radix(timestamp, 16).unwrap(),
+      now.timestamp_millis()
+    );
+    assert_ne!(generator.generate(), id);
+  }
+
+  /// Check that client order IDs with special characters are properly
+  /// encoded in the query of a `GetByClientId` request.
+  #[test]
+  fn encode_client_order_id_query() {
+    let client_order_id = "strat/1 a&b=c+d?".to_string();
+    let query = <GetByClientId as Endpoint>::query(&client_order_id)
+      .unwrap()
+      .unwrap();
+    assert_eq!(query, "client_order_id=strat%2F1+a%26b%3Dc%2Bd%3F");
+
+    let params = from_query::<Vec<(String, String)>>(&query).unwrap();
+    assert_eq!(
+      params,
+      vec![("client_order_id".to_string(), client_order_id)]
+    );
+  }
+
+  /// Create the JSON representation of an order with the given status.
+  fn order_with_status(status: &str) -> String {
+    let order = order_json(json!({
+      "updated_at": null,
+      "submitted_at": null,
+      "qty": "15",
+      "status": status,
+    }));
+    order.to_string()
+  }
+
+  /// Check that we can poll an order until it reaches a terminal
+  /// status.
+  #[test(tokio::test)]
+  async fn poll_order_until_filled() {
+    let mut responses = vec![
+      (StatusCode::OK, order_with_status("filled")),
+      (StatusCode::TOO_MANY_REQUESTS, "{}".to_string()),
+      (StatusCode::OK, order_with_status("partially_filled")),
+      (StatusCode::OK, order_with_status("new")),
+    ];
+    let server = Server::serve(move |_request| responses.pop().unwrap()).await;
+    let client = Client::new(server.api_info());
+    let id = Id(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap());
+
+    let interval = Duration::from_millis(1);
+    let timeout = Duration::from_secs(10);
+    let order = poll_until_terminal(&client, id, interval, timeout)
+      .await
+      .unwrap();
+    assert_eq!(order.status, Status::Filled);
+    assert_eq!(
+      server.requests(),
+      vec!["GET /v2/orders/904837e33b7647ecb432046db621571b".to_string(); 4]
+    );
+  }
+
+  /// Check that polling an order honors the delay requested by the
+  /// server when the rate limit was exceeded.
+  #[test(tokio::test)]
+  async fn poll_order_retry_after() {
+    let mut responses = vec![
+      (StatusCode::OK, Vec::new(), order_with_status("filled")),
+      (
+        StatusCode::TOO_MANY_REQUESTS,
+        vec![("retry-after".to_string(), "1".to_string())],
+        "{}".to_string(),
+      ),
+    ];
+    let server = Server::serve_with_headers(move |request| {
+      if request.starts_with("GET /v2/clock") {
+        (
+          StatusCode::TOO_MANY_REQUESTS,
+          vec![("retry-after".to_string(), "60".to_string())],
+          "{}".to_string(),
+        )
+      } else {
+        responses.pop().unwrap()
+      }
+    })
+    .await;
+    let client = Client::new(server.api_info());
+    let id = Id(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap());
+
+    let interval = Duration::from_millis(1);
+    let timeout = Duration::from_secs(10);
+    let start = Instant::now();
+    // While polling, an unrelated request issued through the same
+    // client is rejected with a much longer delay, which must not be
+    // honored for polling.
+    let unrelated = async {
+      let () = sleep(Duration::from_millis(100)).await;
+      client.issue::<clock::Get>(&()).await
+    };
+    let polled = poll_until_terminal(&client, id, interval, timeout);
+    let (order, _clock) = join(polled, unrelated).await;
+    let order = order.unwrap();
+    let elapsed = start.elapsed();
+    assert_eq!(order.status, Status::Filled);
+    assert!(elapsed >= Duration::from_secs(1), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(10), "{elapsed:?}");
+    assert_eq!(
+      server.requests(),
+      [
+        "GET /v2/orders/904837e33b7647ecb432046db621571b",
+        "GET /v2/clock",
+        "GET /v2/orders/904837e33b7647ecb432046db621571b",
+      ]
+    );
+
+    // If the requested delay exceeds the timeout we give up right
+    // away.
+    let server = Server::serve_with_headers(|_request| {
+      (
+        StatusCode::TOO_MANY_REQUESTS,
+        vec![("retry-after".to_string(), "60".to_string())],
+        "{}".to_string(),
+      )
+    })
+    .await;
+    let client = Client::new(server.api_info());
+    let err = poll_until_terminal(&client, id, interval, timeout)
+      .await
+      .unwrap_err();
+    match err {
+      PollError::Timeout(None) => (),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+    assert_eq!(server.requests().len(), 1);
+  }
+
+  /// Check that polling an order times out if it does not reach a
+  /// terminal status.
+  #[test(tokio::test)]
+  async fn poll_order_timeout() {
+    let server = Server::serve(|_request| (StatusCode::OK, order_with_status("new"))).await;
+    let client = Client::new(server.api_info());
+    let id = Id(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap());
+
+    let interval = Duration::from_millis(10);
+    let timeout = Duration::from_millis(50);
+    let err = poll_until_terminal(&client, id, interval, timeout)
+      .await
+      .unwrap_err();
+    match err {
+      PollError::Timeout(Some(order)) => assert_eq!(order.status, Status::New),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+  }
+
+  /// Check that we detect the rejection of an order due to a
+  /// duplicate client order ID.
+  #[test]
+  fn duplicate_client_order_id() {
+    let body = br#"{"code":40010001,"message":"client_order_id must be unique"}"#;
+    let err = <Create as Endpoint>::parse_err(body).unwrap();
+    assert_eq!(err.code, Some(40010001));
+
+    let status = StatusCode::UNPROCESSABLE_ENTITY;
+    let err = <Create as Endpoint>::evaluate(status, body).unwrap_err();
+    assert!(matches!(err, CreateError::InvalidInput(..)), "{err}");
+    assert!(err.is_duplicate_client_order_id());
+
+    let body = br#"{"code":40010001,"message":"qty must be > 0"}"#;
+    let err = <Create as Endpoint>::evaluate(status, body).unwrap_err();
+    assert!(!err.is_duplicate_client_order_id());
+  }
+
+  /// Check that we detect rejections due to the market being closed.
+  #[test]
+  fn market_closed() {
+    let body = br#"{"code":40310000,"message":"market is closed"}"#;
+    let err = <Create as Endpoint>::evaluate(StatusCode::FORBIDDEN, body).unwrap_err();
+    assert!(matches!(err, CreateError::NotPermitted(..)), "{err}");
+    assert!(err.is_market_closed());
+
+    let body = br#"{"code":42210000,"message":"Market closed for this asset"}"#;
+    let status = StatusCode::UNPROCESSABLE_ENTITY;
+    let err = <Create as Endpoint>::evaluate(status, body).unwrap_err();
+    assert!(err.is_market_closed());
+
+    let body = br#"{"code":40310000,"message":"insufficient buying power"}"#;
+    let err = <Create as Endpoint>::evaluate(StatusCode::FORBIDDEN, body).unwrap_err();
+    assert!(!err.is_market_closed());
+  }
+
+  /// Check that we can parse the multi-status response of the
+  /// cancellation of all open orders.
+  #[test]
+  fn parse_delete_all_response() {
+    let body = br#"[
+  {"id": "d56ba3ea-6d04-48ce-8175-817e242ee608", "status": 200, "body": {}},
+  {"id": "ac5b8c6b-8d41-4b8e-9e56-5ab2a1a4e0fa", "status": 500}
+]"#;
+    let status = StatusCode::MULTI_STATUS;
+    let results = <DeleteAll as Endpoint>::evaluate(status, body).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+      results[0].id,
+      Id(Uuid::parse_str("d56ba3ea-6d04-48ce-8175-817e242ee608").unwrap())
+    );
+    assert!(results[0].is_success());
+    assert_eq!(results[1].status, 500);
+    assert!(!results[1].is_success());
+
+    let results = <DeleteAll as Endpoint>::evaluate(status, b"[]").unwrap();
+    assert!(results.is_empty());
+  }
+
+  /// Check that we can convert a bracket order into a request for
+  /// creating an identical one.
+  #[test]
+  fn bracket_order_to_create_req() {
+    let order = from_json::<Order>(&bracket_order()).unwrap();
+    let request = order.to_create_req();
+
+    let expected = CreateReqInit {
+      class: Class::Bracket,
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      take_profit: Some(TakeProfit::Limit(Num::from(110))),
+      stop_loss: Some(StopLoss::Stop(Num::from(90))),
+      ..Default::default()
+    }
+    .init("AAPL", Side::Buy, Amount::quantity(10));
+    assert_eq!(request, expected);
+  }
+
+  /// Check that the server computed stop price of a trailing stop
+  /// order is not carried over into a request for creating an
+  /// identical one.
+  #[test]
+  fn trailing_stop_order_to_create_req() {
+    let json = order_json(json!({
+      "type": "trailing_stop",
+      "side": "sell",
+      "trail_price": "2",
+      "hwm": "150",
+      "stop_price": "148",
+    }));
+    let order = from_json::<Order>(json.to_string().as_bytes()).unwrap();
+    assert_eq!(order.high_water_mark, Some(Num::from(150)));
+    assert_eq!(order.stop_price, Some(Num::from(148)));
+
+    let request = order.to_create_req();
+    assert_eq!(request.type_, Type::TrailingStop);
+    assert_eq!(request.trail_price, Some(Num::from(2)));
+    assert_eq!(request.stop_price, None);
+
+    let body = <Create as Endpoint>::body(&request).unwrap().unwrap();
+    let json = from_json::<Value>(&body).unwrap();
+    assert_eq!(json["trail_price"], "2");
+    assert!(json.get("stop_price").is_none());
+    assert_eq!(from_json::<CreateReq>(&body).unwrap(), request);
+  }
+
   /// Check that we can serialize and deserialize a [`CreateReq`].
   #[test]
   fn serialize_deserialize_order_request() {
@@ -1132,6 +3050,38 @@ mod tests {
     assert_eq!(order.legs[1].status, Status::Held);
   }
 
+  /// Check that we can resubmit an order after converting it into a
+  /// `CreateReq`.
+  #[test(tokio::test)]
+  async fn resubmit_order() {
+    let request = CreateReqInit {
+      class: Class::Bracket,
+      type_: Type::Limit,
+      limit_price: Some(Num::from(2)),
+      take_profit: Some(TakeProfit::Limit(Num::from(3))),
+      stop_loss: Some(StopLoss::Stop(Num::from(1))),
+      ..Default::default()
+    }
+    .init("SPY", Side::Buy, Amount::quantity(1));
+
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let order = client.issue::<Create>(&request).await.unwrap();
+    client.issue::<Delete>(&order.id).await.unwrap();
+
+    let resubmit = order.to_create_req();
+    assert_eq!(resubmit, request);
+
+    let order = client.issue::<Create>(&resubmit).await.unwrap();
+    client.issue::<Delete>(&order.id).await.unwrap();
+
+    assert_eq!(order.symbol, "SPY");
+    assert_eq!(order.class, Class::Bracket);
+    assert_eq!(order.limit_price, Some(Num::from(2)));
+    assert_eq!(order.legs.len(), 2);
+  }
+
   #[test(tokio::test)]
   async fn submit_one_triggers_other_order() {
     let request = CreateReqInit {
@@ -1312,26 +3262,19 @@ mod tests {
     };
   }
 
-  #[test(tokio::test)]
-  async fn extended_hours_market_order() {
+  /// Check that an extended hours market order is rejected locally.
+  #[test]
+  fn extended_hours_market_order() {
     let request = CreateReqInit {
       extended_hours: true,
       ..Default::default()
     }
     .init("SPY", Side::Buy, Amount::quantity(1));
 
-    let api_info = ApiInfo::from_env().unwrap();
-    let client = Client::new(api_info);
-
     // We are submitting a market order with extended_hours, that is
     // invalid as per the Alpaca documentation.
-    let result = client.issue::<Create>(&request).await;
-    let err = result.unwrap_err();
-
-    match err {
-      RequestError::Endpoint(CreateError::InvalidInput(..)) => (),
-      _ => panic!("Received unexpected error: {err:?}"),
-    };
+    let err = <Create as Endpoint>::body(&request).unwrap_err();
+    assert!(matches!(err, ConversionError::InvalidRequest(..)), "{err}");
   }
 
   /// Check that we can change an existing order.
@@ -1381,6 +3324,117 @@ mod tests {
     }
   }
 
+  /// Check that we can change an order idempotently and retrieve the
+  /// replacement by its client order ID afterwards.
+  #[test(tokio::test)]
+  async fn change_order_idempotent() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let order = order_aapl(&client).await.unwrap();
+
+    let client_order_id = Uuid::new_v4().to_string();
+    let request = ChangeReq {
+      limit_price: Some(Num::from(2)),
+      client_order_id: Some(client_order_id.clone()),
+      ..Default::default()
+    };
+
+    let result = change_idempotent(&client, order.id, request).await;
+    let id = if let Ok(replaced) = &result {
+      replaced.id
+    } else {
+      order.id
+    };
+
+    let lookup = client.issue::<GetByClientId>(&client_order_id).await;
+    client.issue::<Delete>(&id).await.unwrap();
+
+    match result {
+      Ok(replaced) => {
+        assert_eq!(replaced.client_order_id, client_order_id);
+        assert_eq!(replaced.limit_price, Some(Num::from(2)));
+        assert_eq!(lookup.unwrap().id, replaced.id);
+      },
+      Err(RequestError::Endpoint(ChangeError::InvalidInput(..))) => (),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+  }
+
+  /// Check that a change failing at the transport level is not
+  /// reissued if the replacement order can be found by its client
+  /// order ID.
+  #[test(tokio::test)]
+  async fn change_order_idempotent_found() {
+    let mut changed = false;
+    let server = Server::serve_or_drop(move |request| {
+      if request.starts_with("PATCH /v2/orders/") {
+        assert!(!changed);
+        changed = true;
+        None
+      } else {
+        assert!(request.starts_with("GET /v2/orders:by_client_order_id?"));
+        Some((StatusCode::OK, Vec::new(), order_with_status("new")))
+      }
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let id = Id(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap());
+    let request = ChangeReq {
+      limit_price: Some(Num::from(2)),
+      client_order_id: Some("replacement".to_string()),
+      ..Default::default()
+    };
+    let order = change_idempotent(&client, id, request).await.unwrap();
+    assert_eq!(order.status, Status::New);
+    assert_eq!(
+      server.requests(),
+      [
+        "PATCH /v2/orders/904837e33b7647ecb432046db621571b",
+        "GET /v2/orders:by_client_order_id?client_order_id=replacement",
+      ]
+    );
+  }
+
+  /// Check that a change failing at the transport level is reissued
+  /// exactly once if no replacement order exists.
+  #[test(tokio::test)]
+  async fn change_order_idempotent_not_found() {
+    let mut changes = 0;
+    let server = Server::serve_or_drop(move |request| {
+      if request.starts_with("PATCH /v2/orders/") {
+        changes += 1;
+        if changes == 1 {
+          None
+        } else {
+          Some((StatusCode::OK, Vec::new(), order_with_status("new")))
+        }
+      } else {
+        let body = r#"{"code": 40410000, "message": "order not found"}"#.to_string();
+        Some((StatusCode::NOT_FOUND, Vec::new(), body))
+      }
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let id = Id(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap());
+    let request = ChangeReq {
+      limit_price: Some(Num::from(2)),
+      client_order_id: Some("replacement".to_string()),
+      ..Default::default()
+    };
+    let order = change_idempotent(&client, id, request).await.unwrap();
+    assert_eq!(order.status, Status::New);
+    assert_eq!(
+      server.requests(),
+      [
+        "PATCH /v2/orders/904837e33b7647ecb432046db621571b",
+        "GET /v2/orders:by_client_order_id?client_order_id=replacement",
+        "PATCH /v2/orders/904837e33b7647ecb432046db621571b",
+      ]
+    );
+  }
+
   /// Test changing of a trailing stop order.
   #[test(tokio::test)]
   async fn change_trail_stop_order() {
@@ -1458,7 +3512,9 @@ mod tests {
     let err = client.issue::<Create>(&request).await.unwrap_err();
 
     match err {
-      RequestError::Endpoint(CreateError::InvalidInput(..)) => (),
+      RequestError::Endpoint(ref err @ CreateError::InvalidInput(..)) => {
+        assert!(err.is_duplicate_client_order_id(), "{err}")
+      },
       _ => panic!("Received unexpected error: {err:?}"),
     };
   }
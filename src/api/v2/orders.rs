@@ -1,15 +1,38 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
+
+use futures::stream::unfold;
+use futures::Stream;
+
 use serde::Deserialize;
 use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
 
+use crate::api::v2::order;
 use crate::api::v2::order::Order;
+use crate::time_source::TimeSource;
+use crate::util::clamp_limit;
 use crate::util::string_slice_to_str;
 use crate::util::vec_from_comma_separated_str;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
+
+/// The maximum number of orders Alpaca reports in a single response.
+const MAX_PAGE_SIZE: usize = 500;
+/// The number of orders Alpaca reports in a single response if no
+/// limit is provided.
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+
 /// The status of orders to list.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Status {
@@ -25,6 +48,27 @@ pub enum Status {
 }
 
 
+/// The direction in which orders are reported.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Direction {
+  /// Report orders in descending order, i.e., from more recently
+  /// submitted orders to older ones.
+  #[serde(rename = "desc")]
+  Descending,
+  /// Report orders in ascending order, i.e., from older orders to more
+  /// recently submitted ones.
+  #[serde(rename = "asc")]
+  Ascending,
+}
+
+impl Default for Direction {
+  #[inline]
+  fn default() -> Self {
+    Self::Descending
+  }
+}
+
+
 /// A GET request to be made to the /v2/orders endpoint.
 // Note that we do not expose or supply all parameters that the Alpaca
 // API supports.
@@ -42,9 +86,24 @@ pub struct ListReq {
   #[serde(rename = "status")]
   pub status: Status,
   /// The maximum number of orders contained in the response. Defaults
-  /// to 50 and max is 500.
-  #[serde(rename = "limit")]
+  /// to 50 and max is 500. Larger values are clamped to the maximum
+  /// when issuing the request.
+  #[serde(rename = "limit", serialize_with = "clamp_limit::<MAX_PAGE_SIZE, _>")]
   pub limit: Option<usize>,
+  /// If set, only orders submitted before this time (exclusive) are
+  /// listed.
+  #[serde(rename = "until", skip_serializing_if = "Option::is_none")]
+  pub until: Option<DateTime<Utc>>,
+  /// If set, only orders submitted after this time (exclusive) are
+  /// listed.
+  #[serde(rename = "after", skip_serializing_if = "Option::is_none")]
+  pub after: Option<DateTime<Utc>>,
+  /// The chronological order in which orders are reported.
+  #[serde(rename = "direction", default)]
+  pub direction: Direction,
+  /// If set, only orders on this side are listed.
+  #[serde(rename = "side", default, skip_serializing_if = "Option::is_none")]
+  pub side: Option<order::Side>,
   /// If false the result will not roll up multi-leg orders under the
   /// legs field of the primary order.
   #[serde(rename = "nested")]
@@ -61,6 +120,10 @@ impl Default for ListReq {
       symbols: Vec::new(),
       status: Status::Open,
       limit: None,
+      until: None,
+      after: None,
+      direction: Direction::Descending,
+      side: None,
       // Nested orders merely appear as legs in each order being
       // returned. As such, having them included is very non-intrusive
       // and should be a reasonable default.
@@ -91,29 +154,253 @@ Endpoint! {
 }
 
 
+/// A page of orders as retrieved by [`list_page`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderPage {
+  /// The orders on the page.
+  pub orders: Vec<Order>,
+  /// Whether the number of orders reached the limit in effect for the
+  /// request, meaning that more orders are likely available.
+  pub truncated: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// List orders as per the provided request, reporting whether the
+/// result was likely truncated at the limit in effect.
+pub async fn list_page(
+  client: &Client,
+  request: &ListReq,
+) -> Result<OrderPage, RequestError<ListError>> {
+  // A limit of zero is treated just like an absent one.
+  let limit = request
+    .limit
+    .filter(|limit| *limit != 0)
+    .unwrap_or(DEFAULT_PAGE_SIZE)
+    .min(MAX_PAGE_SIZE);
+  let orders = client.issue::<List>(request).await?;
+  let truncated = orders.len() >= limit;
+
+  Ok(OrderPage {
+    orders,
+    truncated,
+    _non_exhaustive: (),
+  })
+}
+
+
+/// List all orders with the given status, paging through results in
+/// chunks of at most `page_size`.
+async fn list_all_paged(
+  client: &Client,
+  status: Status,
+  page_size: usize,
+) -> Result<Vec<Order>, RequestError<ListError>> {
+  let mut request = ListReq {
+    status,
+    limit: Some(page_size),
+    ..Default::default()
+  };
+  let mut seen = HashSet::new();
+  let mut all = Vec::new();
+
+  loop {
+    let OrderPage {
+      orders, truncated, ..
+    } = list_page(client, &request).await?;
+    // Orders are reported with the most recently submitted one first.
+    let oldest = orders
+      .last()
+      .map(|order| order.submitted_at.unwrap_or(order.created_at));
+
+    all.extend(orders.into_iter().filter(|order| seen.insert(order.id)));
+
+    match oldest {
+      Some(oldest) if truncated => {
+        // `until` is exclusive. More orders may have been submitted at
+        // the time of the oldest one reported than made it into the
+        // page, so we include that time in the next page, skipping
+        // orders already seen. Only if we had already done so do we
+        // move past it, to guarantee progress.
+        let until = oldest + TimeDelta::nanoseconds(1);
+        request.until = if request.until == Some(until) {
+          Some(oldest)
+        } else {
+          Some(until)
+        };
+      },
+      _ => break,
+    }
+  }
+  Ok(all)
+}
+
+
+/// List all orders with the given status.
+///
+/// Orders are retrieved page by page and reported with the most
+/// recently submitted one first. Only more orders than fit into a
+/// single response being submitted at the very same time could cause
+/// some to go missing.
+pub async fn list_all(
+  client: &Client,
+  status: Status,
+) -> Result<Vec<Order>, RequestError<ListError>> {
+  list_all_paged(client, status, MAX_PAGE_SIZE).await
+}
+
+
+/// List orders with the given status, paging through results in
+/// chunks of at most `page_size`, and retain those whose client order
+/// ID starts with `prefix`.
+async fn list_by_prefix_paged(
+  client: &Client,
+  prefix: &str,
+  status: Status,
+  page_size: usize,
+) -> Result<Vec<Order>, RequestError<ListError>> {
+  let orders = list_all_paged(client, status, page_size).await?;
+  let matching = orders
+    .into_iter()
+    .filter(|order| order.client_order_id.starts_with(prefix))
+    .collect();
+  Ok(matching)
+}
+
+
+/// List all orders with the given status whose client order ID starts
+/// with `prefix`.
+///
+/// Alpaca does not support filtering by client order ID, so all
+/// orders with the given status are retrieved, page by page, and
+/// filtered locally. Orders are reported with the most recently
+/// submitted one first. Only more orders than fit into a single
+/// response being submitted at the very same time could cause some
+/// to go missing.
+pub async fn list_by_prefix(
+  client: &Client,
+  prefix: &str,
+  status: Status,
+) -> Result<Vec<Order>, RequestError<ListError>> {
+  list_by_prefix_paged(client, prefix, status, MAX_PAGE_SIZE).await
+}
+
+
+/// List the orders submitted within consecutive time windows.
+///
+/// The time span from `start` (inclusive) to `end` (exclusive) is
+/// walked in windows of the given length, and the orders of each
+/// window are yielded as they are retrieved, i.e., in chronological
+/// order of windows. A window containing more orders than can be
+/// reported in a single response is split in halves recursively, so
+/// that coverage is complete and no order is reported twice. Only
+/// more orders than fit into a single response being submitted at
+/// the very same time could still cause some to go missing.
+///
+/// No orders can have been submitted in the future, so `end` is
+/// capped at the current time as reported by `clock`.
+pub fn list_windowed<C>(
+  client: &Client,
+  status: Status,
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+  window: TimeDelta,
+  clock: C,
+) -> impl Stream<Item = Result<Vec<Order>, RequestError<ListError>>> + '_
+where
+  C: TimeSource,
+{
+  list_windowed_paged(client, status, start, end, window, clock, MAX_PAGE_SIZE)
+}
+
+
+/// List the orders submitted within consecutive time windows, each
+/// retrieved using responses of at most `page_size` orders.
+fn list_windowed_paged<C>(
+  client: &Client,
+  status: Status,
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+  window: TimeDelta,
+  clock: C,
+  page_size: usize,
+) -> impl Stream<Item = Result<Vec<Order>, RequestError<ListError>>> + '_
+where
+  C: TimeSource,
+{
+  let end = end.min(clock.now());
+  let window = window.max(TimeDelta::nanoseconds(1));
+  let mut windows = VecDeque::new();
+  let mut window_start = start;
+  while window_start < end {
+    let window_end = (window_start + window).min(end);
+    let () = windows.push_back((window_start, window_end));
+    window_start = window_end;
+  }
+
+  unfold(windows, move |mut windows| async move {
+    while let Some((start, end)) = windows.pop_front() {
+      // Both `after` and `until` are exclusive, so we move `after` by
+      // the smallest possible amount to cover `start` itself.
+      let request = ListReq {
+        status,
+        limit: Some(page_size),
+        after: Some(start - TimeDelta::nanoseconds(1)),
+        until: Some(end),
+        ..Default::default()
+      };
+
+      match client.issue::<List>(&request).await {
+        Ok(orders) if orders.len() >= page_size && end - start > TimeDelta::nanoseconds(1) => {
+          let middle = start + (end - start) / 2;
+          let () = windows.push_front((middle, end));
+          let () = windows.push_front((start, middle));
+        },
+        Ok(orders) => return Some((Ok(orders), windows)),
+        Err(err) => return Some((Err(err), VecDeque::new())),
+      }
+    }
+    None
+  })
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
-  use futures::future::ok;
-  use futures::pin_mut;
-  use futures::StreamExt;
+  use std::collections::HashMap;
+  use std::str::FromStr as _;
+  #[cfg(not(feature = "streaming"))]
+  use std::time::Duration;
+
   use futures::TryStreamExt;
 
+  use http::StatusCode;
+
   use num_decimal::Num;
 
   use serde_json::from_slice as from_json;
+  use serde_json::json;
   use serde_json::to_vec as to_json;
   use serde_urlencoded::from_str as from_query;
   use serde_urlencoded::to_string as to_query;
 
   use test_log::test;
 
+  use uuid::Uuid;
+
+  use crate::api::v2::fixture;
   use crate::api::v2::order;
   use crate::api::v2::order_util::order_aapl;
   use crate::api::v2::order_util::order_stock;
+  #[cfg(feature = "streaming")]
   use crate::api::v2::updates;
   use crate::api_info::ApiInfo;
+  use crate::mock::Server;
+  use crate::time_source::SystemClock;
   use crate::Client;
 
 
@@ -156,9 +443,257 @@ mod tests {
     assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
   }
 
+  /// Make sure that we can serialize and deserialize a `ListReq` with
+  /// a side filter from a query string.
+  #[test]
+  fn serialize_deserialize_query_request_with_side() {
+    let mut request = ListReq {
+      symbols: vec!["ABC".into()],
+      status: Status::Closed,
+      side: Some(order::Side::Sell),
+      ..Default::default()
+    };
+
+    let query = to_query(&request).unwrap();
+    assert!(query.contains("side=sell"), "{query}");
+    assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
+
+    request.side = None;
+    let query = to_query(&request).unwrap();
+    assert!(!query.contains("side"), "{query}");
+    assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
+  }
+
+  /// Check that the time window and direction of a `ListReq` are
+  /// encoded in the query string.
+  #[test]
+  fn encode_time_window_query() {
+    let request = ListReq::default();
+    let query = to_query(&request).unwrap();
+    assert!(query.contains("direction=desc"), "{query}");
+    assert!(!query.contains("after"), "{query}");
+    assert!(!query.contains("until"), "{query}");
+
+    let request = ListReq {
+      status: Status::All,
+      after: Some(DateTime::from_str("2024-01-01T00:00:00Z").unwrap()),
+      until: Some(DateTime::from_str("2024-01-02T00:00:00Z").unwrap()),
+      direction: Direction::Ascending,
+      ..Default::default()
+    };
+    let query = to_query(&request).unwrap();
+    assert!(query.contains("after=2024-01-01T00%3A00%3A00Z"), "{query}");
+    assert!(query.contains("until=2024-01-02T00%3A00%3A00Z"), "{query}");
+    assert!(query.contains("direction=asc"), "{query}");
+    assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
+  }
+
+  /// Check that we report whether a page of orders was truncated at
+  /// the limit.
+  #[test(tokio::test)]
+  async fn list_truncated_page() {
+    let server = Server::serve(|request| {
+      let query = request.split_once('?').unwrap().1;
+      let params = from_query::<HashMap<String, String>>(query).unwrap();
+      let limit = params
+        .get("limit")
+        .map(|limit| limit.parse::<usize>().unwrap())
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+      let orders = (0..3)
+        .take(limit)
+        .map(|i| order_json(&i.to_string(), "2024-01-01T10:00:00Z"))
+        .collect::<Vec<_>>();
+      (StatusCode::OK, format!("[{}]", orders.join(",")))
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = ListReq {
+      limit: Some(3),
+      ..Default::default()
+    };
+    let page = list_page(&client, &request).await.unwrap();
+    assert_eq!(page.orders.len(), 3);
+    assert!(page.truncated);
+
+    let request = ListReq {
+      limit: Some(2),
+      ..Default::default()
+    };
+    let page = list_page(&client, &request).await.unwrap();
+    assert_eq!(page.orders.len(), 2);
+    assert!(page.truncated);
+
+    let request = ListReq {
+      limit: Some(4),
+      ..Default::default()
+    };
+    let page = list_page(&client, &request).await.unwrap();
+    assert_eq!(page.orders.len(), 3);
+    assert!(!page.truncated);
+
+    let page = list_page(&client, &ListReq::default()).await.unwrap();
+    assert!(!page.truncated);
+
+    let request = ListReq {
+      limit: Some(0),
+      ..Default::default()
+    };
+    let page = list_page(&client, &request).await.unwrap();
+    assert_eq!(page.orders.len(), 0);
+    assert!(!page.truncated);
+  }
+
+  /// Create the JSON representation of an order with the given client
+  /// order ID, submitted at the given time.
+  fn order_json(client_order_id: &str, submitted_at: &str) -> String {
+    let order = fixture::order_json(json!({
+      "id": Uuid::new_v4(),
+      "client_order_id": client_order_id,
+      "created_at": submitted_at,
+      "updated_at": submitted_at,
+      "submitted_at": submitted_at,
+      "qty": "1",
+    }));
+    order.to_string()
+  }
+
+  /// Check that we can list orders by client order ID prefix, paging
+  /// through all results, including orders sharing a timestamp with
+  /// the last one on a page.
+  #[test(tokio::test)]
+  async fn list_orders_by_prefix() {
+    let fixture = [
+      ("alpha-1", "2024-01-01T12:00:00Z"),
+      ("beta-1", "2024-01-01T11:00:00Z"),
+      ("alpha-2", "2024-01-01T11:00:00Z"),
+      ("alpha-3", "2024-01-01T10:00:00Z"),
+    ]
+    .map(|(id, submitted_at)| (order_json(id, submitted_at), submitted_at));
+
+    let server = Server::serve(move |request| {
+      let (_path, query) = request.split_once('?').unwrap();
+      let params = from_query::<HashMap<String, String>>(query).unwrap();
+      let until = params
+        .get("until")
+        .map(|until| DateTime::<Utc>::from_str(until).unwrap());
+      let limit = params["limit"].parse::<usize>().unwrap();
+
+      let orders = fixture
+        .iter()
+        .filter(|(_json, submitted_at)| {
+          until.is_none_or(|until| DateTime::<Utc>::from_str(submitted_at).unwrap() < until)
+        })
+        .take(limit)
+        .map(|(json, _submitted_at)| json.as_str())
+        .collect::<Vec<_>>();
+      (StatusCode::OK, format!("[{}]", orders.join(",")))
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let orders = list_by_prefix_paged(&client, "alpha-", Status::All, 2)
+      .await
+      .unwrap();
+    let ids = orders
+      .iter()
+      .map(|order| order.client_order_id.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(ids, ["alpha-1", "alpha-2", "alpha-3"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(
+      requests[1].contains("until=2024-01-01T11%3A00%3A00.000000001Z"),
+      "{}",
+      requests[1]
+    );
+    assert!(
+      requests[2].contains("until=2024-01-01T11%3A00%3A00Z"),
+      "{}",
+      requests[2]
+    );
+  }
+
+  /// Check that walking time windows covers all orders exactly once,
+  /// including ones submitted at window boundaries and ones sharing a
+  /// timestamp.
+  #[test(tokio::test)]
+  async fn list_orders_windowed() {
+    let fixture = [
+      ("a", "2024-01-01T10:00:00Z"),
+      ("b", "2024-01-01T12:00:00Z"),
+      ("c", "2024-01-02T00:00:00Z"),
+      ("d", "2024-01-02T08:00:00Z"),
+      ("e", "2024-01-02T08:00:00Z"),
+      ("f", "2024-01-02T09:00:00Z"),
+      ("g", "2024-01-03T23:59:59Z"),
+      ("h", "2024-01-04T00:00:00Z"),
+    ];
+
+    let server = Server::serve(move |request| {
+      let query = request.split_once('?').unwrap().1;
+      let params = from_query::<HashMap<String, String>>(query).unwrap();
+      let time = |name: &str| DateTime::<Utc>::from_str(&params[name]).unwrap();
+      let (after, until) = (time("after"), time("until"));
+      let limit = params["limit"].parse::<usize>().unwrap();
+
+      let orders = fixture
+        .iter()
+        .rev()
+        .filter(|(_id, submitted_at)| {
+          let submitted_at = DateTime::<Utc>::from_str(submitted_at).unwrap();
+          after < submitted_at && submitted_at < until
+        })
+        .take(limit)
+        .map(|(id, submitted_at)| order_json(id, submitted_at))
+        .collect::<Vec<_>>();
+      (StatusCode::OK, format!("[{}]", orders.join(",")))
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let start = DateTime::from_str("2024-01-01T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2024-01-04T00:00:00Z").unwrap();
+    let window = TimeDelta::days(1);
+    let windows = list_windowed_paged(&client, Status::All, start, end, window, SystemClock, 2)
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    let mut ids = windows
+      .iter()
+      .flatten()
+      .map(|order| order.client_order_id.as_str())
+      .collect::<Vec<_>>();
+    let () = ids.sort();
+    assert_eq!(ids, ["a", "b", "c", "d", "e", "f", "g"]);
+
+    // With a clock fixed before `end` no windows past the current
+    // time should be walked.
+    let now = || DateTime::from_str("2024-01-03T00:00:00Z").unwrap();
+    let windows = list_windowed_paged(&client, Status::All, start, end, window, now, 2)
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    let mut ids = windows
+      .iter()
+      .flatten()
+      .map(|order| order.client_order_id.as_str())
+      .collect::<Vec<_>>();
+    let () = ids.sort();
+    assert_eq!(ids, ["a", "b", "c", "d", "e", "f"]);
+  }
+
   /// Cancel an order and wait for the corresponding cancellation event
   /// to arrive.
+  #[cfg(feature = "streaming")]
   async fn cancel_order(client: &Client, id: order::Id) {
+    use futures::future::ok;
+    use futures::pin_mut;
+    use futures::StreamExt as _;
+
     let (stream, _subscription) = client.subscribe::<updates::OrderUpdates>().await.unwrap();
     pin_mut!(stream);
 
@@ -182,6 +717,21 @@ mod tests {
       .unwrap();
   }
 
+  /// Cancel an order and poll it until the cancellation is reflected.
+  #[cfg(not(feature = "streaming"))]
+  async fn cancel_order(client: &Client, id: order::Id) {
+    client.issue::<order::Delete>(&id).await.unwrap();
+
+    let _order = order::poll_until_terminal(
+      client,
+      id,
+      Duration::from_millis(250),
+      Duration::from_secs(10),
+    )
+    .await
+    .unwrap();
+  }
+
   /// Check that we can list existing orders.
   #[test(tokio::test)]
   #[ignore]
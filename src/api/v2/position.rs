@@ -9,6 +9,7 @@ use num_decimal::Num;
 
 use serde::Deserialize;
 use serde::Serialize;
+use serde_urlencoded::to_string as to_query;
 
 use crate::api::v2::asset;
 use crate::api::v2::order;
@@ -102,6 +103,31 @@ pub struct Position {
   pub _non_exhaustive: (),
 }
 
+impl Position {
+  /// Check whether all shares of the position are covered by open
+  /// orders, i.e., whether no shares are available for selling (or
+  /// covering) anymore.
+  #[inline]
+  pub fn is_fully_committed(&self) -> bool {
+    self.quantity_available.is_zero()
+  }
+
+  /// Calculate the profit/loss in dollar since the last day's close,
+  /// i.e., `(current_price - last_day_price) * quantity`, negated for
+  /// short positions.
+  ///
+  /// `None` is returned if either price is not available.
+  pub fn todays_pl(&self) -> Option<Num> {
+    let current = self.current_price.as_ref()?;
+    let last_day = self.last_day_price.as_ref()?;
+    let pl = (current - last_day) * &self.quantity;
+    match self.side {
+      Side::Long => Some(pl),
+      Side::Short => Some(-pl),
+    }
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/positions/{symbol}
@@ -123,10 +149,65 @@ Endpoint! {
 }
 
 
+/// The portion of a position to close.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Portion {
+  /// Close the given number of shares.
+  Quantity(Num),
+  /// Close the given percentage of the position, between 0 and 100.
+  Percentage(Num),
+}
+
+
+/// A DELETE request to be made to the /v2/positions/{symbol} endpoint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeleteReq {
+  /// The symbol or asset ID of the position to close.
+  pub symbol: asset::Symbol,
+  /// The portion of the position to close. The entire position is
+  /// closed if `None`.
+  pub portion: Option<Portion>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl From<asset::Symbol> for DeleteReq {
+  /// Create a request closing the entire position in `symbol`.
+  #[inline]
+  fn from(symbol: asset::Symbol) -> Self {
+    DeleteReqInit::default().init(symbol)
+  }
+}
+
+
+/// A helper for initializing [`DeleteReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeleteReqInit {
+  /// See `DeleteReq::portion`.
+  pub portion: Option<Portion>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl DeleteReqInit {
+  /// Create a [`DeleteReq`] from a `DeleteReqInit`.
+  #[inline]
+  pub fn init(self, symbol: asset::Symbol) -> DeleteReq {
+    DeleteReq {
+      symbol,
+      portion: self.portion,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
 Endpoint! {
   /// The representation of a DELETE request to the
   /// /v2/positions/{symbol} endpoint.
-  pub Delete(asset::Symbol),
+  pub Delete(DeleteReq),
   Ok => order::Order, [
     /// The position was liquidated successfully.
     /* 200 */ OK,
@@ -143,7 +224,33 @@ Endpoint! {
 
   #[inline]
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/positions/{input}").into()
+    format!("/v2/positions/{}", input.symbol).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    /// The query parameters of a `Delete` request.
+    #[derive(Serialize)]
+    struct Query<'n> {
+      /// The number of shares to close.
+      #[serde(rename = "qty", skip_serializing_if = "Option::is_none")]
+      quantity: Option<&'n Num>,
+      /// The percentage of the position to close.
+      #[serde(rename = "percentage", skip_serializing_if = "Option::is_none")]
+      percentage: Option<&'n Num>,
+    }
+
+    let query = match &input.portion {
+      None => return Ok(None),
+      Some(Portion::Quantity(quantity)) => Query {
+        quantity: Some(quantity),
+        percentage: None,
+      },
+      Some(Portion::Percentage(percentage)) => Query {
+        quantity: None,
+        percentage: Some(percentage),
+      },
+    };
+    Ok(Some(to_query(query)?.into()))
   }
 }
 
@@ -152,11 +259,17 @@ Endpoint! {
 mod tests {
   use super::*;
 
+  use std::str::FromStr as _;
+
+  use http_endpoint::Endpoint;
+
   use serde_json::from_str as from_json;
   use serde_json::to_string as to_json;
+  use serde_json::json;
 
   use test_log::test;
 
+  use crate::api::v2::fixture::position_json;
   use crate::api_info::ApiInfo;
   use crate::Client;
   use crate::RequestError;
@@ -211,6 +324,7 @@ mod tests {
     assert_eq!(pos.current_price, Some(Num::from(120)));
     assert_eq!(pos.last_day_price, Some(Num::from(119)));
     assert_eq!(pos.change_today, Some(Num::new(84, 10000)));
+    assert_eq!(pos.todays_pl(), Some(Num::from(5)));
   }
 
   /// Check that we can parse a position with a fractional quantity.
@@ -282,6 +396,32 @@ mod tests {
     assert_eq!(pos.symbol, "XLK");
     assert_eq!(pos.quantity, Num::from(24));
     assert_eq!(pos.quantity_available, Num::from(-24));
+    assert_eq!(pos.last_day_price, Some(Num::new(8891, 100)));
+    assert_eq!(
+      pos.change_today,
+      Some(Num::from_str("-0.0573613766730402").unwrap())
+    );
+    // The price dropped by 5.10 per share, which is a gain for a short
+    // position of 24 shares.
+    assert_eq!(pos.todays_pl(), Some(Num::new(12240, 100)));
+  }
+
+  /// Check that we can parse a position with shares committed to open
+  /// orders.
+  #[test]
+  fn parse_committed_position() {
+    let response = position_json(json!({"qty_available": "4"}));
+
+    let pos = from_json::<Position>(&response.to_string()).unwrap();
+    assert_eq!(pos.quantity, Num::from(10));
+    assert_eq!(pos.quantity_available, Num::from(4));
+    assert!(!pos.is_fully_committed());
+
+    let pos = Position {
+      quantity_available: Num::from(0),
+      ..pos
+    };
+    assert!(pos.is_fully_committed());
   }
 
   /// Check that we can retrieve an open position, if one exists.
@@ -308,6 +448,32 @@ mod tests {
     }
   }
 
+  /// Check that we encode the portion of a position to close in the
+  /// query of a `Delete` request.
+  #[test]
+  fn delete_request_query() {
+    let symbol = asset::Symbol::Sym("AAPL".to_string());
+    let request = DeleteReq::from(symbol.clone());
+    assert_eq!(<Delete as Endpoint>::path(&request), "/v2/positions/AAPL");
+    assert_eq!(<Delete as Endpoint>::query(&request).unwrap(), None);
+
+    let request = DeleteReqInit {
+      portion: Some(Portion::Quantity(Num::new(5, 2))),
+      ..Default::default()
+    }
+    .init(symbol.clone());
+    let query = <Delete as Endpoint>::query(&request).unwrap().unwrap();
+    assert_eq!(query, "qty=2.5");
+
+    let request = DeleteReqInit {
+      portion: Some(Portion::Percentage(Num::from(50))),
+      ..Default::default()
+    }
+    .init(symbol);
+    let query = <Delete as Endpoint>::query(&request).unwrap().unwrap();
+    assert_eq!(query, "percentage=50");
+  }
+
   /// Check that we error out as expected when attempting to delete a
   /// non-existent position.
   #[test(tokio::test)]
@@ -324,7 +490,7 @@ mod tests {
         continue
       }
 
-      let result = client.issue::<Delete>(&symbol).await;
+      let result = client.issue::<Delete>(&DeleteReq::from(symbol)).await;
       match result {
         Err(RequestError::Endpoint(DeleteError::NotFound(..))) => (),
         _ => panic!("Received unexpected result: {:?}", result),
@@ -1,7 +1,24 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::BTreeMap;
+
+use http::Method;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Error as JsonError;
+use serde_json::Value as JsonValue;
+use serde_urlencoded::to_string as to_query;
+
+use thiserror::Error;
+
+use crate::api::v2::order;
 use crate::api::v2::position::Position;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -21,10 +38,257 @@ Endpoint! {
 }
 
 
-// TODO: There is the possibility to issue a DELETE against the
-//       /v2/positions endpoint in order to liquidate all open
-//       positions, which may be interesting to use. However, that
-//       requires support for multi-status HTTP responses.
+/// A DELETE request to be made to the /v2/positions endpoint.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct DeleteAllReq {
+  /// Whether to cancel all open orders before liquidating positions.
+  #[serde(rename = "cancel_orders")]
+  pub cancel_orders: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The outcome of the attempted closing of a single position, as
+/// reported by the DELETE /v2/positions endpoint.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "CloseResultRepr")]
+pub struct CloseResult {
+  /// The symbol of the position that was attempted to be closed.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The HTTP status code reported for the closing of this position.
+  #[serde(rename = "status")]
+  pub status: u16,
+  /// The order submitted for closing the position, if any.
+  #[serde(rename = "body", default)]
+  pub order: Option<order::Order>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The wire representation of a [`CloseResult`].
+///
+/// For an unsuccessful closing the body contains error information
+/// instead of an order, so it can only be interpreted once the status
+/// is known.
+#[derive(Deserialize)]
+struct CloseResultRepr {
+  #[serde(rename = "symbol")]
+  symbol: String,
+  #[serde(rename = "status")]
+  status: u16,
+  #[serde(rename = "body", default)]
+  body: Option<JsonValue>,
+}
+
+impl TryFrom<CloseResultRepr> for CloseResult {
+  type Error = JsonError;
+
+  fn try_from(repr: CloseResultRepr) -> Result<Self, Self::Error> {
+    let CloseResultRepr {
+      symbol,
+      status,
+      body,
+    } = repr;
+
+    let order = match body {
+      Some(body) if (200..300).contains(&status) => Some(order::Order::deserialize(&body)?),
+      _ => None,
+    };
+
+    Ok(Self {
+      symbol,
+      status,
+      order,
+      _non_exhaustive: (),
+    })
+  }
+}
+
+impl CloseResult {
+  /// Check whether the position was closed successfully.
+  #[inline]
+  pub fn is_success(&self) -> bool {
+    (200..300).contains(&self.status)
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a DELETE request to the /v2/positions
+  /// endpoint, liquidating all open positions.
+  pub DeleteAll(DeleteAllReq),
+  Ok => Vec<CloseResult>, [
+    /// The liquidation of all open positions was attempted. The result
+    /// for each individual position is reported separately.
+    /* 207 */ MULTI_STATUS,
+  ],
+  Err => DeleteAllError, [
+    /// Liquidating open positions failed.
+    /* 500 */ INTERNAL_SERVER_ERROR => Failed,
+  ]
+
+  #[inline]
+  fn method() -> Method {
+    Method::DELETE
+  }
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/positions".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+/// A report of the actions taken by [`liquidate_all`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Liquidation {
+  /// The outcome of the cancellation of each open order.
+  pub canceled: Vec<order::CancelResult>,
+  /// The outcome of the closing of each open position.
+  pub closed: Vec<CloseResult>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An error as reported by [`liquidate_all`].
+#[derive(Debug, Error)]
+pub enum LiquidateError {
+  /// Failed to cancel open orders.
+  #[error("failed to cancel open orders")]
+  CancelOrders(#[source] RequestError<order::DeleteAllError>),
+  /// Failed to close open positions.
+  #[error("failed to close open positions")]
+  ClosePositions(#[source] RequestError<DeleteAllError>),
+}
+
+
+/// Flatten the account by canceling all open orders and then closing
+/// all open positions.
+///
+/// Orders are canceled first, so that shares committed to them are
+/// freed up. Cancellation is asynchronous, which is why positions are
+/// closed via [`DeleteAll`] with [`DeleteAllReq::cancel_orders`] set,
+/// which has Alpaca wait for all orders to be canceled before
+/// liquidating. Failures to cancel individual orders or to close
+/// individual positions are reported as part of the [`Liquidation`].
+pub async fn liquidate_all(client: &Client) -> Result<Liquidation, LiquidateError> {
+  let canceled = client
+    .issue::<order::DeleteAll>(&())
+    .await
+    .map_err(LiquidateError::CancelOrders)?;
+
+  let request = DeleteAllReq {
+    cancel_orders: true,
+    ..Default::default()
+  };
+  let closed = client
+    .issue::<DeleteAll>(&request)
+    .await
+    .map_err(LiquidateError::ClosePositions)?;
+
+  Ok(Liquidation {
+    canceled,
+    closed,
+    _non_exhaustive: (),
+  })
+}
+
+
+/// The change in market value of the position in a single symbol
+/// between two snapshots of positions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValueChange {
+  /// The symbol of the position.
+  pub symbol: String,
+  /// The market value of the position in the earlier snapshot, or
+  /// `None` if the position was opened in between.
+  pub before: Option<Num>,
+  /// The market value of the position in the later snapshot, or `None`
+  /// if the position was closed in between.
+  pub after: Option<Num>,
+  /// The change in market value, with an absent position counting as
+  /// zero.
+  pub change: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The changes in market value between two snapshots of positions, as
+/// reported by [`value_change`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValueChanges {
+  /// The per-symbol changes, ordered by symbol.
+  pub changes: Vec<ValueChange>,
+  /// The net change in market value across all positions.
+  pub total: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// Calculate the change in market value of positions between the
+/// `before` and `after` snapshots (as retrieved via [`List`]).
+///
+/// Positions are matched by symbol. Positions opened or closed in
+/// between are reported with a `None` value on the respective side. A
+/// market value not reported by Alpaca counts as zero. Note that
+/// Alpaca reports the market value of short positions as negative.
+pub fn value_change(before: &[Position], after: &[Position]) -> ValueChanges {
+  fn market_value(position: &Position) -> Num {
+    position
+      .market_value
+      .clone()
+      .unwrap_or_else(|| Num::from(0))
+  }
+
+  let mut values = BTreeMap::<&str, (Option<Num>, Option<Num>)>::new();
+  for position in before {
+    values.entry(&position.symbol).or_default().0 = Some(market_value(position));
+  }
+  for position in after {
+    values.entry(&position.symbol).or_default().1 = Some(market_value(position));
+  }
+
+  let mut total = Num::from(0);
+  let changes = values
+    .into_iter()
+    .map(|(symbol, (before, after))| {
+      let zero = Num::from(0);
+      let change = after.as_ref().unwrap_or(&zero) - before.as_ref().unwrap_or(&zero);
+      total += &change;
+
+      ValueChange {
+        symbol: symbol.to_string(),
+        before,
+        after,
+        change,
+        _non_exhaustive: (),
+      }
+    })
+    .collect();
+
+  ValueChanges {
+    changes,
+    total,
+    _non_exhaustive: (),
+  }
+}
 
 
 #[cfg(test)]
@@ -33,10 +297,197 @@ mod tests {
 
   use test_log::test;
 
+  use http::StatusCode;
+
+  use serde_json::from_str as from_json;
+  use serde_json::json;
+  use serde_json::to_string as to_json;
+
+  use uuid::Uuid;
+
+  use crate::api::v2::asset;
+  use crate::api::v2::fixture;
+  use crate::api::v2::position;
   use crate::api_info::ApiInfo;
+  use crate::mock::Server;
   use crate::Client;
 
 
+  /// Create the JSON representation of a (simple) order with the given
+  /// ID and side.
+  fn order_json(id: &str, side: &str) -> String {
+    let order = fixture::order_json(json!({
+      "id": id,
+      "client_order_id": id,
+      "side": side,
+    }));
+    order.to_string()
+  }
+
+
+  /// Create a position in the given symbol with the given side and
+  /// market value.
+  fn position(symbol: &str, side: &str, market_value: &str) -> Position {
+    let position = fixture::position_json(json!({
+      "symbol": symbol,
+      "side": side,
+      "market_value": market_value,
+      "unrealized_pl": null,
+      "unrealized_plpc": null,
+      "unrealized_intraday_pl": null,
+      "unrealized_intraday_plpc": null,
+      "current_price": null,
+      "lastday_price": null,
+      "change_today": null,
+    }));
+    from_json::<Position>(&position.to_string()).unwrap()
+  }
+
+
+  /// Check that we correctly attribute market value changes to opened,
+  /// closed, and changed positions.
+  #[test]
+  fn position_value_change() {
+    let before = [
+      position("AAPL", "long", "1000"),
+      position("MSFT", "long", "2500.5"),
+      position("SPY", "short", "-4000"),
+    ];
+    let after = [
+      position("SPY", "short", "-3900"),
+      position("AAPL", "long", "1020.25"),
+      position("TSLA", "long", "750"),
+    ];
+
+    let changes = value_change(&before, &after);
+    let summary = changes
+      .changes
+      .iter()
+      .map(|change| {
+        (
+          change.symbol.as_str(),
+          change.before.clone(),
+          change.after.clone(),
+          change.change.clone(),
+        )
+      })
+      .collect::<Vec<_>>();
+    let expected = vec![
+      (
+        "AAPL",
+        Some(Num::from(1000)),
+        Some(Num::new(102025, 100)),
+        Num::new(2025, 100),
+      ),
+      (
+        "MSFT",
+        Some(Num::new(25005, 10)),
+        None,
+        Num::new(-25005, 10),
+      ),
+      (
+        "SPY",
+        Some(Num::from(-4000)),
+        Some(Num::from(-3900)),
+        Num::from(100),
+      ),
+      ("TSLA", None, Some(Num::from(750)), Num::from(750)),
+    ];
+    assert_eq!(summary, expected);
+    // 20.25 - 2500.5 + 100 + 750
+    assert_eq!(changes.total, Num::new(-163025, 100));
+
+    let changes = value_change(&[], &[]);
+    assert!(changes.changes.is_empty());
+    assert_eq!(changes.total, Num::from(0));
+  }
+
+  /// Check that we can list open positions.
+  #[test(tokio::test)]
+  async fn list_mock_positions() {
+    let server = Server::serve(|_request| {
+      let positions = [
+        position("AAPL", "long", "1000.5"),
+        position("SPY", "short", "-4000"),
+      ];
+      (StatusCode::OK, to_json(&positions).unwrap())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let positions = client.issue::<List>(&()).await.unwrap();
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[0].symbol, "AAPL");
+    assert_eq!(positions[0].side, position::Side::Long);
+    assert_eq!(positions[0].quantity, Num::from(10));
+    assert_eq!(positions[0].average_entry_price, Num::from(100));
+    assert_eq!(positions[0].market_value, Some(Num::new(10005, 10)));
+    assert_eq!(positions[0].asset_class, asset::Class::UsEquity);
+    assert_eq!(positions[1].symbol, "SPY");
+    assert_eq!(positions[1].side, position::Side::Short);
+
+    assert_eq!(server.requests(), ["GET /v2/positions"]);
+  }
+
+  /// Check that the body of a close result is only interpreted as an
+  /// order if closing the position succeeded.
+  #[test]
+  fn parse_close_result() {
+    let json = r#"{"symbol": "SPY", "status": 403, "body": {"code": 40310000}}"#;
+    let result = from_json::<CloseResult>(json).unwrap();
+    assert!(!result.is_success());
+    assert_eq!(result.order, None);
+
+    let json = r#"{"symbol": "SPY", "status": 200, "body": {"id": "foobar"}}"#;
+    let _err = from_json::<CloseResult>(json).unwrap_err();
+
+    let json = r#"{"symbol": "SPY", "status": 200}"#;
+    let result = from_json::<CloseResult>(json).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.order, None);
+  }
+
+  /// Check that we can liquidate all positions in one go and evaluate
+  /// the per-position results.
+  #[test(tokio::test)]
+  async fn delete_all_mock_positions() {
+    const ORDER: &str = "b1d7a5a4-2c48-4c4f-a2a6-7d4f4e6d2a01";
+
+    let server = Server::serve(|_request| {
+      let body = format!(
+        r#"[
+  {{"symbol": "AAPL", "status": 200, "body": {}}},
+  {{"symbol": "SPY", "status": 403, "body": {{"code": 40310000, "message": "insufficient qty"}}}}
+]"#,
+        order_json(ORDER, "sell")
+      );
+      (StatusCode::MULTI_STATUS, body)
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = DeleteAllReq {
+      cancel_orders: true,
+      ..Default::default()
+    };
+    let results = client.issue::<DeleteAll>(&request).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].symbol, "AAPL");
+    assert!(results[0].is_success());
+    assert_eq!(
+      results[0].order.as_ref().unwrap().id,
+      order::Id(Uuid::parse_str(ORDER).unwrap())
+    );
+    assert_eq!(results[1].symbol, "SPY");
+    assert!(!results[1].is_success());
+    assert_eq!(results[1].order, None);
+
+    assert_eq!(
+      server.requests(),
+      ["DELETE /v2/positions?cancel_orders=true"]
+    );
+  }
+
   #[test(tokio::test)]
   async fn list_positions() {
     // We can't do much here except check that the request is not
@@ -45,4 +496,55 @@ mod tests {
     let client = Client::new(api_info);
     let _ = client.issue::<List>(&()).await.unwrap();
   }
+
+  /// Check that `liquidate_all` cancels open orders before closing
+  /// positions and reports what it did.
+  #[test(tokio::test)]
+  async fn liquidate_mock_account() {
+    const ORDER1: &str = "b1d7a5a4-2c48-4c4f-a2a6-7d4f4e6d2a01";
+    const ORDER2: &str = "b1d7a5a4-2c48-4c4f-a2a6-7d4f4e6d2a02";
+    const ORDER3: &str = "b1d7a5a4-2c48-4c4f-a2a6-7d4f4e6d2a03";
+
+    let server = Server::serve(|request| {
+      if request.starts_with("DELETE /v2/orders") {
+        let results = json!([
+          {"id": ORDER1, "status": 200},
+          {"id": ORDER2, "status": 500},
+        ]);
+        (StatusCode::MULTI_STATUS, results.to_string())
+      } else if request.starts_with("DELETE /v2/positions") {
+        let results = format!(
+          r#"[{{"symbol":"AAPL","status":200,"body":{}}}]"#,
+          order_json(ORDER3, "sell")
+        );
+        (StatusCode::MULTI_STATUS, results)
+      } else {
+        (StatusCode::NOT_FOUND, String::new())
+      }
+    })
+    .await;
+
+    let client = Client::new(server.api_info());
+    let liquidation = liquidate_all(&client).await.unwrap();
+
+    let id = |id| order::Id(Uuid::parse_str(id).unwrap());
+    assert_eq!(liquidation.canceled.len(), 2);
+    assert_eq!(liquidation.canceled[0].id, id(ORDER1));
+    assert!(liquidation.canceled[0].is_success());
+    assert_eq!(liquidation.canceled[1].id, id(ORDER2));
+    assert!(!liquidation.canceled[1].is_success());
+    assert_eq!(liquidation.closed.len(), 1);
+    assert!(liquidation.closed[0].is_success());
+    let order = liquidation.closed[0].order.as_ref().unwrap();
+    assert_eq!(order.id, id(ORDER3));
+    assert_eq!(order.side, order::Side::Sell);
+
+    assert_eq!(
+      server.requests(),
+      [
+        "DELETE /v2/orders",
+        "DELETE /v2/positions?cancel_orders=true",
+      ]
+    );
+  }
 }
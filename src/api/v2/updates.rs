@@ -2,17 +2,26 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 use async_trait::async_trait;
 
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
+
+use futures::future::ready;
 use futures::stream::Fuse;
 use futures::stream::Map;
 use futures::stream::SplitSink;
 use futures::stream::SplitStream;
 use futures::FutureExt as _;
 use futures::Sink;
+use futures::Stream as FutStream;
 use futures::StreamExt as _;
 
+use num_decimal::Num;
+
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::from_slice as json_from_slice;
@@ -20,6 +29,8 @@ use serde_json::from_str as json_from_str;
 use serde_json::to_string as to_json;
 use serde_json::Error as JsonError;
 
+use thiserror::Error as ThisError;
+
 use tokio::net::TcpStream;
 
 use tungstenite::MaybeTlsStream;
@@ -32,11 +43,16 @@ use websocket_util::wrap;
 use websocket_util::wrap::Wrapper;
 
 use crate::api::v2::order;
+use crate::api::v2::orders;
 use crate::api_info::ApiInfo;
+use crate::backpressure::Backpressure;
+use crate::subscribable::Resubscribable;
 use crate::subscribable::Subscribable;
 use crate::websocket::connect;
 use crate::websocket::MessageResult;
+use crate::Client;
 use crate::Error;
+use crate::RequestError;
 
 
 /// The status of an order, as reported as part of a `OrderUpdate`.
@@ -101,6 +117,20 @@ pub enum OrderStatus {
   /// for day), but remaining settlement calculations are still pending.
   #[serde(rename = "calculated")]
   Calculated,
+  /// A previously reported execution has been corrected, e.g., with
+  /// respect to its price or quantity.
+  ///
+  /// The order reported along with this event reflects the corrected
+  /// cumulative fill quantity and average fill price.
+  #[serde(rename = "trade_correction")]
+  TradeCorrection,
+  /// A previously reported execution has been canceled (busted) and
+  /// should be reversed.
+  ///
+  /// The order reported along with this event reflects the cumulative
+  /// fill quantity and average fill price without the execution.
+  #[serde(rename = "trade_cancel")]
+  TradeCancel,
   /// Any other status that we have not accounted for.
   ///
   /// Note that having any such status should be considered a bug.
@@ -109,6 +139,18 @@ pub enum OrderStatus {
   Unknown,
 }
 
+impl OrderStatus {
+  /// Check whether the status is terminal, i.e., whether no further
+  /// updates will occur for the order.
+  #[inline]
+  pub fn is_terminal(&self) -> bool {
+    matches!(
+      self,
+      Self::Replaced | Self::Filled | Self::Canceled | Self::Expired | Self::Rejected
+    )
+  }
+}
+
 
 /// An enumeration of the different event streams.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -240,6 +282,131 @@ pub struct OrderUpdate {
   /// The order that received an update.
   #[serde(rename = "order")]
   pub order: order::Order,
+  /// The time at which the event occurred, if reported. For fill
+  /// events this is the time of the fill as seen by the stream.
+  #[serde(rename = "timestamp", default, skip_serializing_if = "Option::is_none")]
+  pub timestamp: Option<DateTime<Utc>>,
+  /// The ID of the execution the event pertains to. Reported for fill,
+  /// partial fill, trade correction, and trade cancel events.
+  #[serde(
+    rename = "execution_id",
+    default,
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub execution_id: Option<String>,
+  /// The price of the execution the event pertains to.
+  #[serde(rename = "price", default, skip_serializing_if = "Option::is_none")]
+  pub price: Option<Num>,
+  /// The quantity of the execution the event pertains to.
+  #[serde(rename = "qty", default, skip_serializing_if = "Option::is_none")]
+  pub quantity: Option<Num>,
+  /// The size of the position after the event, with a negative value
+  /// indicating a short position.
+  #[serde(
+    rename = "position_qty",
+    default,
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub position_quantity: Option<Num>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl OrderUpdate {
+  /// Compute the difference between the event's timestamp and the
+  /// `filled_at` time reported for the order.
+  ///
+  /// A positive value means that the streamed event was time stamped
+  /// after the fill time reported by the REST API. `None` is returned
+  /// if either timestamp is unavailable.
+  pub fn fill_time_delta(&self) -> Option<TimeDelta> {
+    let timestamp = self.timestamp?;
+    let filled_at = self.order.filled_at?;
+    Some(timestamp - filled_at)
+  }
+}
+
+
+/// The consolidated state of an order, as emitted by [`order_states`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderState {
+  /// The most recent event reported for the order.
+  pub status: OrderStatus,
+  /// The most recent snapshot of the order.
+  pub order: order::Order,
+  /// All events reported for the order so far, in order of arrival.
+  pub events: Vec<OrderStatus>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl OrderState {
+  /// Fold an order update into the state of the corresponding order.
+  ///
+  /// Updates that are older than the most recently seen state of the
+  /// order are discarded and `None` is returned. Once an order reached
+  /// a terminal state its state is no longer tracked.
+  fn fold(states: &mut HashMap<order::Id, OrderState>, update: OrderUpdate) -> Option<Self> {
+    let OrderUpdate { event, order, .. } = update;
+    let id = order.id;
+
+    let state = match states.get_mut(&id) {
+      Some(state) => {
+        if order.updated_at < state.order.updated_at {
+          return None
+        }
+        state.status = event;
+        state.order = order;
+        state.events.push(event);
+        state.clone()
+      },
+      None => {
+        let state = OrderState {
+          status: event,
+          order,
+          events: vec![event],
+          _non_exhaustive: (),
+        };
+        let _prev = states.insert(id, state.clone());
+        state
+      },
+    };
+
+    if event.is_terminal() {
+      let _state = states.remove(&id);
+    }
+    Some(state)
+  }
+}
+
+
+/// Fold a stream of order updates into a stream of consolidated order
+/// states.
+///
+/// For every update received, the state of the affected order (i.e.,
+/// its most recent snapshot along with the history of events reported
+/// for it) is emitted. That relieves clients from having to track
+/// partial fills and similar transitions themselves. Errors are passed
+/// through unchanged.
+pub fn order_states<S>(
+  stream: S,
+) -> impl FutStream<Item = Result<Result<OrderState, JsonError>, WebSocketError>>
+where
+  S: FutStream<Item = Result<Result<OrderUpdate, JsonError>, WebSocketError>>,
+{
+  stream
+    .scan(HashMap::new(), |states, result| {
+      let result = match result {
+        Ok(Ok(update)) => OrderState::fold(states, update).map(Ok).map(Ok),
+        Ok(Err(err)) => Some(Ok(Err(err))),
+        Err(err) => Some(Err(err)),
+      };
+      ready(Some(result))
+    })
+    .filter_map(ready)
 }
 
 
@@ -376,7 +543,7 @@ pub enum OrderUpdates {}
 impl Subscribable for OrderUpdates {
   type Input = ApiInfo;
   type Subscription = Subscription<SplitSink<Stream, wrap::Message>>;
-  type Stream = Fuse<MessageStream<SplitStream<Stream>, ParsedMessage>>;
+  type Stream = Backpressure<Fuse<MessageStream<SplitStream<Stream>, ParsedMessage>>>;
 
   async fn connect(api_info: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error> {
     fn map(result: Result<wrap::Message, WebSocketError>) -> ParsedMessage {
@@ -388,12 +555,14 @@ impl Subscribable for OrderUpdates {
 
     let ApiInfo {
       api_stream_url: url,
-      key_id,
-      secret,
+      auth,
+      stream_buffer_size,
+      stream_backpressure,
       ..
     } = api_info;
+    let (key_id, secret) = auth.stream_credentials()?;
 
-    let stream = connect(url).await?.map(map as MapFn);
+    let stream = connect(url, *stream_buffer_size).await?.map(map as MapFn);
     let (send, recv) = stream.split();
     let (stream, subscription) = subscribe::subscribe(recv, send);
     let mut stream = stream.fuse();
@@ -405,7 +574,7 @@ impl Subscribable for OrderUpdates {
       .map_err(|result| {
         result
           .map(|result| Error::Json(result.unwrap_err()))
-          .map_err(Error::WebSocket)
+          .map_err(Error::from)
           .unwrap_or_else(|err| err)
       })???;
 
@@ -415,42 +584,128 @@ impl Subscribable for OrderUpdates {
       .map_err(|result| {
         result
           .map(|result| Error::Json(result.unwrap_err()))
-          .map_err(Error::WebSocket)
+          .map_err(Error::from)
           .unwrap_or_else(|err| err)
       })???;
 
+    let stream = Backpressure::new(stream, *stream_backpressure);
     Ok((stream, subscription))
   }
 }
 
+#[async_trait]
+impl Resubscribable for OrderUpdates {
+  type State = ();
+
+  #[inline]
+  fn state(_subscription: &Self::Subscription) -> Self::State {}
+
+  /// Order updates are listened to as part of establishing the
+  /// connection, so there is nothing to restore.
+  #[inline]
+  async fn resubscribe(
+    _state: &Self::State,
+    _stream: &mut Self::Stream,
+    _subscription: &mut Self::Subscription,
+  ) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+
+/// An error as reported by [`subscribe_with_snapshot`].
+#[derive(Debug, ThisError)]
+pub enum SnapshotError {
+  /// Failed to subscribe to order updates.
+  #[error("failed to subscribe to order updates")]
+  Subscribe(#[source] Error),
+  /// Failed to retrieve the currently open orders.
+  #[error("failed to retrieve open orders")]
+  List(#[source] RequestError<orders::ListError>),
+}
+
+
+/// Subscribe to order updates and retrieve a snapshot of all currently
+/// open orders.
+///
+/// The subscription is established before the snapshot is retrieved,
+/// so that no update can fall into the gap between the two: any order
+/// change happening after the snapshot was taken is reported on the
+/// returned stream. As a consequence, the stream may also report
+/// updates that the snapshot already reflects. These can be told apart
+/// by comparing [`Order::updated_at`][order::Order::updated_at], which
+/// is what [`order_states`] does when folding updates.
+pub async fn subscribe_with_snapshot(
+  client: &Client,
+) -> Result<
+  (
+    Vec<order::Order>,
+    <OrderUpdates as Subscribable>::Stream,
+    <OrderUpdates as Subscribable>::Subscription,
+  ),
+  SnapshotError,
+> {
+  let (stream, subscription) = client
+    .subscribe::<OrderUpdates>()
+    .await
+    .map_err(SnapshotError::Subscribe)?;
+  let orders = orders::list_all(client, orders::Status::Open)
+    .await
+    .map_err(SnapshotError::List)?;
+
+  Ok((orders, stream, subscription))
+}
+
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  use std::sync::Arc;
+  use std::sync::Mutex;
+  use std::time::Duration;
+
+  use futures::channel::mpsc::unbounded;
   use futures::channel::oneshot::channel;
   use futures::future::ok;
-  use futures::future::ready;
+  use futures::stream::iter;
   use futures::SinkExt;
   use futures::TryStreamExt;
 
+  use http::StatusCode;
+
   use serde_json::from_str as json_from_str;
+  use serde_json::json;
+  use serde_json::Value;
 
   use test_log::test;
 
+  use tokio::net::TcpListener;
+
+  use tungstenite::accept_async;
   use tungstenite::tungstenite::Bytes;
   use tungstenite::tungstenite::Utf8Bytes;
 
+  use url::Url;
+
+  use websocket_util::test::mock_server;
   use websocket_util::test::WebSocketStream;
   use websocket_util::tungstenite::error::ProtocolError;
   use websocket_util::tungstenite::Message;
 
+  use crate::api::v2::fixture;
   use crate::api::v2::order;
   use crate::api::v2::order_util::order_aapl;
   use crate::api::API_BASE_URL;
+  use crate::mock::Server;
   use crate::websocket::test::mock_stream;
+  use crate::websocket::test::KEY_ID;
+  use crate::websocket::test::SECRET;
+  use crate::Auth;
   use crate::Client;
   use crate::Error;
+  use crate::Resilient;
+  use crate::RetryConfig;
 
 
   // TODO: Until we can interpolate more complex expressions using
@@ -516,6 +771,213 @@ mod tests {
     }
   }
 
+  /// Check that we can decode an update to a trailing stop order
+  /// reflecting a moved high water mark.
+  #[test]
+  fn decode_trailing_stop_update() {
+    let order = fixture::order_json(json!({
+      "qty": "1",
+      "type": "trailing_stop",
+      "side": "sell",
+      "stop_price": "149.5",
+      "trail_price": "3",
+      "hwm": "152.5",
+    }));
+    let json = json!({"event": "replaced", "order": order});
+    let update = json_from_str::<OrderUpdate>(&json.to_string()).unwrap();
+    assert_eq!(update.event, OrderStatus::Replaced);
+    assert_eq!(update.order.type_, order::Type::TrailingStop);
+    assert_eq!(update.order.high_water_mark, Some(Num::new(1525, 10)));
+    assert_eq!(update.order.stop_price, Some(Num::new(1495, 10)));
+    assert_eq!(update.order.trail_price, Some(Num::from(3)));
+  }
+
+  /// Check that we can decode trade correction and trade cancel events
+  /// along with the details of the affected execution.
+  #[test]
+  fn decode_trade_correction_and_cancel() {
+    let event_json = |event: &str, filled_qty: &str, filled_avg_price: &str| {
+      let order = fixture::order_json(json!({
+        "filled_at": "2021-12-09T19:50:00Z",
+        "filled_qty": filled_qty,
+        "filled_avg_price": filled_avg_price,
+        "qty": "30",
+        "status": "partially_filled",
+      }));
+      let json = json!({
+        "event": event,
+        "execution_id": "99999999-8888-7777-6666-555555555555",
+        "price": "101.5",
+        "qty": "10",
+        "position_qty": "20",
+        "timestamp": "2021-12-09T19:55:01Z",
+        "order": order,
+      });
+      json.to_string()
+    };
+
+    let json = event_json("trade_correction", "20", "101.25");
+    let correction = json_from_str::<OrderUpdate>(&json).unwrap();
+    assert_eq!(correction.event, OrderStatus::TradeCorrection);
+    assert!(!correction.event.is_terminal());
+    assert_eq!(
+      correction.execution_id.as_deref(),
+      Some("99999999-8888-7777-6666-555555555555")
+    );
+    assert_eq!(correction.price, Some(Num::new(1015, 10)));
+    assert_eq!(correction.quantity, Some(Num::from(10)));
+    assert_eq!(correction.position_quantity, Some(Num::from(20)));
+    assert_eq!(correction.order.filled_quantity, Num::from(20));
+    assert_eq!(
+      correction.order.average_fill_price,
+      Some(Num::new(10125, 100))
+    );
+
+    let json = event_json("trade_cancel", "10", "101");
+    let cancel = json_from_str::<OrderUpdate>(&json).unwrap();
+    assert_eq!(cancel.event, OrderStatus::TradeCancel);
+    assert_eq!(cancel.order.filled_quantity, Num::from(10));
+    assert_eq!(cancel.order.average_fill_price, Some(Num::from(101)));
+
+    // Make sure that we can round-trip the execution details.
+    let json = to_json(&cancel).unwrap();
+    assert_eq!(json_from_str::<OrderUpdate>(&json).unwrap(), cancel);
+  }
+
+  /// Check that the execution details of fill events are decoded, and
+  /// absent for other events.
+  #[test]
+  fn decode_partial_fill_details() {
+    let update = |mut json: Value| {
+      json["order"] = fixture::order_json(json!({
+        "filled_qty": "4",
+        "filled_avg_price": "101.5",
+        "type": "limit",
+        "limit_price": "102",
+        "status": "partially_filled",
+      }));
+      json_from_str::<OrderUpdate>(&json.to_string()).unwrap()
+    };
+
+    let fill = update(json!({
+      "event": "partial_fill",
+      "price": "101.5",
+      "qty": "4",
+      "position_qty": "-6",
+    }));
+    assert_eq!(fill.event, OrderStatus::PartialFill);
+    assert_eq!(fill.price, Some(Num::new(1015, 10)));
+    assert_eq!(fill.quantity, Some(Num::from(4)));
+    assert_eq!(fill.position_quantity, Some(Num::from(-6)));
+    assert_eq!(fill.order.filled_quantity, Num::from(4));
+
+    let new = update(json!({"event": "new"}));
+    assert_eq!(new.price, None);
+    assert_eq!(new.quantity, None);
+    assert_eq!(new.position_quantity, None);
+  }
+
+  /// Check that we can compute the delta between the streamed fill
+  /// time and the fill time reported for the order.
+  #[test]
+  fn fill_time_delta() {
+    let order = fixture::order_json(json!({
+      "filled_at": "2021-12-09T19:48:47.250Z",
+      "filled_avg_price": "1",
+      "filled_qty": "1",
+      "qty": "1",
+      "status": "filled",
+    }));
+    let json = json!({
+      "event": "fill",
+      "execution_id": "11111111-2222-3333-4444-555555555555",
+      "order": order,
+      "timestamp": "2021-12-09T19:48:47.275Z",
+    });
+    let mut update = json_from_str::<OrderUpdate>(&json.to_string()).unwrap();
+    assert_eq!(
+      update.timestamp,
+      Some(
+        DateTime::parse_from_rfc3339("2021-12-09T19:48:47.275Z")
+          .unwrap()
+          .into()
+      )
+    );
+    assert_eq!(update.fill_time_delta(), Some(TimeDelta::milliseconds(25)));
+
+    update.timestamp = None;
+    assert_eq!(update.fill_time_delta(), None);
+  }
+
+  /// Check that we fold a sequence of order updates into the expected
+  /// order states.
+  #[test(tokio::test)]
+  async fn fold_order_states() {
+    fn update(event: &str, status: &str, filled: &str, updated_at: &str) -> OrderUpdate {
+      let order = fixture::order_json(json!({
+        "filled_qty": filled,
+        "status": status,
+        "updated_at": updated_at,
+      }));
+      let json = json!({"event": event, "order": order});
+      json_from_str::<OrderUpdate>(&json.to_string()).unwrap()
+    }
+
+    let updates = vec![
+      update("new", "new", "0", "2021-12-09T19:48:47Z"),
+      update(
+        "partial_fill",
+        "partially_filled",
+        "4",
+        "2021-12-09T19:48:48Z",
+      ),
+      // A stale update that arrived out of order.
+      update("new", "new", "0", "2021-12-09T19:48:47Z"),
+      update("fill", "filled", "10", "2021-12-09T19:48:49Z"),
+    ];
+
+    let stream = iter(updates.into_iter().map(|update| Ok(Ok(update))));
+    let states = order_states(stream)
+      .map(|result| result.unwrap().unwrap())
+      .collect::<Vec<_>>()
+      .await;
+
+    assert_eq!(states.len(), 3);
+    assert_eq!(states[0].status, OrderStatus::New);
+    assert_eq!(states[0].order.filled_quantity, Num::from(0));
+    assert_eq!(states[1].status, OrderStatus::PartialFill);
+    assert_eq!(states[1].order.filled_quantity, Num::from(4));
+    assert_eq!(
+      states[1].events,
+      vec![OrderStatus::New, OrderStatus::PartialFill]
+    );
+    assert_eq!(states[2].status, OrderStatus::Filled);
+    assert_eq!(states[2].order.status, order::Status::Filled);
+    assert_eq!(states[2].order.filled_quantity, Num::from(10));
+    assert_eq!(
+      states[2].events,
+      vec![
+        OrderStatus::New,
+        OrderStatus::PartialFill,
+        OrderStatus::Filled
+      ]
+    );
+  }
+
+  /// Check that subscribing to order updates is rejected when
+  /// authenticating via OAuth.
+  #[test(tokio::test)]
+  async fn reject_oauth_subscription() {
+    let api_info = ApiInfo::from_oauth(API_BASE_URL, "access-token").unwrap();
+    let client = Client::new(api_info);
+
+    let err = client.subscribe::<OrderUpdates>().await.unwrap_err();
+    match err {
+      Error::Str(message) => assert!(message.contains("OAuth"), "{message}"),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+  }
+
   /// Verify that we can decode a authentication control message.
   #[test]
   fn decode_authentication() {
@@ -573,9 +1035,11 @@ mod tests {
     let result = mock_stream::<OrderUpdates, _, _>(test).await;
     match result {
       Ok(..) => panic!("authentication succeeded unexpectedly"),
-      Err(Error::WebSocket(WebSocketError::Protocol(
-        ProtocolError::ResetWithoutClosingHandshake,
-      ))) => {},
+      Err(Error::WebSocket(err))
+        if matches!(
+          *err,
+          WebSocketError::Protocol(ProtocolError::ResetWithoutClosingHandshake)
+        ) => {},
       Err(e) => panic!("received unexpected error: {e}"),
     }
   }
@@ -639,7 +1103,11 @@ mod tests {
 
     let err = mock_stream::<OrderUpdates, _, _>(test).await.unwrap_err();
     match err {
-      Error::WebSocket(WebSocketError::Protocol(ProtocolError::ResetWithoutClosingHandshake)) => {},
+      Error::WebSocket(err)
+        if matches!(
+          *err,
+          WebSocketError::Protocol(ProtocolError::ResetWithoutClosingHandshake)
+        ) => {},
       e => panic!("received unexpected error: {e}"),
     }
   }
@@ -757,6 +1225,182 @@ mod tests {
       .unwrap();
   }
 
+  /// Create the JSON representation of a new order with the given ID.
+  fn order_json(id: &str) -> String {
+    let order = fixture::order_json(json!({
+      "id": id,
+      "client_order_id": id,
+      "qty": "1",
+      "type": "limit",
+      "limit_price": "1",
+    }));
+    order.to_string()
+  }
+
+  /// Check that the order update subscription is established before
+  /// the snapshot of open orders is retrieved and that updates sent in
+  /// between are not lost.
+  #[test(tokio::test)]
+  async fn subscribe_with_open_orders_snapshot() {
+    const SNAPSHOT_ID: &str = "11111111-1111-1111-1111-111111111111";
+    const UPDATE_ID: &str = "22222222-2222-2222-2222-222222222222";
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (listed_send, listed_recv) = channel();
+
+    let ws_events = events.clone();
+    let test = |mut stream: WebSocketStream| async move {
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(STREAM_REQ)),
+      );
+      let () = ws_events.lock().unwrap().push("listen");
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(STREAM_RESP)))
+        .await?;
+
+      // Report an update while the snapshot is being retrieved. It
+      // has to show up on the stream.
+      let () = listed_recv.await.unwrap();
+      let update = format!(
+        r#"{{"stream":"trade_updates","data":{{"event":"new","order":{}}}}}"#,
+        order_json(UPDATE_ID)
+      );
+      stream.send(Message::Text(update.into())).await?;
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    };
+    let addr = mock_server(test).await;
+
+    let http_events = events.clone();
+    let mut listed_send = Some(listed_send);
+    let server = Server::serve(move |_request| {
+      let () = http_events.lock().unwrap().push("list");
+      if let Some(send) = listed_send.take() {
+        let () = send.send(()).unwrap();
+      }
+      (StatusCode::OK, format!("[{}]", order_json(SNAPSHOT_ID)))
+    })
+    .await;
+
+    let mut api_info = server.api_info();
+    api_info.api_stream_url = Url::parse(&format!("ws://{addr}")).unwrap();
+    api_info.auth = Auth::Keys {
+      key_id: KEY_ID.to_string(),
+      secret: SECRET.to_string(),
+    };
+    let client = Client::new(api_info);
+
+    let (orders, stream, _subscription) = subscribe_with_snapshot(&client).await.unwrap();
+    assert_eq!(*events.lock().unwrap(), ["listen", "list"]);
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].id.to_string(), SNAPSHOT_ID);
+    assert!(server.requests()[0].contains("status=open"));
+
+    let updates = stream
+      .map_err(Error::from)
+      .try_filter_map(|result| ok(result.ok()))
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].order.id.to_string(), UPDATE_ID);
+  }
+
+  /// Check that a resilient subscription reconnects once the
+  /// connection is closed, reports every reconnect attempt, and gives
+  /// up eventually.
+  #[test(tokio::test)]
+  async fn subscribe_resilient_reconnect() {
+    const FIRST_ID: &str = "11111111-1111-1111-1111-111111111111";
+    const SECOND_ID: &str = "22222222-2222-2222-2222-222222222222";
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (go_send, mut go_recv) = unbounded();
+
+    // Serve two connections, each sending a single update once
+    // signaled, and then closing it.
+    let _handle = tokio::spawn(async move {
+      for id in [FIRST_ID, SECOND_ID] {
+        let (stream, _addr) = listener.accept().await.unwrap();
+        let mut stream = accept_async(stream).await.unwrap();
+
+        let message = stream.next().await.unwrap().unwrap();
+        assert_eq!(message, Message::Text(Utf8Bytes::from_static(AUTH_REQ)));
+        let message = Message::Text(Utf8Bytes::from_static(AUTH_RESP));
+        let () = stream.send(message).await.unwrap();
+
+        let message = stream.next().await.unwrap().unwrap();
+        assert_eq!(message, Message::Text(Utf8Bytes::from_static(STREAM_REQ)));
+        let message = Message::Text(Utf8Bytes::from_static(STREAM_RESP));
+        let () = stream.send(message).await.unwrap();
+
+        let () = go_recv.next().await.unwrap();
+        let update = format!(
+          r#"{{"stream":"trade_updates","data":{{"event":"new","order":{}}}}}"#,
+          order_json(id)
+        );
+        let () = stream.send(Message::Text(update.into())).await.unwrap();
+        let () = stream.send(Message::Close(None)).await.unwrap();
+      }
+    });
+
+    let mut api_info = ApiInfo::from_parts(API_BASE_URL, KEY_ID, SECRET).unwrap();
+    api_info.api_stream_url = Url::parse(&format!("ws://{addr}")).unwrap();
+    let client = Client::new(api_info);
+
+    let config = RetryConfig {
+      max_attempts: 2,
+      base_delay: Duration::from_millis(10),
+      ..Default::default()
+    };
+    let (mut stream, _subscription) = client
+      .subscribe_resilient::<OrderUpdates>(config)
+      .await
+      .unwrap();
+    let () = go_send.unbounded_send(()).unwrap();
+
+    let mut ids = Vec::new();
+    let mut attempts = Vec::new();
+    let mut reconnects = 0;
+    let mut disconnected = false;
+
+    while let Some(event) = stream.next().await {
+      match event {
+        Resilient::Message(Ok(Ok(update))) => ids.push(update.order.id.to_string()),
+        Resilient::Message(_) => (),
+        Resilient::Reconnecting { attempt, error } => attempts.push((attempt, error.is_some())),
+        Resilient::Reconnected(_subscription) => {
+          reconnects += 1;
+          let () = go_send.unbounded_send(()).unwrap();
+        },
+        Resilient::Disconnected(_err) => disconnected = true,
+      }
+    }
+
+    assert_eq!(ids, vec![FIRST_ID, SECOND_ID]);
+    // The first reconnect succeeds. Once the server is gone, both
+    // attempts fail, with the second one being triggered by the
+    // failure of the first.
+    assert_eq!(attempts, vec![(1, false), (1, false), (2, true)]);
+    assert_eq!(reconnects, 1);
+    assert!(disconnected);
+
+    let stats = stream.stats();
+    assert_eq!(stats.attempts(), 3);
+    assert_eq!(stats.reconnects(), 1);
+    assert_eq!(stats.failures(), 2);
+  }
+
   /// Test the end-to-end workflow of streaming an order update for a
   /// newly created order.
   #[test(tokio::test)]
@@ -12,7 +12,6 @@ use http_endpoint::Bytes;
 use serde::Deserialize;
 use serde::Serialize;
 
-use serde_json::from_slice as from_json;
 use serde_json::to_vec as to_json;
 
 use uuid::Uuid;
@@ -75,6 +74,7 @@ pub struct CreateReq {
   pub symbols: Vec<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
+  #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
@@ -230,7 +230,7 @@ EndpointNoParse! {
   }
 
   fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
-    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+    Self::ApiError::parse(body)
   }
 }
 
@@ -239,8 +239,13 @@ EndpointNoParse! {
 mod tests {
   use super::*;
 
+  use std::str::from_utf8;
+
+  use http::StatusCode;
+
   use crate::api::v2::account;
   use crate::api_info::ApiInfo;
+  use crate::mock::Server;
   use crate::Client;
   use crate::RequestError;
 
@@ -364,6 +369,49 @@ mod tests {
     assert_eq!(symbols, vec!["AMZN", "SPY"]);
   }
 
+  /// Check that an update replaces the full set of symbols of a
+  /// watchlist in a single PUT request.
+  #[test(tokio::test)]
+  async fn update_replaces_symbols() {
+    const ID: &str = "fb306e55-16d3-4118-8c3d-c1615fcd4c03";
+
+    let server = Server::serve(|_request| {
+      let body = format!(
+        r#"{{
+        "id": "{ID}",
+        "account_id": "bbd63e1e-5dd6-4a82-a6e2-a3c53fc0b4e5",
+        "created_at": "2026-01-05T12:00:00Z",
+        "updated_at": "2026-01-06T12:00:00Z",
+        "name": "tech",
+        "assets": []
+      }}"#
+      );
+      (StatusCode::OK, body)
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let id = Id(Uuid::parse_str(ID).unwrap());
+    let request = UpdateReqInit {
+      symbols: vec!["AAPL".to_string(), "MSFT".to_string(), "NVDA".to_string()],
+      ..Default::default()
+    }
+    .init("tech");
+    let watchlist = client.issue::<Update>(&(id, request)).await.unwrap();
+    assert_eq!(watchlist.id, id);
+    assert_eq!(watchlist.name, "tech");
+
+    assert_eq!(
+      server.requests(),
+      [format!("PUT /v2/watchlists/{}", id.as_simple())]
+    );
+    let bodies = server.bodies();
+    assert_eq!(
+      from_utf8(&bodies[0]).unwrap(),
+      r#"{"name":"tech","symbols":["AAPL","MSFT","NVDA"]}"#
+    );
+  }
+
   /// Verify that we report the appropriate error when attempting to
   /// delete a watchlist that does not exist.
   #[test(tokio::test)]
@@ -3,18 +3,29 @@
 
 use std::env::var_os;
 use std::ffi::OsString;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 
 use url::Url;
 
 use crate::api::API_BASE_URL;
+use crate::backpressure::BackpressureStrategy;
 use crate::data::DATA_BASE_URL;
 use crate::data::DATA_STREAM_BASE_URL;
 use crate::Error;
 
 /// The base URL of the Trading API to use.
 const ENV_API_BASE_URL: &str = "APCA_API_BASE_URL";
+/// The base URL of the data API to use.
+const ENV_DATA_URL: &str = "APCA_DATA_URL";
+/// The base URL of the data API to use, as an alternative to
+/// `APCA_DATA_URL`.
+const ENV_API_DATA_URL: &str = "APCA_API_DATA_URL";
 /// The URL of the websocket stream portion of the Trading API to use.
 const ENV_API_STREAM_URL: &str = "APCA_API_STREAM_URL";
+/// The websocket base URL for streaming of data.
+const ENV_DATA_STREAM_URL: &str = "APCA_API_DATA_STREAM_URL";
 /// The environment variable representing the key ID.
 const ENV_KEY_ID: &str = "APCA_API_KEY_ID";
 /// The environment variable representing the secret key.
@@ -33,9 +44,96 @@ fn make_api_stream_url(base_url: Url) -> Result<Url, Error> {
 }
 
 
+/// Retrieve a URL from the environment variable `name`, falling back
+/// to the one provided by `default` if the variable is not set.
+fn url_from_env<F>(name: &str, default: F) -> Result<Url, Error>
+where
+  F: FnOnce() -> Result<Url, Error>,
+{
+  url_from_var(name, var_os(name), default)
+}
+
+
+/// Parse a URL from the `value` of the environment variable `name`,
+/// falling back to the one provided by `default` if it is not set.
+fn url_from_var<F>(name: &str, value: Option<OsString>, default: F) -> Result<Url, Error>
+where
+  F: FnOnce() -> Result<Url, Error>,
+{
+  match value {
+    Some(url) => {
+      let url = url.into_string().map_err(|_| {
+        Error::Str(format!("{name} environment variable is not a valid string").into())
+      })?;
+      Ok(Url::parse(&url)?)
+    },
+    None => default(),
+  }
+}
+
+
+/// Create a redacted representation of a key ID for debug output.
+///
+/// Only a short prefix of the key ID is kept, which is sufficient for
+/// telling accounts apart (e.g., "PK" for paper trading keys).
+pub(crate) fn redact_key_id(key_id: &str) -> String {
+  format!("{}****", key_id.chars().take(2).collect::<String>())
+}
+
+
+/// The credentials used for authenticating with the Alpaca API.
+#[derive(Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Auth {
+  /// Authentication using a key ID and secret.
+  Keys {
+    /// The key ID to use for authentication.
+    key_id: String,
+    /// The secret to use for authentication.
+    secret: String,
+  },
+  /// Authentication using an OAuth access token, obtained on behalf of
+  /// a user.
+  ///
+  /// Requests to the Trading and data APIs carry the token as bearer
+  /// token in the `Authorization` header. Note that websocket streams
+  /// do not support this form of authentication.
+  OAuth(String),
+}
+
+impl Auth {
+  /// Retrieve the key ID and secret to authenticate websocket streams
+  /// with.
+  #[cfg(feature = "streaming")]
+  pub(crate) fn stream_credentials(&self) -> Result<(&str, &str), Error> {
+    match self {
+      Self::Keys { key_id, secret } => Ok((key_id, secret)),
+      Self::OAuth(..) => Err(Error::Str(
+        "websocket streams do not support OAuth authentication".into(),
+      )),
+    }
+  }
+}
+
+impl Debug for Auth {
+  /// Format the `Auth` object, redacting the credentials contained in
+  /// it.
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::Keys { key_id, secret: _ } => f
+        .debug_struct("Keys")
+        .field("key_id", &redact_key_id(key_id))
+        .field("secret", &"<redacted>")
+        .finish(),
+      Self::OAuth(..) => f.debug_tuple("OAuth").field(&"<redacted>").finish(),
+    }
+  }
+}
+
+
 /// An object encapsulating the information used for working with the
 /// Alpaca API.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub struct ApiInfo {
   /// The base URL for the Trading API.
@@ -46,10 +144,22 @@ pub struct ApiInfo {
   pub data_base_url: Url,
   /// The websocket base URL for streaming of data.
   pub data_stream_base_url: Url,
-  /// The key ID to use for authentication.
-  pub key_id: String,
-  /// The secret to use for authentication.
-  pub secret: String,
+  /// The credentials to use for authentication.
+  pub auth: Auth,
+  /// The size of the buffer (in bytes) used for reading messages from
+  /// websocket streams.
+  ///
+  /// A larger buffer allows for more data to be read from the
+  /// underlying connection in one go, which may help keep up with
+  /// high-throughput streams, at the cost of higher memory usage per
+  /// connection. Note that the buffer never drops data: if it is full
+  /// no more data are read from the connection and back-pressure is
+  /// applied to the server (see `stream_backpressure` for dropping
+  /// messages instead). If `None`, a default of 128 KiB is used.
+  pub stream_buffer_size: Option<usize>,
+  /// The strategy to apply when a consumer of a websocket stream does
+  /// not keep up with the messages being received.
+  pub stream_backpressure: BackpressureStrategy,
 }
 
 impl ApiInfo {
@@ -76,11 +186,27 @@ impl ApiInfo {
       // fine.
       data_base_url: Url::parse(DATA_BASE_URL).unwrap(),
       data_stream_base_url: Url::parse(DATA_STREAM_BASE_URL).unwrap(),
-      key_id: key_id.to_string(),
-      secret: secret.to_string(),
+      auth: Auth::Keys {
+        key_id: key_id.to_string(),
+        secret: secret.to_string(),
+      },
+      stream_buffer_size: None,
+      stream_backpressure: BackpressureStrategy::default(),
     })
   }
 
+  /// Create an `ApiInfo` authenticating with an OAuth access token
+  /// instead of a key ID and secret (see [`Auth::OAuth`]).
+  ///
+  /// # Errors
+  /// - [`Error::Url`](crate::Error::Url) If `api_base_url` cannot be parsed
+  ///   into a [`url::Url`](url::Url).
+  pub fn from_oauth(api_base_url: impl AsRef<str>, token: impl ToString) -> Result<Self, Error> {
+    let mut api_info = Self::from_parts(api_base_url, "", "")?;
+    api_info.auth = Auth::OAuth(token.to_string());
+    Ok(api_info)
+  }
+
   /// Create an `ApiInfo` object with information from the environment.
   ///
   /// This constructor retrieves API related information from the
@@ -90,14 +216,16 @@ impl ApiInfo {
   ///   `APCA_API_BASE_URL` variable
   /// - the Alpaca Trading API stream URL is retrieved from the
   ///   `APCA_API_STREAM_URL` variable
+  /// - the market data API base URL is retrieved from the
+  ///   `APCA_DATA_URL` variable or, if not set, the `APCA_API_DATA_URL`
+  ///   one, defaulting to `https://data.alpaca.markets`
+  /// - the market data stream base URL is retrieved from the
+  ///   `APCA_API_DATA_STREAM_URL` variable, defaulting to
+  ///   `wss://stream.data.alpaca.markets`
   /// - the Alpaca account key ID is retrieved from the
   ///   `APCA_API_KEY_ID` variable
   /// - the Alpaca account secret is retrieved from the
   ///   `APCA_API_SECRET_KEY` variable
-  ///
-  /// # Notes
-  /// - Neither of the two data APIs can be configured via the
-  ///   environment currently; defaults will be used
   #[allow(unused_qualifications)]
   pub fn from_env() -> Result<Self, Error> {
     let api_base_url = var_os(ENV_API_BASE_URL)
@@ -106,23 +234,6 @@ impl ApiInfo {
       .map_err(|_| {
         Error::Str(format!("{ENV_API_BASE_URL} environment variable is not a valid string").into())
       })?;
-    let api_base_url = Url::parse(&api_base_url)?;
-
-    let api_stream_url = var_os(ENV_API_STREAM_URL)
-      .map(Result::<_, Error>::Ok)
-      .unwrap_or_else(|| {
-        // If the user did not provide an explicit websocket URL then
-        // infer the one to use based on the API base URL.
-        let url = make_api_stream_url(api_base_url.clone())?;
-        Ok(OsString::from(url.as_str()))
-      })?
-      .into_string()
-      .map_err(|_| {
-        Error::Str(
-          format!("{ENV_API_STREAM_URL} environment variable is not a valid string").into(),
-        )
-      })?;
-    let api_stream_url = Url::parse(&api_stream_url)?;
 
     let key_id = var_os(ENV_KEY_ID)
       .ok_or_else(|| Error::Str(format!("{ENV_KEY_ID} environment variable not found").into()))?
@@ -138,17 +249,45 @@ impl ApiInfo {
         Error::Str(format!("{ENV_SECRET} environment variable is not a valid string").into())
       })?;
 
-    Ok(Self {
+    let mut api_info = Self::from_parts(api_base_url, key_id, secret)?;
+    // If the user did not provide an explicit websocket URL then we
+    // stick to the one inferred based on the API base URL.
+    let api_stream_url = api_info.api_stream_url.clone();
+    api_info.api_stream_url = url_from_env(ENV_API_STREAM_URL, || Ok(api_stream_url))?;
+    let data_base_url = api_info.data_base_url.clone();
+    api_info.data_base_url = url_from_env(ENV_DATA_URL, || {
+      url_from_env(ENV_API_DATA_URL, || Ok(data_base_url))
+    })?;
+    let data_stream_base_url = api_info.data_stream_base_url.clone();
+    api_info.data_stream_base_url = url_from_env(ENV_DATA_STREAM_URL, || Ok(data_stream_base_url))?;
+
+    Ok(api_info)
+  }
+}
+
+impl Debug for ApiInfo {
+  /// Format the `ApiInfo` object, redacting the credentials contained
+  /// in it.
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let Self {
       api_base_url,
       api_stream_url,
-      // We basically only work with statically defined URL parts here
-      // which we know can be parsed successfully, so unwrapping is
-      // fine.
-      data_base_url: Url::parse(DATA_BASE_URL).unwrap(),
-      data_stream_base_url: Url::parse(DATA_STREAM_BASE_URL).unwrap(),
-      key_id,
-      secret,
-    })
+      data_base_url,
+      data_stream_base_url,
+      auth,
+      stream_buffer_size,
+      stream_backpressure,
+    } = self;
+
+    f.debug_struct("ApiInfo")
+      .field("api_base_url", &api_base_url.as_str())
+      .field("api_stream_url", &api_stream_url.as_str())
+      .field("data_base_url", &data_base_url.as_str())
+      .field("data_stream_base_url", &data_stream_base_url.as_str())
+      .field("auth", auth)
+      .field("stream_buffer_size", stream_buffer_size)
+      .field("stream_backpressure", stream_backpressure)
+      .finish()
   }
 }
 
@@ -168,7 +307,61 @@ mod tests {
 
     let api_info = ApiInfo::from_parts(api_base_url, key_id, secret).unwrap();
     assert_eq!(api_info.api_base_url.as_str(), api_base_url);
-    assert_eq!(api_info.key_id, key_id);
-    assert_eq!(api_info.secret, secret);
+    assert_eq!(
+      api_info.api_stream_url.as_str(),
+      "wss://paper-api.alpaca.markets/stream"
+    );
+    assert_eq!(
+      api_info.data_stream_base_url.as_str(),
+      "wss://stream.data.alpaca.markets/"
+    );
+    assert_eq!(
+      api_info.auth,
+      Auth::Keys {
+        key_id: key_id.to_string(),
+        secret: secret.to_string(),
+      }
+    );
+  }
+
+  /// Make sure that credentials are redacted when formatting an
+  /// [`ApiInfo`] object.
+  #[test]
+  fn debug_redacts_credentials() {
+    let api_base_url = "https://paper-api.alpaca.markets/";
+    let key_id = "PKXXXXXXXXXXXXXXXXXX";
+    let secret = "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY";
+
+    let api_info = ApiInfo::from_parts(api_base_url, key_id, secret).unwrap();
+    let string = format!("{api_info:?}");
+    assert!(!string.contains(secret), "{string}");
+    assert!(!string.contains(key_id), "{string}");
+    assert!(string.contains(r#"key_id: "PK****""#), "{string}");
+    assert!(string.contains(r#"secret: "<redacted>""#), "{string}");
+    assert!(string.contains(api_base_url), "{string}");
+
+    let token = "ZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZ";
+    let api_info = ApiInfo::from_oauth(api_base_url, token).unwrap();
+    let string = format!("{api_info:?}");
+    assert!(!string.contains(token), "{string}");
+    assert!(string.contains(r#"auth: OAuth("<redacted>")"#), "{string}");
+  }
+
+  /// Check that URLs can be overridden via the environment.
+  #[test]
+  fn url_override_from_env() {
+    let name = ENV_DATA_STREAM_URL;
+    let default = || Ok(Url::parse(DATA_STREAM_BASE_URL).unwrap());
+
+    let url = url_from_var(name, None, default).unwrap();
+    assert_eq!(url.as_str(), "wss://stream.data.alpaca.markets/");
+
+    let value = OsString::from("ws://127.0.0.1:8765");
+    let url = url_from_var(name, Some(value), default).unwrap();
+    assert_eq!(url.as_str(), "ws://127.0.0.1:8765/");
+
+    let value = OsString::from("not a url");
+    let result = url_from_var(name, Some(value), default);
+    assert!(matches!(result, Err(Error::Url(..))), "{result:?}");
   }
 }
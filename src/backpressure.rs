@@ -0,0 +1,221 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use futures::stream::FusedStream;
+use futures::Stream;
+use futures::StreamExt as _;
+
+
+/// A strategy for dealing with a consumer not keeping up with the
+/// messages received over a websocket stream.
+///
+/// Errors reported by the stream are never dropped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BackpressureStrategy {
+  /// Never drop any messages. If the consumer falls behind, no more
+  /// data are read from the connection and back-pressure is applied to
+  /// the server.
+  ///
+  /// This strategy favors completeness over latency and is the
+  /// default.
+  Block,
+  /// Whenever the stream is polled, eagerly read all messages available
+  /// and retain at most the given number of the most recent ones,
+  /// dropping older ones.
+  ///
+  /// This strategy favors latency over completeness.
+  DropOldest(usize),
+  /// Whenever the stream is polled, eagerly read all messages available
+  /// and retain at most the given number of the oldest ones not yet
+  /// consumed, dropping newer ones.
+  DropNewest(usize),
+}
+
+impl Default for BackpressureStrategy {
+  #[inline]
+  fn default() -> Self {
+    Self::Block
+  }
+}
+
+
+/// A stream adapter applying a [`BackpressureStrategy`] to the items
+/// of a stream.
+///
+/// Only `Ok` items are ever dropped. Errors are always retained, as
+/// they may signal the termination of the stream.
+#[derive(Debug)]
+pub struct Backpressure<S>
+where
+  S: Stream,
+{
+  /// The wrapped stream.
+  stream: S,
+  /// The strategy to apply.
+  strategy: BackpressureStrategy,
+  /// Items read but not yet handed out.
+  queue: VecDeque<S::Item>,
+  /// The number of `Ok` items in `queue`.
+  data: usize,
+  /// Whether the wrapped stream has been exhausted.
+  exhausted: bool,
+}
+
+impl<S> Backpressure<S>
+where
+  S: Stream,
+{
+  /// Wrap the provided stream, applying the given strategy.
+  pub(crate) fn new(stream: S, strategy: BackpressureStrategy) -> Self {
+    Self {
+      stream,
+      strategy,
+      queue: VecDeque::new(),
+      data: 0,
+      exhausted: false,
+    }
+  }
+}
+
+impl<S, T, E> Stream for Backpressure<S>
+where
+  S: Stream<Item = Result<T, E>> + Unpin,
+  T: Unpin,
+  E: Unpin,
+{
+  type Item = S::Item;
+
+  fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = self.get_mut();
+    let (capacity, drop_oldest) = match this.strategy {
+      BackpressureStrategy::Block => return this.stream.poll_next_unpin(ctx),
+      BackpressureStrategy::DropOldest(capacity) => (capacity.max(1), true),
+      BackpressureStrategy::DropNewest(capacity) => (capacity.max(1), false),
+    };
+
+    while !this.exhausted {
+      match this.stream.poll_next_unpin(ctx) {
+        Poll::Ready(Some(item)) => {
+          if item.is_err() {
+            let () = this.queue.push_back(item);
+          } else if this.data < capacity {
+            this.data += 1;
+            let () = this.queue.push_back(item);
+          } else if drop_oldest {
+            // There is at least one `Ok` item queued, because the
+            // capacity is never zero.
+            let idx = this.queue.iter().position(Result::is_ok).unwrap();
+            let _item = this.queue.remove(idx);
+            let () = this.queue.push_back(item);
+          }
+        },
+        Poll::Ready(None) => this.exhausted = true,
+        Poll::Pending => break,
+      }
+    }
+
+    match this.queue.pop_front() {
+      Some(item) => {
+        if item.is_ok() {
+          this.data -= 1;
+        }
+        Poll::Ready(Some(item))
+      },
+      None if this.exhausted => Poll::Ready(None),
+      None => Poll::Pending,
+    }
+  }
+}
+
+impl<S, T, E> FusedStream for Backpressure<S>
+where
+  S: FusedStream<Item = Result<T, E>> + Unpin,
+  T: Unpin,
+  E: Unpin,
+{
+  fn is_terminated(&self) -> bool {
+    match self.strategy {
+      BackpressureStrategy::Block => self.stream.is_terminated(),
+      BackpressureStrategy::DropOldest(..) | BackpressureStrategy::DropNewest(..) => {
+        self.exhausted && self.queue.is_empty()
+      },
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use futures::stream::iter;
+
+  use test_log::test;
+
+
+  /// Flood a stream wrapped with the given strategy and collect the
+  /// items retained.
+  async fn flood(strategy: BackpressureStrategy) -> Vec<usize> {
+    let stream = iter(0..100).map(Ok::<_, ()>).fuse();
+    let stream = Backpressure::new(stream, strategy);
+    stream.map(Result::unwrap).collect::<Vec<_>>().await
+  }
+
+
+  /// Check that we retain the expected items when flooding a stream
+  /// under the various strategies.
+  #[test(tokio::test)]
+  async fn flood_strategies() {
+    let items = flood(BackpressureStrategy::Block).await;
+    assert_eq!(items, (0..100).collect::<Vec<_>>());
+
+    let items = flood(BackpressureStrategy::DropOldest(10)).await;
+    assert_eq!(items, (90..100).collect::<Vec<_>>());
+
+    let items = flood(BackpressureStrategy::DropNewest(10)).await;
+    assert_eq!(items, (0..10).collect::<Vec<_>>());
+  }
+
+  /// Check that a wrapped stream reports termination only once all
+  /// retained items have been consumed.
+  #[test(tokio::test)]
+  async fn termination() {
+    let stream = iter(0..3).map(Ok::<_, ()>).fuse();
+    let mut stream = Backpressure::new(stream, BackpressureStrategy::DropOldest(2));
+    assert!(!stream.is_terminated());
+    assert_eq!(stream.next().await, Some(Ok(1)));
+    assert!(!stream.is_terminated());
+    assert_eq!(stream.next().await, Some(Ok(2)));
+    assert!(stream.is_terminated());
+    assert_eq!(stream.next().await, None);
+  }
+
+  /// Check that errors are never dropped, irrespective of the strategy
+  /// in use.
+  #[test(tokio::test)]
+  async fn retain_errors() {
+    let items = [Err(0), Ok(1), Ok(2), Err(3), Ok(4), Ok(5)];
+    let strategies = [
+      (
+        BackpressureStrategy::DropOldest(2),
+        vec![Err(0), Err(3), Ok(4), Ok(5)],
+      ),
+      (
+        BackpressureStrategy::DropNewest(2),
+        vec![Err(0), Ok(1), Ok(2), Err(3)],
+      ),
+    ];
+
+    for (strategy, expected) in strategies {
+      let stream = Backpressure::new(iter(items).fuse(), strategy);
+      let retained = stream.collect::<Vec<Result<usize, usize>>>().await;
+      assert_eq!(retained, expected);
+    }
+  }
+}
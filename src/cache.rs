@@ -0,0 +1,125 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use http::Method;
+use http::Request;
+use http::StatusCode;
+
+use hyper::body::Bytes;
+
+
+/// A cached response.
+#[derive(Debug)]
+struct Entry {
+  /// The time at which the entry expires.
+  expires_at: Instant,
+  /// The HTTP status of the response.
+  status: StatusCode,
+  /// The (decoded) body of the response.
+  body: Bytes,
+}
+
+
+/// An in-memory cache of endpoint responses.
+///
+/// Only responses to GET requests issued to endpoints for which a time
+/// to live (TTL) was configured are cached.
+#[derive(Debug, Default)]
+pub(crate) struct Cache {
+  /// The time to live of cached responses, keyed by endpoint type name.
+  ttls: HashMap<&'static str, Duration>,
+  /// The cached responses, keyed by request method and URI.
+  entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl Cache {
+  /// Create a new `Cache` object using the provided TTLs.
+  pub(crate) fn new(ttls: HashMap<&'static str, Duration>) -> Self {
+    Self {
+      ttls,
+      entries: Mutex::default(),
+    }
+  }
+
+  /// Determine the key and TTL to use for caching the response to the
+  /// provided request for endpoint `endpoint`, if any.
+  pub(crate) fn key<B>(
+    &self,
+    endpoint: &'static str,
+    request: &Request<B>,
+  ) -> Option<(String, Duration)> {
+    if request.method() != Method::GET {
+      return None
+    }
+
+    self
+      .ttls
+      .get(endpoint)
+      .map(|ttl| (format!("{} {}", request.method(), request.uri()), *ttl))
+  }
+
+  /// Look up the unexpired response cached for the given key.
+  pub(crate) fn get(&self, key: &str) -> Option<(StatusCode, Bytes)> {
+    // SANITY: We never panic while holding the lock.
+    let mut entries = self.entries.lock().unwrap();
+    match entries.get(key) {
+      Some(entry) if entry.expires_at > Instant::now() => Some((entry.status, entry.body.clone())),
+      Some(..) => {
+        let _entry = entries.remove(key);
+        None
+      },
+      None => None,
+    }
+  }
+
+  /// Cache a response under the given key.
+  ///
+  /// All expired entries are evicted in the process, so that entries
+  /// for keys that are never looked up again do not accumulate.
+  pub(crate) fn insert(&self, key: String, ttl: Duration, status: StatusCode, body: Bytes) {
+    let now = Instant::now();
+    let entry = Entry {
+      expires_at: now + ttl,
+      status,
+      body,
+    };
+    // SANITY: We never panic while holding the lock.
+    let mut entries = self.entries.lock().unwrap();
+    let () = entries.retain(|_key, entry| entry.expires_at > now);
+    let _prev = entries.insert(key, entry);
+  }
+
+  /// Remove all cached responses.
+  pub(crate) fn clear(&self) {
+    // SANITY: We never panic while holding the lock.
+    let () = self.entries.lock().unwrap().clear();
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+
+  /// Check that expired entries are evicted when inserting new ones.
+  #[test]
+  fn prune_expired_on_insert() {
+    let cache = Cache::default();
+    let status = StatusCode::OK;
+    let ttl = Duration::from_secs(60);
+    let () = cache.insert("GET /a".to_string(), Duration::ZERO, status, Bytes::new());
+    assert_eq!(cache.entries.lock().unwrap().len(), 1);
+
+    let () = cache.insert("GET /b".to_string(), ttl, status, Bytes::new());
+    let () = cache.insert("GET /c".to_string(), ttl, status, Bytes::new());
+    let entries = cache.entries.lock().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(!entries.contains_key("GET /a"));
+  }
+}
@@ -1,18 +1,47 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::any::type_name;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::future::Future;
+#[cfg(feature = "gzip")]
+use std::io::Error as IoError;
+use std::marker::PhantomData;
+#[cfg(feature = "streaming")]
+use std::pin::Pin;
 use std::str::from_utf8;
+#[cfg(feature = "streaming")]
+use std::sync::atomic::AtomicUsize;
+#[cfg(feature = "streaming")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "streaming")]
+use std::sync::Arc;
+use std::sync::Mutex;
+#[cfg(feature = "streaming")]
+use std::task::Context;
+#[cfg(feature = "streaming")]
+use std::task::Poll;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+use chrono::DateTime;
+use chrono::Utc;
+
+use http::header::AUTHORIZATION;
+use http::header::RETRY_AFTER;
 use http::request::Builder as HttpRequestBuilder;
 use http::HeaderMap;
 use http::HeaderValue;
+use http::Method;
 use http::Request;
 use http::Response;
+use http::StatusCode;
+use http::Uri;
 use http_body_util::BodyExt;
 use http_body_util::Full;
 use http_endpoint::Endpoint;
@@ -26,9 +55,24 @@ use hyper_util::client::legacy::Builder as HttpClientBuilder;
 use hyper_util::client::legacy::Client as HttpClient;
 use hyper_util::rt::TokioExecutor;
 
+use native_tls::TlsConnector;
+
+#[cfg(feature = "streaming")]
+use futures::stream::unfold;
+#[cfg(feature = "streaming")]
+use futures::Stream;
+#[cfg(feature = "streaming")]
+use futures::StreamExt as _;
+
+use serde::de::DeserializeSeed;
+use serde_json::Deserializer as JsonDeserializer;
+
+use tokio::time::sleep;
+
 use tracing::debug;
 use tracing::field::debug;
 use tracing::field::DebugValue;
+#[cfg(feature = "streaming")]
 use tracing::instrument;
 use tracing::span;
 use tracing::trace;
@@ -37,11 +81,20 @@ use tracing_futures::Instrument;
 
 use url::Url;
 
+use crate::api::v2::clock;
 use crate::api::HDR_KEY_ID;
 use crate::api::HDR_SECRET;
 use crate::api_info::ApiInfo;
+use crate::api_info::Auth;
+use crate::cache::Cache;
+use crate::endpoint::BaseUrl;
+use crate::endpoint::ConversionError;
 use crate::error::RequestError;
+#[cfg(feature = "streaming")]
+use crate::subscribable::Resubscribable;
+#[cfg(feature = "streaming")]
 use crate::subscribable::Subscribable;
+#[cfg(feature = "streaming")]
 use crate::Error;
 
 
@@ -57,7 +110,7 @@ impl Debug for DebugHeaders<'_> {
 
     f.debug_map()
       .entries(self.headers.iter().map(|(k, v)| {
-        if k == HDR_KEY_ID || k == HDR_SECRET {
+        if k == HDR_KEY_ID || k == HDR_SECRET || k == AUTHORIZATION {
           (k, &MASKED)
         } else {
           (k, v)
@@ -71,7 +124,7 @@ impl Debug for DebugHeaders<'_> {
 /// A type providing a debug representation of an HTTP request, with
 /// sensitive data being masked out.
 struct DebugRequest<'r> {
-  request: &'r Request<Full<Bytes>>,
+  request: &'r Request<Bytes>,
 }
 
 impl Debug for DebugRequest<'_> {
@@ -94,15 +147,357 @@ impl Debug for DebugRequest<'_> {
 
 
 /// Emit a debug representation of an HTTP request.
-fn debug_request(request: &Request<Full<Bytes>>) -> DebugValue<DebugRequest<'_>> {
+fn debug_request(request: &Request<Bytes>) -> DebugValue<DebugRequest<'_>> {
   debug(DebugRequest { request })
 }
 
 
+/// A request to an endpoint that has been fully constructed, but not
+/// yet sent.
+///
+/// Objects of this type are created by [`Client::prepare`] and can be
+/// sent using [`Client::execute`].
+pub struct PreparedRequest<R> {
+  /// The HTTP request, as it is sent over the wire.
+  request: Request<Bytes>,
+  /// Phantom data for the endpoint the request is for.
+  _endpoint: PhantomData<fn() -> R>,
+}
+
+impl<R> PreparedRequest<R> {
+  /// Retrieve the HTTP method of the request.
+  #[inline]
+  pub fn method(&self) -> &Method {
+    self.request.method()
+  }
+
+  /// Retrieve the full URL, including the query, the request is
+  /// directed at.
+  #[inline]
+  pub fn url(&self) -> &Uri {
+    self.request.uri()
+  }
+
+  /// Retrieve the headers of the request, with sensitive data being
+  /// masked out.
+  pub fn headers(&self) -> HeaderMap<HeaderValue> {
+    let mut headers = self.request.headers().clone();
+    for name in [HDR_KEY_ID, HDR_SECRET, AUTHORIZATION.as_str()] {
+      if let Some(value) = headers.get_mut(name) {
+        *value = HeaderValue::from_static("<masked>");
+      }
+    }
+    headers
+  }
+
+  /// Retrieve the body of the request.
+  #[inline]
+  pub fn body(&self) -> &[u8] {
+    self.request.body()
+  }
+}
+
+impl<R> Debug for PreparedRequest<R> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    f.debug_struct("PreparedRequest")
+      .field("method", self.request.method())
+      .field("url", self.request.uri())
+      .field(
+        "headers",
+        &DebugHeaders {
+          headers: self.request.headers(),
+        },
+      )
+      .field("body", self.request.body())
+      .finish()
+  }
+}
+
+
+/// The header Alpaca uses for reporting the maximum number of requests
+/// permitted per minute.
+const HDR_RATE_LIMIT_LIMIT: &str = "X-RateLimit-Limit";
+/// The header Alpaca uses for reporting the number of requests
+/// remaining in the current minute.
+const HDR_RATE_LIMIT_REMAINING: &str = "X-RateLimit-Remaining";
+/// The header Alpaca uses for reporting the time at which the rate
+/// limit is reset, as a Unix timestamp in seconds.
+const HDR_RATE_LIMIT_RESET: &str = "X-RateLimit-Reset";
+
+
+/// Parse a numeric header value.
+fn parse_header(headers: &HeaderMap<HeaderValue>, name: &str) -> Option<u64> {
+  headers
+    .get(name)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+
+/// Rate limit information as reported by the API alongside a response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+  /// The maximum number of requests permitted per minute.
+  pub limit: u64,
+  /// The number of requests remaining in the current minute.
+  pub remaining: u64,
+  /// The time at which the rate limit is reset.
+  pub reset: DateTime<Utc>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl RateLimit {
+  /// Parse rate limit information from a set of response headers.
+  ///
+  /// `None` is returned if any of the relevant headers is missing or
+  /// invalid.
+  fn from_headers(headers: &HeaderMap<HeaderValue>) -> Option<Self> {
+    let limit = parse_header(headers, HDR_RATE_LIMIT_LIMIT)?;
+    let remaining = parse_header(headers, HDR_RATE_LIMIT_REMAINING)?;
+    let reset = parse_header(headers, HDR_RATE_LIMIT_RESET)?;
+    let reset = DateTime::from_timestamp(i64::try_from(reset).ok()?, 0)?;
+
+    Some(Self {
+      limit,
+      remaining,
+      reset,
+      _non_exhaustive: (),
+    })
+  }
+}
+
+
+/// A configuration for retrying requests that were rejected because
+/// the rate limit was exceeded (HTTP status 429) or for reconnecting
+/// to a stream (see [`Client::subscribe_resilient`]).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryConfig {
+  /// The maximum number of attempts made for a request, including the
+  /// initial one, or the maximum number of consecutive attempts made
+  /// for reconnecting to a stream.
+  pub max_attempts: usize,
+  /// The delay before the first retry, doubled for every subsequent
+  /// one. For requests it is only used if the response does not
+  /// indicate when the request may be retried.
+  pub base_delay: Duration,
+  /// The upper bound of the delay before a retry, including one
+  /// requested by the server.
+  pub max_delay: Duration,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl RetryConfig {
+  /// Calculate the exponentially increasing delay before the next
+  /// attempt, after `attempt` failed ones.
+  fn backoff(&self, attempt: usize) -> Duration {
+    let exponent = u32::try_from(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+    self
+      .base_delay
+      .saturating_mul(2u32.saturating_pow(exponent))
+      .min(self.max_delay)
+  }
+
+  /// Determine the delay before retrying a request that was rejected
+  /// with the provided response headers, after `attempt` attempts.
+  ///
+  /// The `Retry-After` header takes precedence over the
+  /// `X-RateLimit-Reset` one. If neither is present (or valid) we back
+  /// off exponentially. In any case the delay is bounded by
+  /// `max_delay`.
+  fn delay(&self, headers: &HeaderMap<HeaderValue>, attempt: usize, now: SystemTime) -> Duration {
+    if let Some(secs) = parse_header(headers, RETRY_AFTER.as_str()) {
+      Duration::from_secs(secs).min(self.max_delay)
+    } else if let Some(reset) = parse_header(headers, HDR_RATE_LIMIT_RESET) {
+      let reset = UNIX_EPOCH + Duration::from_secs(reset);
+      reset
+        .duration_since(now)
+        .unwrap_or_default()
+        .min(self.max_delay)
+    } else {
+      self.backoff(attempt)
+    }
+  }
+}
+
+impl Default for RetryConfig {
+  #[inline]
+  fn default() -> Self {
+    Self {
+      max_attempts: 3,
+      base_delay: Duration::from_millis(500),
+      max_delay: Duration::from_secs(30),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// An event emitted by a stream created by
+/// [`Client::subscribe_resilient`].
+#[cfg(feature = "streaming")]
+#[derive(Debug)]
+pub enum Resilient<T, S> {
+  /// A message received over the stream.
+  Message(T),
+  /// A reconnect is about to be attempted.
+  Reconnecting {
+    /// The number of the attempt, counting consecutive attempts made
+    /// since the connection got closed, starting at one.
+    attempt: usize,
+    /// The error causing the previous attempt to fail. `None` for the
+    /// first attempt, which is triggered by the stream being closed.
+    error: Option<Error>,
+  },
+  /// The stream was closed and a new connection has been established.
+  /// Messages sent in between may have been missed.
+  ///
+  /// The subscription of the new connection is included and replaces
+  /// the previous one, which no longer has any effect.
+  Reconnected(S),
+  /// The stream was closed and reconnecting failed repeatedly, as per
+  /// the [`RetryConfig`] in use. This is the last event emitted.
+  Disconnected(Error),
+}
+
+
+/// Counters of reconnects of a [`ResilientStream`].
+#[cfg(feature = "streaming")]
+#[derive(Debug, Default)]
+struct ReconnectCounters {
+  /// The number of reconnect attempts made.
+  attempts: AtomicUsize,
+  /// The number of reconnect attempts that succeeded.
+  reconnects: AtomicUsize,
+  /// The number of reconnect attempts that failed.
+  failures: AtomicUsize,
+}
+
+
+/// Statistics about the reconnects of a [`ResilientStream`].
+///
+/// Objects of this type are cheap to clone and all clones refer to the
+/// same underlying counters, which are updated as reconnects happen.
+#[cfg(feature = "streaming")]
+#[derive(Clone, Debug, Default)]
+pub struct ReconnectStats(Arc<ReconnectCounters>);
+
+#[cfg(feature = "streaming")]
+impl ReconnectStats {
+  /// Retrieve the total number of reconnect attempts made.
+  #[inline]
+  pub fn attempts(&self) -> usize {
+    self.0.attempts.load(Ordering::Relaxed)
+  }
+
+  /// Retrieve the number of reconnect attempts that succeeded.
+  #[inline]
+  pub fn reconnects(&self) -> usize {
+    self.0.reconnects.load(Ordering::Relaxed)
+  }
+
+  /// Retrieve the number of reconnect attempts that failed.
+  #[inline]
+  pub fn failures(&self) -> usize {
+    self.0.failures.load(Ordering::Relaxed)
+  }
+}
+
+
+/// A stream transparently reconnecting once its connection is closed,
+/// as created by [`Client::subscribe_resilient`].
+#[cfg(feature = "streaming")]
+pub struct ResilientStream<S> {
+  /// The stream emitting [`Resilient`] events.
+  stream: Pin<Box<S>>,
+  /// The reconnect statistics of the stream.
+  stats: ReconnectStats,
+}
+
+#[cfg(feature = "streaming")]
+impl<S> ResilientStream<S> {
+  /// Retrieve the reconnect statistics of the stream.
+  #[inline]
+  pub fn stats(&self) -> &ReconnectStats {
+    &self.stats
+  }
+}
+
+#[cfg(feature = "streaming")]
+impl<S> Debug for ResilientStream<S> {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt
+      .debug_struct("ResilientStream")
+      .field("stats", &self.stats)
+      .finish_non_exhaustive()
+  }
+}
+
+#[cfg(feature = "streaming")]
+impl<S> Stream for ResilientStream<S>
+where
+  S: Stream,
+{
+  type Item = S::Item;
+
+  #[inline]
+  fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    self.stream.as_mut().poll_next(ctx)
+  }
+}
+
+
+/// The state of a stream created by [`Client::subscribe_resilient`].
+#[cfg(feature = "streaming")]
+enum ResilientState<S> {
+  /// We are connected and receive messages over the stream.
+  Connected(S),
+  /// The connection got closed and we are about to make the given
+  /// reconnect attempt.
+  Reconnecting(usize),
+}
+
+
+/// The HTTP protocol version a [`Client`] uses for issuing requests.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum HttpVersion {
+  /// Use HTTP/1.1. This is the default.
+  ///
+  /// Each connection serves a single request at a time; concurrent
+  /// requests are spread over multiple pooled connections.
+  #[default]
+  Http1,
+  /// Use HTTP/2 exclusively, negotiated via ALPN during the TLS
+  /// handshake.
+  ///
+  /// Concurrent requests are multiplexed over a single connection.
+  /// Requests fail if the server does not support HTTP/2.
+  Http2,
+}
+
+
+/// Create a copy of an HTTP request.
+fn clone_request(request: &Request<Bytes>) -> Request<Bytes> {
+  let mut clone = Request::new(request.body().clone());
+  *clone.method_mut() = request.method().clone();
+  *clone.uri_mut() = request.uri().clone();
+  *clone.version_mut() = request.version();
+  *clone.headers_mut() = request.headers().clone();
+  clone
+}
+
+
 /// A builder for creating customized `Client` objects.
 #[derive(Debug)]
 pub struct Builder {
   builder: HttpClientBuilder,
+  cache_ttls: HashMap<&'static str, Duration>,
+  retry: Option<RetryConfig>,
+  http_version: HttpVersion,
 }
 
 impl Builder {
@@ -113,12 +508,75 @@ impl Builder {
     self
   }
 
+  /// Enable caching of responses to GET requests issued to endpoint
+  /// `R`, with cached responses being served for the provided duration.
+  ///
+  /// Caching is keyed by the full request URI, i.e., requests with
+  /// different parameters are cached separately. Only successful
+  /// responses are cached. Use [`Client::issue_uncached`] to bypass the
+  /// cache for individual requests.
+  #[inline]
+  pub fn cache<R>(&mut self, ttl: Duration) -> &mut Self
+  where
+    R: Endpoint,
+  {
+    let _prev = self.cache_ttls.insert(type_name::<R>(), ttl);
+    self
+  }
+
+  /// Enable retrying of requests that were rejected because the rate
+  /// limit was exceeded.
+  ///
+  /// A rejected request is reissued after the delay indicated by the
+  /// response (or an exponentially increasing one), for up to
+  /// [`max_attempts`][RetryConfig::max_attempts] attempts in total.
+  /// Once exhausted, the final error is reported.
+  #[inline]
+  pub fn retry(&mut self, config: RetryConfig) -> &mut Self {
+    self.retry = Some(config);
+    self
+  }
+
+  /// Set the HTTP protocol version to use for requests.
+  ///
+  /// By default, [`HttpVersion::Http1`] is used.
+  #[inline]
+  pub fn http_version(&mut self, version: HttpVersion) -> &mut Self {
+    let _ = self.builder.http2_only(version == HttpVersion::Http2);
+    self.http_version = version;
+    self
+  }
+
   /// Build the final `Client` object.
+  ///
+  /// # Panics
+  /// This method panics if the system's TLS implementation fails to
+  /// initialize, just like [`HttpsConnector::new`] does. Such a
+  /// failure is not recoverable and indicates a broken environment
+  /// rather than a usage error.
   pub fn build(&self, api_info: ApiInfo) -> Client {
-    let https = HttpsConnector::new();
+    let https = match self.http_version {
+      HttpVersion::Http1 => HttpsConnector::new(),
+      HttpVersion::Http2 => {
+        let tls = TlsConnector::builder()
+          .request_alpns(&["h2"])
+          .build()
+          .unwrap_or_else(|err| panic!("failed to create TLS connector: {err}"));
+        let mut http = HttpConnector::new();
+        let () = http.enforce_http(false);
+        HttpsConnector::from((http, tls.into()))
+      },
+    };
     let client = self.builder.build(https);
+    let cache = Cache::new(self.cache_ttls.clone());
 
-    Client { api_info, client }
+    Client {
+      api_info,
+      client,
+      cache,
+      retry: self.retry,
+      rate_limit: Mutex::new(None),
+    }
   }
 }
 
@@ -136,7 +594,12 @@ impl Default for Builder {
     let mut builder = HttpClient::builder(TokioExecutor::new());
     let _ = builder.pool_max_idle_per_host(0);
 
-    Self { builder }
+    Self {
+      builder,
+      cache_ttls: HashMap::new(),
+      retry: None,
+      http_version: HttpVersion::default(),
+    }
   }
 
   #[cfg(not(test))]
@@ -144,6 +607,9 @@ impl Default for Builder {
   fn default() -> Self {
     Self {
       builder: HttpClient::builder(TokioExecutor::new()),
+      cache_ttls: HashMap::new(),
+      retry: None,
+      http_version: HttpVersion::default(),
     }
   }
 }
@@ -151,10 +617,21 @@ impl Default for Builder {
 
 /// A `Client` is the entity used by clients of this module for
 /// interacting with the Alpaca API.
+///
+/// A `Client` maintains a pool of connections: once a request
+/// completed, the connection it used is kept alive and reused by
+/// subsequent requests to the same host, saving the cost of connection
+/// establishment and TLS handshake (see
+/// `Builder::max_idle_per_host` for adjusting the number of idle
+/// connections kept). Use [`Client::warmup`] to establish a pooled
+/// connection ahead of latency sensitive requests.
 #[derive(Debug)]
 pub struct Client {
   api_info: ApiInfo,
   client: HttpClient<HttpsConnector<HttpConnector>, Full<Bytes>>,
+  cache: Cache,
+  retry: Option<RetryConfig>,
+  rate_limit: Mutex<Option<RateLimit>>,
 }
 
 impl Client {
@@ -173,7 +650,7 @@ impl Client {
 
   /// Add "gzip" as an accepted encoding to the request.
   #[cfg(feature = "gzip")]
-  fn maybe_add_gzip_header(request: &mut Request<Full<Bytes>>) {
+  fn maybe_add_gzip_header(request: &mut Request<Bytes>) {
     use http::header::ACCEPT_ENCODING;
 
     let _ = request
@@ -183,16 +660,18 @@ impl Client {
 
   /// An implementation stub not actually doing anything.
   #[cfg(not(feature = "gzip"))]
-  fn maybe_add_gzip_header(_request: &mut Request<Full<Bytes>>) {}
+  fn maybe_add_gzip_header(_request: &mut Request<Bytes>) {}
 
   /// Create a `Request` to the endpoint.
-  fn request<R>(&self, input: &R::Input) -> Result<Request<Full<Bytes>>, R::Error>
+  fn request<R>(&self, input: &R::Input) -> Result<Request<Bytes>, R::Error>
   where
     R: Endpoint,
   {
-    let mut url = R::base_url()
-      .map(|url| Url::parse(url.as_ref()).expect("endpoint definition contains invalid URL"))
-      .unwrap_or_else(|| self.api_info.api_base_url.clone());
+    let mut url = match BaseUrl::classify(R::base_url()) {
+      Ok(BaseUrl::Api) => self.api_info.api_base_url.clone(),
+      Ok(BaseUrl::Data) => self.api_info.data_base_url.clone(),
+      Err(url) => Url::parse(url.as_ref()).expect("endpoint definition contains invalid URL"),
+    };
 
     url.set_path(&R::path(input));
     url.set_query(R::query(input)?.as_ref().map(AsRef::as_ref));
@@ -203,13 +682,17 @@ impl Client {
       Some(Cow::Owned(vec)) => Bytes::from(vec),
     };
 
-    let mut request = HttpRequestBuilder::new()
+    let builder = HttpRequestBuilder::new()
       .method(R::method())
-      .uri(url.as_str())
-      // Add required authentication information.
-      .header(HDR_KEY_ID, self.api_info.key_id.as_str())
-      .header(HDR_SECRET, self.api_info.secret.as_str())
-      .body(Full::new(body))?;
+      .uri(url.as_str());
+    // Add required authentication information.
+    let builder = match &self.api_info.auth {
+      Auth::Keys { key_id, secret } => builder
+        .header(HDR_KEY_ID, key_id.as_str())
+        .header(HDR_SECRET, secret.as_str()),
+      Auth::OAuth(token) => builder.header(AUTHORIZATION, format!("Bearer {token}")),
+    };
+    let mut request = builder.body(body)?;
 
 
     Self::maybe_add_gzip_header(&mut request);
@@ -234,18 +717,13 @@ impl Client {
     Ok(bytes)
   }
 
-  /// Retrieve the HTTP body, possible uncompressing it if it was gzip
-  /// encoded.
+  /// Uncompress an HTTP body if it is gzip encoded, as indicated by the
+  /// provided `Content-Encoding` header value.
   #[cfg(feature = "gzip")]
-  async fn retrieve_body<E>(response: Response<Incoming>) -> Result<Bytes, RequestError<E>> {
+  async fn decode_body(encoding: Option<&HeaderValue>, bytes: Bytes) -> Result<Bytes, IoError> {
     use async_compression::futures::bufread::GzipDecoder;
     use futures::AsyncReadExt as _;
-    use http::header::CONTENT_ENCODING;
-
-    let (parts, body) = response.into_parts();
-    let encoding = parts.headers.get(CONTENT_ENCODING);
 
-    let bytes = Self::retrieve_raw_body(body).await?;
     let bytes = match encoding {
       Some(value) if value == HeaderValue::from_static("gzip") => {
         let mut buffer = Vec::new();
@@ -258,6 +736,20 @@ impl Client {
     Ok(bytes)
   }
 
+  /// Retrieve the HTTP body, possible uncompressing it if it was gzip
+  /// encoded.
+  #[cfg(feature = "gzip")]
+  async fn retrieve_body<E>(response: Response<Incoming>) -> Result<Bytes, RequestError<E>> {
+    use http::header::CONTENT_ENCODING;
+
+    let (parts, body) = response.into_parts();
+    let encoding = parts.headers.get(CONTENT_ENCODING);
+
+    let bytes = Self::retrieve_raw_body(body).await?;
+    let bytes = Self::decode_body(encoding, bytes).await?;
+    Ok(bytes)
+  }
+
   /// Retrieve the HTTP body.
   #[cfg(not(feature = "gzip"))]
   async fn retrieve_body<E>(response: Response<Incoming>) -> Result<Bytes, RequestError<E>> {
@@ -266,10 +758,146 @@ impl Client {
   }
 
   /// Create and issue a request and decode the response.
+  ///
+  /// If caching was enabled for endpoint `R` (see `Builder::cache`),
+  /// a cached response may be served instead of issuing a request.
   pub fn issue<R>(
     &self,
     input: &R::Input,
   ) -> impl Future<Output = Result<R::Output, RequestError<R::Error>>> + '_
+  where
+    R: Endpoint,
+  {
+    let future = self.issue_with::<R>(input, true);
+    async move { future.await.map(|(output, _status)| output) }
+  }
+
+  /// Create and issue a request and decode the response, additionally
+  /// reporting the HTTP status code of the response.
+  ///
+  /// Caching behaves as it does for [`issue`][Client::issue], with a
+  /// cached response reporting the status it was originally received
+  /// with.
+  pub fn issue_with_status<R>(
+    &self,
+    input: &R::Input,
+  ) -> impl Future<Output = Result<(R::Output, StatusCode), RequestError<R::Error>>> + '_
+  where
+    R: Endpoint,
+  {
+    self.issue_with::<R>(input, true)
+  }
+
+  /// Create and issue a request and decode the response, bypassing the
+  /// response cache.
+  ///
+  /// Note that the response received is still used for updating the
+  /// cache, if caching was enabled for endpoint `R`.
+  pub fn issue_uncached<R>(
+    &self,
+    input: &R::Input,
+  ) -> impl Future<Output = Result<R::Output, RequestError<R::Error>>> + '_
+  where
+    R: Endpoint,
+  {
+    let future = self.issue_with::<R>(input, false);
+    async move { future.await.map(|(output, _status)| output) }
+  }
+
+  /// Create and issue a request and decode the response, bypassing the
+  /// response cache, additionally reporting the delay requested by the
+  /// server via the `Retry-After` header if the request was rejected
+  /// because the rate limit was exceeded.
+  ///
+  /// In contrast to [`last_rate_limit`][Client::last_rate_limit], the
+  /// reported delay always pertains to this very request, even if the
+  /// client is used concurrently.
+  pub(crate) async fn issue_uncached_with_retry_after<R>(
+    &self,
+    input: &R::Input,
+  ) -> (Result<R::Output, RequestError<R::Error>>, Option<Duration>)
+  where
+    R: Endpoint,
+  {
+    let request = match self.request::<R>(input) {
+      Ok(request) => request,
+      Err(err) => return (Err(RequestError::Endpoint(err)), None),
+    };
+    let span = span!(
+      Level::INFO,
+      "issue",
+      method = display(request.method()),
+      uri = display(request.uri())
+    );
+    let (result, retry_after) = self
+      .issue_reporting::<R>(request, false)
+      .instrument(span)
+      .await;
+    (result.map(|(output, _status)| output), retry_after)
+  }
+
+  /// Construct the request that [`issue`][Client::issue] would send to
+  /// endpoint `R`, without sending it.
+  #[inline]
+  pub fn prepare<R>(&self, input: &R::Input) -> Result<PreparedRequest<R>, R::Error>
+  where
+    R: Endpoint,
+  {
+    let request = self.request::<R>(input)?;
+    Ok(PreparedRequest {
+      request,
+      _endpoint: PhantomData,
+    })
+  }
+
+  /// Send a previously prepared request and decode the response.
+  ///
+  /// The request is always sent and never served from the cache.
+  pub async fn execute<R>(
+    &self,
+    prepared: PreparedRequest<R>,
+  ) -> Result<R::Output, RequestError<R::Error>>
+  where
+    R: Endpoint,
+  {
+    let request = prepared.request;
+    let span = span!(
+      Level::INFO,
+      "issue",
+      method = display(request.method()),
+      uri = display(request.uri())
+    );
+    self
+      .issue_::<R>(request, false)
+      .instrument(span)
+      .await
+      .map(|(output, _status)| output)
+  }
+
+  /// Remove all cached responses.
+  #[inline]
+  pub fn clear_cache(&self) {
+    self.cache.clear()
+  }
+
+  /// Warm up the connection pool by issuing a lightweight request to
+  /// the Trading API.
+  ///
+  /// The connection established this way is kept in the pool and
+  /// reused by the next request, e.g., an order submission, which as a
+  /// result does not pay for connection establishment and TLS
+  /// handshake.
+  pub async fn warmup(&self) -> Result<(), RequestError<clock::GetError>> {
+    let _clock = self.issue_uncached::<clock::Get>(&()).await?;
+    Ok(())
+  }
+
+  /// Create and issue a request, optionally consulting the cache.
+  fn issue_with<R>(
+    &self,
+    input: &R::Input,
+    use_cache: bool,
+  ) -> impl Future<Output = Result<(R::Output, StatusCode), RequestError<R::Error>>> + '_
   where
     R: Endpoint,
   {
@@ -282,35 +910,171 @@ impl Client {
         method = display(request.method()),
         uri = display(request.uri())
       );
-      self.issue_::<R>(request).instrument(span).await
+      self.issue_::<R>(request, use_cache).instrument(span).await
     }
   }
 
-  /// Issue a request.
-  #[allow(clippy::cognitive_complexity)]
+  /// Issue a request, reporting the decoded response along with its
+  /// HTTP status.
   async fn issue_<R>(
     &self,
-    request: Request<Full<Bytes>>,
-  ) -> Result<R::Output, RequestError<R::Error>>
+    request: Request<Bytes>,
+    use_cache: bool,
+  ) -> Result<(R::Output, StatusCode), RequestError<R::Error>>
+  where
+    R: Endpoint,
+  {
+    let (result, _retry_after) = self.issue_reporting::<R>(request, use_cache).await;
+    result
+  }
+
+  /// Issue a request, reporting the decoded response along with its
+  /// HTTP status as well as the delay requested via the `Retry-After`
+  /// header if the request was rejected because the rate limit was
+  /// exceeded.
+  #[allow(clippy::cognitive_complexity)]
+  async fn issue_reporting<R>(
+    &self,
+    request: Request<Bytes>,
+    use_cache: bool,
+  ) -> (
+    Result<(R::Output, StatusCode), RequestError<R::Error>>,
+    Option<Duration>,
+  )
   where
     R: Endpoint,
   {
-    debug!("requesting");
-    trace!(request = debug_request(&request));
+    let cache_key = self.cache.key(type_name::<R>(), &request);
+    if let Some((key, _ttl)) = cache_key.as_ref().filter(|_| use_cache) {
+      if let Some((status, bytes)) = self.cache.get(key) {
+        debug!("serving cached response");
+        let result = R::evaluate(status, &bytes)
+          .map(|output| (output, status))
+          .map_err(RequestError::Endpoint);
+        return (result, None)
+      }
+    }
 
-    let result = self.client.request(request).await?;
+    let (status, retry_after, bytes) = match self.fetch::<R::Error>(request).await {
+      Ok(fetched) => fetched,
+      Err(err) => return (Err(err), None),
+    };
+    if let Some((key, ttl)) = cache_key {
+      if status.is_success() {
+        let () = self.cache.insert(key, ttl, status, bytes.clone());
+      }
+    }
+
+    let result = R::evaluate(status, &bytes)
+      .map(|output| (output, status))
+      .map_err(RequestError::Endpoint);
+    (result, retry_after)
+  }
+
+  /// Send a request and retrieve the response status and body, along
+  /// with the delay requested via the `Retry-After` header if the
+  /// request was rejected because the rate limit was exceeded.
+  async fn fetch<E>(
+    &self,
+    request: Request<Bytes>,
+  ) -> Result<(StatusCode, Option<Duration>, Bytes), RequestError<E>> {
+    let max_attempts = self.retry.map(|retry| retry.max_attempts).unwrap_or(1);
+    let mut attempt = 1;
+
+    let result = loop {
+      debug!("requesting");
+      trace!(request = debug_request(&request));
+
+      let result = self
+        .client
+        .request(clone_request(&request).map(Full::new))
+        .await?;
+      let status = result.status();
+      debug!(status = debug(&status));
+      trace!(response = debug(&result));
+
+      if let Some(rate_limit) = RateLimit::from_headers(result.headers()) {
+        *self.rate_limit.lock().unwrap() = Some(rate_limit);
+      }
+
+      match self.retry {
+        Some(retry) if status == StatusCode::TOO_MANY_REQUESTS && attempt < max_attempts => {
+          let delay = retry.delay(result.headers(), attempt, SystemTime::now());
+          debug!(
+            attempt,
+            delay = debug(&delay),
+            "rate limit exceeded; retrying"
+          );
+          // Drain the body so that the connection can be reused for
+          // the retry.
+          let _body = Self::retrieve_raw_body(result.into_body()).await?;
+          let () = sleep(delay).await;
+          attempt += 1;
+        },
+        _ => break result,
+      }
+    };
     let status = result.status();
-    debug!(status = debug(&status));
-    trace!(response = debug(&result));
+    let retry_after = (status == StatusCode::TOO_MANY_REQUESTS)
+      .then(|| parse_header(result.headers(), RETRY_AFTER.as_str()))
+      .flatten()
+      .map(Duration::from_secs);
 
-    let bytes = Self::retrieve_body::<R::Error>(result).await?;
-    let body = bytes.as_ref();
-    match from_utf8(body) {
+    let bytes = Self::retrieve_body::<E>(result).await?;
+    match from_utf8(&bytes) {
       Ok(s) => trace!(body = display(&s)),
       Err(b) => trace!(body = display(&b)),
     }
+    Ok((status, retry_after, bytes))
+  }
+
+  /// Create and issue a request, deserializing a successful response
+  /// using the provided [`DeserializeSeed`] instead of into the
+  /// endpoint's output type.
+  ///
+  /// This method allows for folding large responses, e.g., arrays of
+  /// bars or trades, incrementally without materializing the
+  /// endpoint's output. Note that the response body itself is still
+  /// received in full before deserialization begins. Responses are
+  /// never served from or stored in the cache.
+  pub async fn issue_with_seed<R, S, V>(
+    &self,
+    input: &R::Input,
+    seed: S,
+  ) -> Result<V, RequestError<R::Error>>
+  where
+    R: Endpoint<ConversionError = ConversionError>,
+    S: for<'de> DeserializeSeed<'de, Value = V>,
+  {
+    let request = self.request::<R>(input).map_err(RequestError::Endpoint)?;
+    let span = span!(
+      Level::INFO,
+      "issue",
+      method = display(request.method()),
+      uri = display(request.uri())
+    );
+
+    async move {
+      let (status, _retry_after, bytes) = self.fetch::<R::Error>(request).await?;
+      if !status.is_success() {
+        // Endpoints never report success with a non-2xx status, so
+        // evaluation yields the error to report.
+        if let Err(err) = R::evaluate(status, &bytes) {
+          return Err(RequestError::Endpoint(err))
+        }
+      }
 
-    R::evaluate(status, body).map_err(RequestError::Endpoint)
+      let mut deserializer = JsonDeserializer::from_slice(&bytes);
+      seed
+        .deserialize(&mut deserializer)
+        .and_then(|value| deserializer.end().map(|()| value))
+        .map_err(|err| {
+          let err = ConversionError::invalid_response(err, &bytes);
+          RequestError::Endpoint(R::Error::from(err))
+        })
+    }
+    .instrument(span)
+    .await
   }
 
   /// Subscribe to the given subscribable in order to receive updates.
@@ -321,6 +1085,7 @@ impl Client {
   ///   [`ApiInfo`] object to the call; if your [`Subscribable`]
   ///   requires a different input then invoke its `connect` method
   ///   yourself
+  #[cfg(feature = "streaming")]
   #[instrument(level = "debug", skip(self))]
   pub async fn subscribe<S>(&self) -> Result<(S::Stream, S::Subscription), Error>
   where
@@ -329,11 +1094,132 @@ impl Client {
     S::connect(&self.api_info).await
   }
 
+  /// Subscribe to the given subscribable, transparently reconnecting
+  /// once the connection is closed.
+  ///
+  /// On every reconnect, the connection is authenticated again and
+  /// the subscription state of the previous connection (e.g., the
+  /// market data subscribed to) is restored. Every reconnect attempt is
+  /// reported by a [`Resilient::Reconnecting`] event and every
+  /// successful reconnect by a [`Resilient::Reconnected`] event
+  /// carrying the subscription of the new connection. Reconnects are attempted with exponentially increasing delay,
+  /// bounded by [`max_delay`][RetryConfig::max_delay], for at most
+  /// [`max_attempts`][RetryConfig::max_attempts] consecutive times.
+  /// Statistics about reconnects are available through
+  /// [`ResilientStream::stats`].
+  ///
+  /// Errors establishing the initial connection are reported directly.
+  #[cfg(feature = "streaming")]
+  #[instrument(level = "debug", skip(self))]
+  pub async fn subscribe_resilient<S>(
+    &self,
+    config: RetryConfig,
+  ) -> Result<
+    (
+      ResilientStream<
+        impl Stream<Item = Resilient<<S::Stream as Stream>::Item, S::Subscription>> + use<S>,
+      >,
+      S::Subscription,
+    ),
+    Error,
+  >
+  where
+    S: Resubscribable<Input = ApiInfo>,
+    S::Stream: Stream + Unpin,
+  {
+    let (stream, subscription) = S::connect(&self.api_info).await?;
+    let stats = ReconnectStats::default();
+    let counters = stats.0.clone();
+    let subscribed = Arc::new(S::state(&subscription));
+    let state = Some((ResilientState::Connected(stream), self.api_info.clone()));
+
+    let stream = unfold(state, move |state| {
+      let counters = counters.clone();
+      let subscribed = subscribed.clone();
+      async move {
+        let (state, api_info) = state?;
+        let attempt = match state {
+          ResilientState::Connected(mut stream) => match stream.next().await {
+            Some(message) => {
+              let state = ResilientState::Connected(stream);
+              return Some((Resilient::Message(message), Some((state, api_info))))
+            },
+            None => {
+              let event = Resilient::Reconnecting {
+                attempt: 1,
+                error: None,
+              };
+              let state = ResilientState::Reconnecting(1);
+              return Some((event, Some((state, api_info))))
+            },
+          },
+          ResilientState::Reconnecting(attempt) => attempt,
+        };
+
+        let () = sleep(config.backoff(attempt)).await;
+        let _count = counters.attempts.fetch_add(1, Ordering::Relaxed);
+        let result = match S::connect(&api_info).await {
+          Ok((mut stream, mut subscription)) => {
+            S::resubscribe(&subscribed, &mut stream, &mut subscription)
+              .await
+              .map(|()| (stream, subscription))
+          },
+          Err(err) => Err(err),
+        };
+
+        match result {
+          Ok((stream, subscription)) => {
+            debug!(attempt, "reconnected to stream");
+            let _count = counters.reconnects.fetch_add(1, Ordering::Relaxed);
+            Some((
+              Resilient::Reconnected(subscription),
+              Some((ResilientState::Connected(stream), api_info)),
+            ))
+          },
+          Err(err) => {
+            debug!(
+              attempt,
+              error = display(&err),
+              "failed to reconnect to stream"
+            );
+            let _count = counters.failures.fetch_add(1, Ordering::Relaxed);
+            if attempt < config.max_attempts {
+              let event = Resilient::Reconnecting {
+                attempt: attempt + 1,
+                error: Some(err),
+              };
+              Some((
+                event,
+                Some((ResilientState::Reconnecting(attempt + 1), api_info)),
+              ))
+            } else {
+              Some((Resilient::Disconnected(err), None))
+            }
+          },
+        }
+      }
+    });
+    let stream = ResilientStream {
+      stream: Box::pin(stream),
+      stats,
+    };
+    Ok((stream, subscription))
+  }
+
   /// Retrieve the `ApiInfo` object used by this `Client` instance.
   #[inline]
   pub fn api_info(&self) -> &ApiInfo {
     &self.api_info
   }
+
+  /// Retrieve the rate limit information reported alongside the most
+  /// recent response that included it.
+  ///
+  /// `None` is returned if no such response was received yet.
+  #[inline]
+  pub fn last_rate_limit(&self) -> Option<RateLimit> {
+    *self.rate_limit.lock().unwrap()
+  }
 }
 
 
@@ -341,11 +1227,22 @@ impl Client {
 mod tests {
   use super::*;
 
-  use http::StatusCode;
+  use num_decimal::Num;
+
+  use serde::de::IgnoredAny;
+  use serde::de::SeqAccess;
+  use serde::de::Visitor;
+  use serde::Deserializer;
+
+  use serde_json::json;
 
   use test_log::test;
 
+  use crate::api::v2::fixture::order_json;
+  use crate::api::v2::order;
+  use crate::api::API_BASE_URL;
   use crate::endpoint::ApiError;
+  use crate::mock::Server;
   use crate::Str;
 
 
@@ -360,6 +1257,351 @@ mod tests {
   }
 
 
+  Endpoint! {
+    GetCached(()),
+    Ok => (), [
+      /* 200 */ OK,
+    ],
+    Err => GetCachedError, []
+
+    fn path(_input: &Self::Input) -> Str {
+      "/v2/cached".into()
+    }
+  }
+
+
+  /// Check that a cached response is served for a request issued
+  /// within the configured TTL, without hitting the server.
+  #[test(tokio::test)]
+  async fn cached_response() {
+    let server = Server::serve(|_request| (StatusCode::OK, "null".to_string())).await;
+    let api_info = server.api_info();
+    let client = Client::builder()
+      .cache::<GetCached>(Duration::from_secs(60))
+      .build(api_info);
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests().len(), 1);
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests().len(), 1);
+
+    // Bypassing the cache should always contact the server.
+    let () = client.issue_uncached::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    let () = client.clear_cache();
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests().len(), 3);
+  }
+
+  /// Check that responses are not served from the cache once their TTL
+  /// expired.
+  #[test(tokio::test)]
+  async fn expired_cached_response() {
+    let server = Server::serve(|_request| (StatusCode::OK, "null".to_string())).await;
+    let api_info = server.api_info();
+    let client = Client::builder()
+      .cache::<GetCached>(Duration::ZERO)
+      .build(api_info);
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+  }
+
+  /// Check that the configured HTTP version is used for requests.
+  #[test(tokio::test)]
+  async fn request_http_version() {
+    let server = Server::serve(|_request| (StatusCode::OK, "null".to_string())).await;
+    let client = Client::builder()
+      .http_version(HttpVersion::Http1)
+      .build(server.api_info());
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests(), ["GET /v2/cached"]);
+
+    // The mock server only speaks HTTP/1.1, but we can still check that
+    // an HTTP/2 connection preface was sent.
+    let server = Server::serve(|_request| (StatusCode::OK, "null".to_string())).await;
+    let client = Client::builder()
+      .http_version(HttpVersion::Http2)
+      .build(server.api_info());
+
+    let _err = client.issue::<GetCached>(&()).await.unwrap_err();
+    assert_eq!(server.requests().first().map(String::as_str), Some("PRI *"));
+  }
+
+  /// Check that we determine the correct delay before retrying a
+  /// rate limited request.
+  #[test]
+  fn retry_delay() {
+    let config = RetryConfig {
+      base_delay: Duration::from_millis(100),
+      ..Default::default()
+    };
+    let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    let mut headers = HeaderMap::new();
+    assert_eq!(config.delay(&headers, 1, now), Duration::from_millis(100));
+    assert_eq!(config.delay(&headers, 3, now), Duration::from_millis(400));
+
+    let _prev = headers.insert(HDR_RATE_LIMIT_RESET, HeaderValue::from_static("1700000002"));
+    assert_eq!(config.delay(&headers, 1, now), Duration::from_secs(2));
+
+    // A reset time in the past does not cause any delay.
+    let _prev = headers.insert(HDR_RATE_LIMIT_RESET, HeaderValue::from_static("1600000000"));
+    assert_eq!(config.delay(&headers, 1, now), Duration::ZERO);
+
+    let _prev = headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+    assert_eq!(config.delay(&headers, 1, now), Duration::from_secs(5));
+
+    // Delays requested by the server are bounded by `max_delay` as
+    // well.
+    let _prev = headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+    assert_eq!(config.delay(&headers, 1, now), config.max_delay);
+
+    let _prev = headers.remove(RETRY_AFTER);
+    let _prev = headers.insert(HDR_RATE_LIMIT_RESET, HeaderValue::from_static("1700003600"));
+    assert_eq!(config.delay(&headers, 1, now), config.max_delay);
+  }
+
+  /// Check that requests rejected due to the rate limit being exceeded
+  /// are retried, if configured.
+  #[test(tokio::test)]
+  async fn retry_rate_limited_request() {
+    let mut count = 0;
+    let server = Server::serve(move |_request| {
+      count += 1;
+      if count < 3 {
+        (StatusCode::TOO_MANY_REQUESTS, "{}".to_string())
+      } else {
+        (StatusCode::OK, "null".to_string())
+      }
+    })
+    .await;
+    let config = RetryConfig {
+      max_attempts: 3,
+      base_delay: Duration::from_millis(1),
+      ..Default::default()
+    };
+    let client = Client::builder().retry(config).build(server.api_info());
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(server.requests().len(), 3);
+  }
+
+  /// Check that the final error is reported once all retry attempts
+  /// are exhausted.
+  #[test(tokio::test)]
+  async fn retry_rate_limited_request_exhausted() {
+    let server = Server::serve(|_request| (StatusCode::TOO_MANY_REQUESTS, "{}".to_string())).await;
+    let config = RetryConfig {
+      max_attempts: 2,
+      base_delay: Duration::from_millis(1),
+      ..Default::default()
+    };
+    let client = Client::builder().retry(config).build(server.api_info());
+
+    let err = client.issue::<GetCached>(&()).await.unwrap_err();
+    match err {
+      RequestError::Endpoint(GetCachedError::RateLimitExceeded(_)) => (),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+    assert_eq!(server.requests().len(), 2);
+
+    // Without a retry configuration no request is retried.
+    let client = Client::new(server.api_info());
+    let _err = client.issue::<GetCached>(&()).await.unwrap_err();
+    assert_eq!(server.requests().len(), 3);
+  }
+
+  /// Check that rate limit information reported alongside responses is
+  /// surfaced.
+  #[test(tokio::test)]
+  async fn last_rate_limit() {
+    let mut remaining = 200;
+    let server = Server::serve_with_headers(move |_request| {
+      remaining -= 1;
+      let headers = vec![
+        (HDR_RATE_LIMIT_LIMIT.to_string(), "200".to_string()),
+        (HDR_RATE_LIMIT_REMAINING.to_string(), remaining.to_string()),
+        (HDR_RATE_LIMIT_RESET.to_string(), "1700000060".to_string()),
+      ];
+      (StatusCode::OK, headers, "null".to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+    assert_eq!(client.last_rate_limit(), None);
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    let rate_limit = client.last_rate_limit().unwrap();
+    assert_eq!(rate_limit.limit, 200);
+    assert_eq!(rate_limit.remaining, 199);
+    assert_eq!(
+      rate_limit.reset,
+      DateTime::from_timestamp(1_700_000_060, 0).unwrap()
+    );
+
+    let () = client.issue::<GetCached>(&()).await.unwrap();
+    assert_eq!(client.last_rate_limit().unwrap().remaining, 198);
+  }
+
+  /// Check that incomplete rate limit information is ignored.
+  #[test]
+  fn parse_incomplete_rate_limit() {
+    let mut headers = HeaderMap::new();
+    let _prev = headers.insert(HDR_RATE_LIMIT_LIMIT, HeaderValue::from_static("200"));
+    let _prev = headers.insert(HDR_RATE_LIMIT_RESET, HeaderValue::from_static("1700000060"));
+    assert_eq!(RateLimit::from_headers(&headers), None);
+
+    let _prev = headers.insert(HDR_RATE_LIMIT_REMAINING, HeaderValue::from_static("foo"));
+    assert_eq!(RateLimit::from_headers(&headers), None);
+
+    let _prev = headers.insert(HDR_RATE_LIMIT_REMAINING, HeaderValue::from_static("42"));
+    assert_eq!(RateLimit::from_headers(&headers).unwrap().remaining, 42);
+  }
+
+  /// Check that warming up the connection pool issues exactly one
+  /// request.
+  #[test(tokio::test)]
+  async fn warmup() {
+    let server = Server::serve(|_request| {
+      let clock = r#"{
+  "timestamp": "2018-04-01T12:00:00.000Z",
+  "is_open": true,
+  "next_open": "2018-04-01T12:00:00.000Z",
+  "next_close": "2018-04-01T12:00:00.000Z"
+}"#;
+      (StatusCode::OK, clock.to_string())
+    })
+    .await;
+    let client = Client::builder()
+      .cache::<clock::Get>(Duration::from_secs(60))
+      .build(server.api_info());
+
+    let () = client.warmup().await.unwrap();
+    assert_eq!(server.requests(), vec!["GET /v2/clock".to_string()]);
+
+    // Warming up should never be served from the cache.
+    let () = client.warmup().await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+  }
+
+  /// A seed counting the elements of a JSON array without
+  /// materializing them.
+  struct CountSeed;
+
+  impl<'de> DeserializeSeed<'de> for CountSeed {
+    type Value = usize;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+      D: Deserializer<'de>,
+    {
+      struct CountVisitor;
+
+      impl<'de> Visitor<'de> for CountVisitor {
+        type Value = usize;
+
+        fn expecting(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+          fmt.write_str("an array")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+          A: SeqAccess<'de>,
+        {
+          let mut count = 0;
+          while let Some(IgnoredAny) = seq.next_element()? {
+            count += 1;
+          }
+          Ok(count)
+        }
+      }
+
+      deserializer.deserialize_seq(CountVisitor)
+    }
+  }
+
+
+  /// Check that we can fold a large response using a custom
+  /// deserialization seed.
+  #[test(tokio::test)]
+  async fn issue_with_count_seed() {
+    let server = Server::serve(|_request| {
+      let bar = r#"{"t":"2021-02-01T16:01:00Z","o":133.32,"c":133.5,"v":9876}"#;
+      let body = format!("[{}]", vec![bar; 10000].join(","));
+      (StatusCode::OK, body)
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let count = client
+      .issue_with_seed::<GetCached, _, _>(&(), CountSeed)
+      .await
+      .unwrap();
+    assert_eq!(count, 10000);
+  }
+
+  /// Check that a prepared request matches what is sent over the wire.
+  #[test(tokio::test)]
+  async fn prepare_and_execute() {
+    let server = Server::serve(|_request| {
+      let order = order_json(json!({
+        "qty": "1",
+        "type": "limit",
+        "limit_price": "100",
+      }));
+      (StatusCode::OK, order.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = order::CreateReqInit {
+      type_: order::Type::Limit,
+      limit_price: Some(Num::from(100)),
+      ..Default::default()
+    }
+    .init("AAPL", order::Side::Buy, order::Amount::quantity(1));
+
+    let prepared = client.prepare::<order::Create>(&request).unwrap();
+    assert_eq!(prepared.method(), Method::POST);
+    assert_eq!(prepared.url().path(), "/v2/orders");
+    assert_eq!(prepared.headers()[HDR_SECRET], "<masked>");
+    let body = prepared.body().to_vec();
+    assert!(format!("{prepared:?}").contains("<masked>"));
+    assert!(server.requests().is_empty());
+
+    let _order = client.execute(prepared).await.unwrap();
+    assert_eq!(server.requests(), vec!["POST /v2/orders".to_string()]);
+    assert_eq!(server.bodies(), vec![body]);
+  }
+
+  /// Check that the HTTP status of a response is reported by
+  /// `Client::issue_with_status`.
+  #[test(tokio::test)]
+  async fn issue_with_multi_status() {
+    let server = Server::serve(|_request| {
+      let body = r#"[
+        {"id": "d56ba3ea-4b5d-4bb3-a6fc-4c8ae8bbd6b9", "status": 200},
+        {"id": "0b6b3c6a-c8d8-4c3e-9d2f-8e4d1f5b2a10", "status": 500}
+      ]"#;
+      (StatusCode::MULTI_STATUS, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let (results, status) = client
+      .issue_with_status::<order::DeleteAll>(&())
+      .await
+      .unwrap();
+    assert_eq!(status, StatusCode::MULTI_STATUS);
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_success());
+    assert!(!results[1].is_success());
+  }
+
   /// Check that we can retrieve the `ApiInfo` object used by a client.
   #[test]
   fn client_api_info() {
@@ -380,6 +1622,52 @@ mod tests {
     assert!(string.contains("<masked>"), "{string}");
   }
 
+  /// Check that requests authenticate using a bearer token if an
+  /// OAuth access token is configured.
+  #[test]
+  fn oauth_bearer_token() {
+    let api_info = ApiInfo::from_oauth(API_BASE_URL, "access-token").unwrap();
+    let client = Client::builder().build(api_info);
+
+    let prepared = client.prepare::<GetNotFound>(&()).unwrap();
+    let headers = prepared.request.headers();
+    assert_eq!(headers[AUTHORIZATION], "Bearer access-token");
+    assert!(!headers.contains_key(HDR_KEY_ID));
+    assert!(!headers.contains_key(HDR_SECRET));
+
+    assert_eq!(prepared.headers()[AUTHORIZATION], "<masked>");
+    let string = format!("{:?}", debug_request(&prepared.request));
+    assert!(!string.contains("access-token"), "{string}");
+  }
+
+  /// Check that we transparently uncompress gzip encoded bodies.
+  #[cfg(feature = "gzip")]
+  #[test(tokio::test)]
+  async fn decode_gzip_body() {
+    use async_compression::futures::bufread::GzipEncoder;
+    use futures::AsyncReadExt as _;
+
+    let body = br#"{"bars":[],"symbol":"SPY","next_page_token":null}"#;
+    let mut compressed = Vec::new();
+    let _count = GzipEncoder::new(&body[..])
+      .read_to_end(&mut compressed)
+      .await
+      .unwrap();
+    assert_ne!(compressed, body);
+
+    let gzip = HeaderValue::from_static("gzip");
+    let bytes = Client::decode_body(Some(&gzip), Bytes::from(compressed))
+      .await
+      .unwrap();
+    assert_eq!(bytes, &body[..]);
+
+    // Bodies without gzip encoding should be passed through unchanged.
+    let bytes = Client::decode_body(None, Bytes::from_static(body))
+      .await
+      .unwrap();
+    assert_eq!(bytes, &body[..]);
+  }
+
   /// Check basic workings of the HTTP status evaluation logic.
   #[test(tokio::test)]
   async fn unexpected_status_code_return() {
@@ -391,6 +1679,7 @@ mod tests {
     match err {
       RequestError::Endpoint(GetNotFoundError::UnexpectedStatus(status, message)) => {
         let expected = ApiError {
+          code: Some(40410000),
           message: "endpoint not found".to_string(),
         };
         assert_eq!(message, Ok(expected));
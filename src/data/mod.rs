@@ -8,3 +8,96 @@ pub mod v2;
 pub(crate) const DATA_BASE_URL: &str = "https://data.alpaca.markets";
 /// The base URL for streaming market data over a websocket connection.
 pub(crate) const DATA_STREAM_BASE_URL: &str = "wss://stream.data.alpaca.markets";
+
+
+/// An enumeration of the various versions of the Alpaca Data API.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Version {
+  /// The second (stable) version of the API.
+  V2,
+  /// The `v1beta1` version of the API.
+  V1Beta1,
+  /// The `v1beta3` version of the API.
+  V1Beta3,
+}
+
+impl Version {
+  /// Retrieve the version's path prefix, without slashes.
+  #[inline]
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::V2 => "v2",
+      Self::V1Beta1 => "v1beta1",
+      Self::V1Beta3 => "v1beta3",
+    }
+  }
+
+  /// Create an absolute, versioned path from a path relative to the
+  /// version's root.
+  #[inline]
+  pub(crate) fn path(&self, path: &str) -> String {
+    format!("/{}/{}", self.as_str(), path)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::str::FromStr as _;
+
+  use chrono::DateTime;
+
+  use http_endpoint::Endpoint;
+
+  use crate::data::v2::bars;
+  use crate::data::v2::last_quotes;
+  use crate::data::v2::quotes;
+  use crate::data::v2::trades;
+
+
+  /// Check that versions map to the expected path prefixes.
+  #[test]
+  fn versioned_paths() {
+    assert_eq!(Version::V2.path("stocks/bars"), "/v2/stocks/bars");
+    assert_eq!(Version::V1Beta1.path("news"), "/v1beta1/news");
+    assert_eq!(
+      Version::V1Beta3.path("crypto/us/bars"),
+      "/v1beta3/crypto/us/bars"
+    );
+  }
+
+  /// Check that each data endpoint produces the correctly versioned
+  /// path.
+  #[test]
+  fn endpoint_paths() {
+    let start = DateTime::from_str("2022-01-04T13:35:00Z").unwrap();
+    let end = DateTime::from_str("2022-01-04T13:36:00Z").unwrap();
+
+    let request = bars::ListReqInit::default().init("SPY", start, end, bars::TimeFrame::OneMinute);
+    assert_eq!(
+      <bars::List as Endpoint>::path(&request),
+      "/v2/stocks/SPY/bars"
+    );
+
+    let request = quotes::ListReqInit::default().init("SPY", start, end);
+    assert_eq!(
+      <quotes::List as Endpoint>::path(&request),
+      "/v2/stocks/SPY/quotes"
+    );
+
+    let request = trades::ListReqInit::default().init("SPY", start, end);
+    assert_eq!(
+      <trades::List as Endpoint>::path(&request),
+      "/v2/stocks/SPY/trades"
+    );
+
+    let request = last_quotes::GetReqInit::default().init(["SPY"]);
+    assert_eq!(
+      <last_quotes::Get as Endpoint>::path(&request),
+      "/v2/stocks/quotes/latest"
+    );
+  }
+}
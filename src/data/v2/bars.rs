@@ -1,9 +1,21 @@
 // Copyright (C) 2021-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::future::Future;
+use std::str::FromStr;
+
 use chrono::DateTime;
 use chrono::Utc;
 
+use futures::stream::iter;
+use futures::StreamExt as _;
+
 use num_decimal::Num;
 
 use serde::Deserialize;
@@ -11,8 +23,14 @@ use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
 
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::MAX_LIMIT;
+use crate::data::v2::VERSION;
+use crate::endpoint::BaseUrl;
+use crate::util::clamp_limit;
+use crate::util::option_from_non_empty_str;
 use crate::util::vec_from_str;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -23,14 +41,84 @@ pub enum TimeFrame {
   /// A time frame of one minute.
   #[serde(rename = "1Min")]
   OneMinute,
+  /// A time frame of five minutes.
+  #[serde(rename = "5Min")]
+  FiveMinutes,
+  /// A time frame of 15 minutes.
+  #[serde(rename = "15Min")]
+  FifteenMinutes,
   /// A time frame of one hour.
   #[serde(rename = "1Hour")]
   OneHour,
   /// A time frame of one day.
   #[serde(rename = "1Day")]
   OneDay,
+  /// A time frame of one week.
+  #[serde(rename = "1Week")]
+  OneWeek,
+  /// A time frame of one month.
+  #[serde(rename = "1Month")]
+  OneMonth,
+}
+
+impl TimeFrame {
+  /// All supported time frames.
+  const ALL: [TimeFrame; 7] = [
+    Self::OneMinute,
+    Self::FiveMinutes,
+    Self::FifteenMinutes,
+    Self::OneHour,
+    Self::OneDay,
+    Self::OneWeek,
+    Self::OneMonth,
+  ];
+}
+
+impl AsRef<str> for TimeFrame {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Self::OneMinute => "1Min",
+      Self::FiveMinutes => "5Min",
+      Self::FifteenMinutes => "15Min",
+      Self::OneHour => "1Hour",
+      Self::OneDay => "1Day",
+      Self::OneWeek => "1Week",
+      Self::OneMonth => "1Month",
+    }
+  }
+}
+
+impl FromStr for TimeFrame {
+  type Err = ParseTimeFrameError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::ALL
+      .into_iter()
+      .find(|time_frame| time_frame.as_ref() == s)
+      .ok_or_else(|| ParseTimeFrameError(s.to_string()))
+  }
+}
+
+
+/// An error indicating that a string does not represent a supported
+/// [`TimeFrame`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseTimeFrameError(String);
+
+impl Display for ParseTimeFrameError {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    write!(fmt, "unsupported time frame '{}'; expected one of ", self.0)?;
+    for (i, time_frame) in TimeFrame::ALL.iter().enumerate() {
+      let sep = if i == 0 { "" } else { ", " };
+      write!(fmt, "{sep}{}", time_frame.as_ref())?;
+    }
+    Ok(())
+  }
 }
 
+impl StdError for ParseTimeFrameError {}
+
 
 /// An enumeration of the possible adjustments.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
@@ -60,8 +148,9 @@ pub struct ListReq {
   /// The maximum number of bars to be returned for each symbol.
   ///
   /// It can be between 1 and 10000. Defaults to 1000 if the provided
-  /// value is None.
-  #[serde(rename = "limit")]
+  /// value is None. Larger values are clamped to the maximum when
+  /// issuing the request.
+  #[serde(rename = "limit", serialize_with = "clamp_limit::<MAX_LIMIT, _>")]
   pub limit: Option<usize>,
   /// Filter bars equal to or after this time.
   #[serde(rename = "start")]
@@ -84,6 +173,10 @@ pub struct ListReq {
   /// If provided we will pass a page token to continue where we left off.
   #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
   pub page_token: Option<String>,
+  /// The currency in which prices are to be reported, as an ISO 4217
+  /// code (e.g., "JPY"). Defaults to USD.
+  #[serde(rename = "currency", skip_serializing_if = "Option::is_none")]
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -102,6 +195,8 @@ pub struct ListReqInit {
   pub feed: Option<Feed>,
   /// See `ListReq::page_token`.
   pub page_token: Option<String>,
+  /// See `ListReq::currency`.
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
@@ -129,6 +224,7 @@ impl ListReqInit {
       adjustment: self.adjustment,
       feed: self.feed,
       page_token: self.page_token,
+      currency: self.currency,
       _non_exhaustive: (),
     }
   }
@@ -166,6 +262,32 @@ pub struct Bar {
 }
 
 
+/// Compute the cumulative volume weighted average price (VWAP) over a
+/// series of bars.
+///
+/// Each bar contributes its typical price, i.e., the average of its
+/// high, low, and close prices, weighted by its volume. The result
+/// contains the running VWAP as of each bar, paired with the bar's
+/// time. Bars without any volume do not carry any price information
+/// and are skipped.
+pub fn vwap(bars: &[Bar]) -> Vec<(DateTime<Utc>, Num)> {
+  let mut total_value = Num::from(0);
+  let mut total_volume = Num::from(0);
+
+  bars
+    .iter()
+    .filter(|bar| bar.volume != 0)
+    .map(|bar| {
+      let typical = (&bar.high + &bar.low + &bar.close) / 3;
+      let volume = Num::from(bar.volume);
+      total_value += &typical * &volume;
+      total_volume += &volume;
+      (bar.time, &total_value / &total_volume)
+    })
+    .collect()
+}
+
+
 /// A collection of bars as returned by the API. This is one page of
 /// bars.
 #[derive(Debug, Deserialize, Eq, PartialEq)]
@@ -178,8 +300,19 @@ pub struct Bars {
   pub symbol: String,
   /// The token to provide to a request to get the next page of bars for
   /// this request.
-  #[serde(rename = "next_page_token")]
+  ///
+  /// `None` (reported as either `null` or an empty string) indicates
+  /// that no more data is available.
+  #[serde(
+    rename = "next_page_token",
+    default,
+    deserialize_with = "option_from_non_empty_str"
+  )]
   pub next_page_token: Option<String>,
+  /// The currency in which prices of the bars are reported, if it
+  /// was reported by the API.
+  #[serde(rename = "currency", default)]
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -200,11 +333,11 @@ Endpoint! {
   ]
 
   fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+    BaseUrl::Data.into_str()
   }
 
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/stocks/{}/bars", input.symbol).into()
+    VERSION.path(&format!("stocks/{}/bars", input.symbol)).into()
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -213,22 +346,105 @@ Endpoint! {
 }
 
 
+/// Run `fetch` once for each of the provided (deduplicated) symbols,
+/// with at most `concurrency` invocations in flight at any time, and
+/// collect the results keyed by symbol.
+async fn fan_out<F, Fut, T, E>(
+  symbols: &[String],
+  concurrency: usize,
+  fetch: F,
+) -> HashMap<String, Result<T, E>>
+where
+  F: Fn(String) -> Fut,
+  Fut: Future<Output = Result<T, E>>,
+{
+  let mut seen = HashSet::with_capacity(symbols.len());
+  let symbols = symbols
+    .iter()
+    .filter(|symbol| seen.insert(symbol.as_str()))
+    .cloned()
+    .collect::<Vec<_>>();
+
+  iter(symbols)
+    .map(|symbol| {
+      let result = fetch(symbol.clone());
+      async move { (symbol, result.await) }
+    })
+    .buffer_unordered(concurrency.max(1))
+    .collect()
+    .await
+}
+
+
+/// Retrieve all bars for `symbol`, following page tokens until the
+/// data are exhausted.
+async fn list_all(
+  client: &Client,
+  mut request: ListReq,
+) -> Result<Vec<Bar>, RequestError<ListError>> {
+  let mut bars = Vec::new();
+  loop {
+    let page = client.issue::<List>(&request).await?;
+    bars.extend(page.bars);
+
+    match page.next_page_token {
+      Some(token) => request.page_token = Some(token),
+      None => break Ok(bars),
+    }
+  }
+}
+
+
+/// Retrieve bars for multiple symbols concurrently.
+///
+/// `request` acts as a template: it is issued once for each of the
+/// provided symbols, with its `symbol` replaced accordingly. Duplicate
+/// symbols are retrieved only once. All pages of data are retrieved,
+/// starting at `request.page_token`. At most `concurrency` symbols are
+/// being retrieved at any time, allowing to stay within the rate
+/// limit. Requests rejected because the rate limit was exceeded
+/// nevertheless are retried as per the client's
+/// [`RetryConfig`][crate::RetryConfig], if any.
+///
+/// Errors are reported on a per-symbol basis, i.e., the failure to
+/// retrieve bars for one symbol does not affect any other.
+pub async fn list_multi(
+  client: &Client,
+  symbols: &[String],
+  request: &ListReq,
+  concurrency: usize,
+) -> HashMap<String, Result<Vec<Bar>, RequestError<ListError>>> {
+  fan_out(symbols, concurrency, |symbol| {
+    let request = ListReq {
+      symbol,
+      ..request.clone()
+    };
+    list_all(client, request)
+  })
+  .await
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use std::ops::RangeInclusive;
-  use std::str::FromStr as _;
+  use std::time::Duration;
 
+  use http::StatusCode;
   use http_endpoint::Endpoint;
 
   use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
 
   use test_log::test;
 
   use crate::api_info::ApiInfo;
+  use crate::mock::Server;
   use crate::Client;
   use crate::RequestError;
+  use crate::RetryConfig;
 
 
   #[track_caller]
@@ -284,6 +500,214 @@ mod tests {
     assert!(res.next_page_token.is_some())
   }
 
+  /// Check that we can parse time frames from their string
+  /// representation and reject invalid ones.
+  #[test]
+  fn parse_time_frame() {
+    for time_frame in TimeFrame::ALL {
+      let string = time_frame.as_ref();
+      assert_eq!(TimeFrame::from_str(string).unwrap(), time_frame);
+      assert_eq!(to_json(&time_frame).unwrap(), format!(r#""{string}""#));
+    }
+    assert_eq!(
+      TimeFrame::from_str("15Min").unwrap(),
+      TimeFrame::FifteenMinutes
+    );
+    assert_eq!(TimeFrame::from_str("5Min").unwrap(), TimeFrame::FiveMinutes);
+
+    for string in ["", "1min", "2Min", "1 Day", "1Year", " 1Day"] {
+      let err = TimeFrame::from_str(string).unwrap_err();
+      assert_eq!(err, ParseTimeFrameError(string.to_string()));
+      assert!(
+        err.to_string().contains("1Min, 5Min, 15Min, 1Hour"),
+        "{err}"
+      );
+    }
+  }
+
+  /// Check that the `currency` parameter is encoded in the query only
+  /// when set and that the reported currency is parsed.
+  #[test]
+  fn currency() {
+    let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
+    let end = DateTime::from_str("2018-12-06T21:47:00Z").unwrap();
+    let request = ListReqInit {
+      currency: Some("JPY".to_string()),
+      ..Default::default()
+    }
+    .init("AAPL", start, end, TimeFrame::OneDay);
+
+    let query = <List as Endpoint>::query(&request).unwrap().unwrap();
+    assert!(query.contains("currency=JPY"), "{query}");
+
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::OneDay);
+    let query = <List as Endpoint>::query(&request).unwrap().unwrap();
+    assert!(!query.contains("currency"), "{query}");
+
+    let response = r#"{"bars":[],"symbol":"AAPL","next_page_token":null,"currency":"JPY"}"#;
+    let bars = from_json::<<List as Endpoint>::Output>(response).unwrap();
+    assert_eq!(bars.currency.as_deref(), Some("JPY"));
+  }
+
+  /// Check that a `limit` exceeding the API maximum is clamped in the
+  /// query.
+  #[test]
+  fn clamp_limit_in_query() {
+    let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
+    let end = DateTime::from_str("2018-12-06T21:47:00Z").unwrap();
+    let request = |limit| {
+      ListReqInit {
+        limit: Some(limit),
+        ..Default::default()
+      }
+      .init("AAPL", start, end, TimeFrame::OneDay)
+    };
+
+    let query = <List as Endpoint>::query(&request(20000)).unwrap().unwrap();
+    assert!(query.contains("limit=10000"), "{query}");
+
+    let query = <List as Endpoint>::query(&request(500)).unwrap().unwrap();
+    assert!(query.contains("limit=500"), "{query}");
+  }
+
+  /// Check that the feed is selected for each request individually,
+  /// even when issued through the same client.
+  #[test(tokio::test)]
+  async fn feed_per_request() {
+    let server = Server::serve(|_request| {
+      let body = r#"{"bars": [], "symbol": "AAPL", "next_page_token": null}"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
+    let end = DateTime::from_str("2018-12-06T21:47:00Z").unwrap();
+    let request = |feed| {
+      ListReqInit {
+        feed,
+        ..Default::default()
+      }
+      .init("AAPL", start, end, TimeFrame::OneDay)
+    };
+
+    for feed in [Some(Feed::IEX), Some(Feed::SIP), None] {
+      let _bars = client.issue::<List>(&request(feed)).await.unwrap();
+    }
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[0].contains("feed=iex"), "{}", requests[0]);
+    assert!(requests[1].contains("feed=sip"), "{}", requests[1]);
+    assert!(!requests[2].contains("feed"), "{}", requests[2]);
+  }
+
+  /// Check that fanning out over multiple symbols isolates per-symbol
+  /// errors.
+  #[test(tokio::test)]
+  async fn fan_out_error_isolation() {
+    let symbols = ["AAPL", "XXXX", "MSFT", "SPY"].map(String::from);
+    let results = fan_out(&symbols, 2, |symbol| async move {
+      if symbol == "XXXX" {
+        Err(format!("no data for {symbol}"))
+      } else {
+        Ok(symbol.len())
+      }
+    })
+    .await;
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results["AAPL"], Ok(4));
+    assert_eq!(results["XXXX"], Err("no data for XXXX".to_string()));
+    assert_eq!(results["MSFT"], Ok(4));
+    assert_eq!(results["SPY"], Ok(3));
+  }
+
+  /// Check that both a `null` and an empty next page token signal the
+  /// end of pagination.
+  #[test]
+  fn pagination_end() {
+    let pages = [
+      r#"{"bars":[],"symbol":"AAPL","next_page_token":"MjAyMQ=="}"#,
+      r#"{"bars":[],"symbol":"AAPL","next_page_token":""}"#,
+      r#"{"bars":[],"symbol":"AAPL","next_page_token":"MjAyMg=="}"#,
+    ];
+
+    let mut count = 0;
+    for page in pages {
+      count += 1;
+      let bars = from_json::<Bars>(page).unwrap();
+      if bars.next_page_token.is_none() {
+        break
+      }
+    }
+    assert_eq!(count, 2);
+
+    let response = r#"{"bars":[],"symbol":"AAPL","next_page_token":null}"#;
+    let bars = from_json::<Bars>(response).unwrap();
+    assert_eq!(bars.next_page_token, None);
+
+    let response = r#"{"bars":[],"symbol":"AAPL"}"#;
+    let bars = from_json::<Bars>(response).unwrap();
+    assert_eq!(bars.next_page_token, None);
+  }
+
+  /// Check that we compute the cumulative VWAP of a series of bars
+  /// correctly, skipping bars without volume.
+  #[test]
+  fn vwap_series() {
+    let response = r#"[
+      {"t":"2021-02-01T16:01:00Z","o":10,"h":12,"l":9,"c":12,"v":100,"vw":11},
+      {"t":"2021-02-01T16:02:00Z","o":12,"h":13,"l":12,"c":12,"v":0,"vw":0},
+      {"t":"2021-02-01T16:03:00Z","o":12,"h":15,"l":12,"c":15,"v":300,"vw":14}
+    ]"#;
+
+    let bars = from_json::<Vec<Bar>>(response).unwrap();
+    let series = vwap(&bars);
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0].0, bars[0].time);
+    assert_eq!(series[0].1, Num::from(11));
+    assert_eq!(series[1].0, bars[2].time);
+    // (11 * 100 + 14 * 300) / 400
+    assert_eq!(series[1].1, Num::new(53, 4));
+
+    assert_eq!(vwap(&[]), Vec::new());
+  }
+
+  /// Check that bar requests are directed to the configured data API
+  /// base URL.
+  #[test(tokio::test)]
+  async fn request_bars_from_data_base_url() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "bars": [
+          {"t": "2021-02-01T16:01:00Z", "o": 133.32, "h": 133.74,
+           "l": 133.31, "c": 133.5, "v": 9876, "vw": 133.4}
+        ],
+        "symbol": "AAPL",
+        "next_page_token": null
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let start = DateTime::from_str("2021-02-01T16:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-02-01T17:00:00Z").unwrap();
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::OneMinute);
+    let res = client.issue::<List>(&request).await.unwrap();
+    assert_eq!(res.bars.len(), 1);
+    assert_eq!(res.bars[0].open, Num::new(13332, 100));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(
+      requests[0].starts_with("GET /v2/stocks/AAPL/bars?"),
+      "{}",
+      requests[0]
+    );
+  }
+
   /// Check that we can decode a response containing no bars correctly.
   #[test(tokio::test)]
   async fn no_bars() {
@@ -478,6 +902,74 @@ mod tests {
     };
   }
 
+  /// Check that we can retrieve bars for multiple symbols at once,
+  /// with a failure for one symbol not affecting the others, rate
+  /// limited requests being retried, and duplicate symbols being
+  /// retrieved only once.
+  #[test(tokio::test)]
+  async fn list_multiple_symbols() {
+    let mut rate_limited = false;
+    let server = Server::serve(move |request| {
+      if request.starts_with("GET /v2/stocks/ABC123/bars?") {
+        let body = r#"{"code":42210000,"message":"invalid symbol: ABC123"}"#;
+        (StatusCode::BAD_REQUEST, body.to_string())
+      } else if request.starts_with("GET /v2/stocks/SPY/bars?") && !rate_limited {
+        rate_limited = true;
+        (StatusCode::TOO_MANY_REQUESTS, "{}".to_string())
+      } else {
+        let symbol = request
+          .strip_prefix("GET /v2/stocks/")
+          .and_then(|rest| rest.split_once('/'))
+          .unwrap()
+          .0;
+        let body = format!(
+          r#"{{
+            "bars": [
+              {{"t": "2018-12-03T05:00:00Z", "o": 184.46, "h": 184.94,
+                "l": 181.21, "c": 184.82, "v": 40802464, "vw": 183.02}}
+            ],
+            "symbol": "{symbol}",
+            "next_page_token": null
+          }}"#
+        );
+        (StatusCode::OK, body)
+      }
+    })
+    .await;
+    let config = RetryConfig {
+      max_attempts: 2,
+      base_delay: Duration::from_millis(1),
+      ..Default::default()
+    };
+    let client = Client::builder().retry(config).build(server.api_info());
+
+    let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
+    let end = DateTime::from_str("2018-12-07T21:47:00Z").unwrap();
+    let request = ListReqInit::default().init("", start, end, TimeFrame::OneDay);
+    let symbols = ["AAPL", "ABC123", "SPY", "AAPL"].map(String::from);
+
+    let results = list_multi(&client, &symbols, &request, 2).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results["AAPL"].as_ref().unwrap().len(), 1);
+    assert_eq!(results["SPY"].as_ref().unwrap().len(), 1);
+    match &results["ABC123"] {
+      Err(RequestError::Endpoint(ListError::InvalidInput(_))) => (),
+      result => panic!("received unexpected result: {result:?}"),
+    }
+
+    let requests = server.requests();
+    let count = |symbol: &str| {
+      let prefix = format!("GET /v2/stocks/{symbol}/bars?");
+      requests
+        .iter()
+        .filter(|request| request.starts_with(&prefix))
+        .count()
+    };
+    assert_eq!(count("AAPL"), 1);
+    assert_eq!(count("ABC123"), 1);
+    assert_eq!(count("SPY"), 2);
+  }
+
   /// Verify that we error out as expected when attempting to retrieve
   /// aggregate data bars for an invalid symbol.
   #[test(tokio::test)]
@@ -0,0 +1,265 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_urlencoded::to_string as to_query;
+
+use crate::data::v2::VERSION;
+use crate::endpoint::BaseUrl;
+use crate::Client;
+use crate::RequestError;
+use crate::Str;
+
+
+/// An enumeration of the different tapes (SIP networks) that
+/// condition codes are reported on.
+///
+/// The meaning of a condition code may differ between tapes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Tape {
+  /// Tape A, covering securities listed on the NYSE.
+  #[serde(rename = "A")]
+  A,
+  /// Tape B, covering securities listed on NYSE Arca, NYSE American,
+  /// and other regional exchanges.
+  #[serde(rename = "B")]
+  B,
+  /// Tape C, covering securities listed on Nasdaq.
+  #[serde(rename = "C")]
+  C,
+}
+
+impl Tape {
+  /// All tapes.
+  pub const ALL: [Tape; 3] = [Tape::A, Tape::B, Tape::C];
+}
+
+impl FromStr for Tape {
+  type Err = ParseTapeError;
+
+  /// Parse a tape as reported alongside market data (e.g., "C").
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "A" => Ok(Tape::A),
+      "B" => Ok(Tape::B),
+      "C" => Ok(Tape::C),
+      _ => Err(ParseTapeError(s.to_string())),
+    }
+  }
+}
+
+
+/// An error indicating that a string does not represent a known
+/// [`Tape`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseTapeError(String);
+
+impl Display for ParseTapeError {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    write!(fmt, "unknown tape '{}'; expected one of A, B, C", self.0)
+  }
+}
+
+impl StdError for ParseTapeError {}
+
+
+/// The type of tick that condition codes apply to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TickType {
+  /// Conditions of trades.
+  Trade,
+  /// Conditions of quotes.
+  Quote,
+}
+
+impl AsRef<str> for TickType {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      TickType::Trade => "trade",
+      TickType::Quote => "quote",
+    }
+  }
+}
+
+
+/// A GET request to be made to the
+/// /v2/stocks/meta/conditions/{ticktype} endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// The type of tick to retrieve condition codes for.
+  #[serde(skip)]
+  pub tick_type: TickType,
+  /// The tape to retrieve condition codes for.
+  #[serde(rename = "tape")]
+  pub tape: Tape,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl From<(TickType, Tape)> for ListReq {
+  #[inline]
+  fn from((tick_type, tape): (TickType, Tape)) -> Self {
+    Self {
+      tick_type,
+      tape,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/meta/conditions/{ticktype} endpoint.
+  ///
+  /// The result maps condition codes to their descriptions.
+  pub List(ListReq),
+  Ok => HashMap<String, String>, [
+    /// The condition codes were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// The tick type or tape was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn base_url() -> Option<Str> {
+    BaseUrl::Data.into_str()
+  }
+
+  fn path(input: &Self::Input) -> Str {
+    VERSION
+      .path(&format!("stocks/meta/conditions/{}", input.tick_type.as_ref()))
+      .into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+/// A tape aware lookup table of the descriptions of condition codes of
+/// a single tick type.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Conditions {
+  /// The condition code descriptions, keyed by tape.
+  tapes: HashMap<Tape, HashMap<String, String>>,
+}
+
+impl Conditions {
+  /// Retrieve the condition codes of the given tick type for all tapes.
+  pub async fn load(client: &Client, tick_type: TickType) -> Result<Self, RequestError<ListError>> {
+    let mut tapes = HashMap::with_capacity(Tape::ALL.len());
+    for tape in Tape::ALL {
+      let request = ListReq::from((tick_type, tape));
+      let conditions = client.issue::<List>(&request).await?;
+      let _prev = tapes.insert(tape, conditions);
+    }
+    Ok(Self { tapes })
+  }
+
+  /// Look up the description of the condition `code` as reported on
+  /// `tape`.
+  pub fn describe(&self, tape: Tape, code: &str) -> Option<&str> {
+    self
+      .tapes
+      .get(&tape)
+      .and_then(|conditions| conditions.get(code))
+      .map(String::as_str)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use http::StatusCode;
+  use http_endpoint::Endpoint;
+
+  use test_log::test;
+
+  use crate::mock::Server;
+
+
+  /// Check that we emit the expected path and query for a request.
+  #[test]
+  fn request_path_and_query() {
+    let request = ListReq::from((TickType::Quote, Tape::B));
+    assert_eq!(
+      <List as Endpoint>::path(&request),
+      "/v2/stocks/meta/conditions/quote"
+    );
+    assert_eq!(
+      <List as Endpoint>::query(&request).unwrap().unwrap(),
+      "tape=B"
+    );
+  }
+
+  /// Check that the same condition code resolves to different
+  /// descriptions depending on the tape.
+  #[test(tokio::test)]
+  async fn describe_conditions_by_tape() {
+    let server = Server::serve(|request| {
+      let body = if request.ends_with("tape=C") {
+        r#"{"4": "On Demand Intraday Auction", "I": "Odd Lot Trade"}"#
+      } else {
+        r#"{"4": "Derivatively Priced", "I": "Odd Lot Trade", "X": "Cross Trade"}"#
+      };
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let conditions = Conditions::load(&client, TickType::Trade).await.unwrap();
+    assert_eq!(
+      conditions.describe(Tape::A, "4"),
+      Some("Derivatively Priced")
+    );
+    assert_eq!(
+      conditions.describe(Tape::B, "4"),
+      Some("Derivatively Priced")
+    );
+    assert_eq!(
+      conditions.describe(Tape::C, "4"),
+      Some("On Demand Intraday Auction")
+    );
+    assert_eq!(conditions.describe(Tape::A, "I"), Some("Odd Lot Trade"));
+    assert_eq!(conditions.describe(Tape::C, "I"), Some("Odd Lot Trade"));
+    assert_eq!(conditions.describe(Tape::B, "X"), Some("Cross Trade"));
+    assert_eq!(conditions.describe(Tape::C, "X"), None);
+
+    let requests = server.requests();
+    assert_eq!(
+      requests,
+      [
+        "GET /v2/stocks/meta/conditions/trade?tape=A",
+        "GET /v2/stocks/meta/conditions/trade?tape=B",
+        "GET /v2/stocks/meta/conditions/trade?tape=C",
+      ]
+    );
+  }
+
+  /// Check that we can parse tapes as reported alongside market data.
+  #[test]
+  fn parse_tape() {
+    assert_eq!(Tape::from_str("A"), Ok(Tape::A));
+    assert_eq!(Tape::from_str("C"), Ok(Tape::C));
+
+    let err = Tape::from_str("D").unwrap_err();
+    assert_eq!(err, ParseTapeError("D".to_string()));
+    assert_eq!(err.to_string(), "unknown tape 'D'; expected one of A, B, C");
+  }
+}
@@ -5,6 +5,13 @@ use serde::Serialize;
 
 
 /// An enumeration of the different supported data feeds.
+///
+/// The feed is selected for each request individually (e.g., by means
+/// of [`bars::ListReq::feed`][crate::data::v2::bars::ListReq::feed]).
+/// A [`Client`][crate::Client] has no notion of a feed, so requests for
+/// different feeds can be freely mixed on the same client. When no
+/// feed is specified, Alpaca picks one based on the account's market
+/// data subscription.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
 #[non_exhaustive]
 pub enum Feed {
@@ -13,9 +13,15 @@ use serde::Serialize;
 use serde_json::from_slice as from_json;
 use serde_urlencoded::to_string as to_query;
 
+use crate::data::v2::chunk_symbols;
+use crate::data::v2::conditions::Tape;
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::VERSION;
+use crate::endpoint::BaseUrl;
 use crate::util::string_slice_to_str;
+use crate::util::vec_from_str;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -82,6 +88,15 @@ pub struct Quote {
   /// The bid size.
   #[serde(rename = "bs")]
   pub bid_size: u64,
+  /// The condition codes of the quote.
+  ///
+  /// Use [`Conditions`][crate::data::v2::conditions::Conditions] to
+  /// look up their descriptions, which depend on the tape.
+  #[serde(rename = "c", default, deserialize_with = "vec_from_str")]
+  pub conditions: Vec<String>,
+  /// The tape on which the quote was reported, if reported.
+  #[serde(rename = "z", default)]
+  pub tape: Option<Tape>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -104,11 +119,11 @@ EndpointNoParse! {
   ]
 
   fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+    BaseUrl::Data.into_str()
   }
 
   fn path(_input: &Self::Input) -> Str {
-    "/v2/stocks/quotes/latest".into()
+    VERSION.path("stocks/quotes/latest").into()
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -138,28 +153,59 @@ EndpointNoParse! {
           .into_iter()
           .collect()
       })
-      .map_err(Self::ConversionError::from)
+      .map_err(|err| Self::ConversionError::invalid_response(err, body))
   }
 
   fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
-    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+    Self::ApiError::parse(body)
   }
 }
 
 
+/// Retrieve the last quotes for the symbols of `request`, splitting
+/// the symbols into multiple requests as necessary to keep the encoded
+/// list of symbols in each of them within `max_symbols_len` bytes (see
+/// [`DEFAULT_MAX_SYMBOLS_LEN`][crate::data::v2::DEFAULT_MAX_SYMBOLS_LEN]).
+///
+/// The requests are issued one after the other and their results are
+/// merged, with quotes being ordered by symbol, just as they would be
+/// for a single request.
+pub async fn get_batched(
+  client: &Client,
+  request: &GetReq,
+  max_symbols_len: usize,
+) -> Result<Vec<(String, Quote)>, RequestError<GetError>> {
+  let mut quotes = BTreeMap::new();
+
+  for symbols in chunk_symbols(&request.symbols, max_symbols_len) {
+    let request = GetReq {
+      symbols: symbols.to_vec(),
+      ..request.clone()
+    };
+    quotes.extend(client.issue::<Get>(&request).await?);
+  }
+  Ok(quotes.into_iter().collect())
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  use std::collections::HashMap;
+
   use chrono::Duration;
 
+  use http::StatusCode;
   use http_endpoint::Endpoint as _;
 
+  use serde_urlencoded::from_str as from_query;
+
   use test_log::test;
 
   use crate::api_info::ApiInfo;
-  use crate::Client;
-  use crate::RequestError;
+  use crate::data::v2::DEFAULT_MAX_SYMBOLS_LEN;
+  use crate::mock::Server;
 
 
   /// Check that we can parse the reference quotes from the
@@ -206,6 +252,8 @@ mod tests {
     assert_eq!(aapl.ask_size, 1);
     assert_eq!(aapl.bid_price, Num::new(16803, 100));
     assert_eq!(aapl.bid_size, 1);
+    assert_eq!(aapl.conditions, ["R"]);
+    assert_eq!(aapl.tape, Some(Tape::C));
 
     assert_eq!(quotes[1].0, "TSLA");
     let tsla = &quotes[1].1;
@@ -219,6 +267,45 @@ mod tests {
     assert_eq!(tsla.bid_size, 5);
   }
 
+  /// Check that a request for many symbols is split into multiple
+  /// requests and that the results are merged.
+  #[test(tokio::test)]
+  async fn request_last_quotes_batched() {
+    let server = Server::serve(|request| {
+      let query = request.split_once('?').unwrap().1;
+      let params = from_query::<HashMap<String, String>>(query).unwrap();
+      let quotes = params["symbols"]
+        .split(',')
+        .map(|symbol| {
+          format!(r#""{symbol}":{{"t":"2022-04-12T17:26:45Z","ap":2,"as":3,"bp":1,"bs":5}}"#)
+        })
+        .collect::<Vec<_>>();
+      let body = format!(r#"{{"quotes":{{{}}}}}"#, quotes.join(","));
+      (StatusCode::OK, body)
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let symbols = (0..10).map(|i| format!("SYM{i}")).collect::<Vec<_>>();
+    let request = GetReqInit::default().init(symbols.clone());
+    // Each symbol is four bytes long, so at most five of them fit into
+    // 32 bytes.
+    let quotes = get_batched(&client, &request, 32).await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+    let received = quotes
+      .iter()
+      .map(|(symbol, _quote)| symbol.clone())
+      .collect::<Vec<_>>();
+    assert_eq!(received, symbols);
+    assert!(quotes.iter().all(|(_symbol, quote)| quote.bid_size == 5));
+
+    let quotes = get_batched(&client, &request, DEFAULT_MAX_SYMBOLS_LEN)
+      .await
+      .unwrap();
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(quotes.len(), 10);
+  }
+
   /// Verify that we can retrieve the last quote for an asset.
   #[test(tokio::test)]
   async fn request_last_quotes() {
@@ -2,17 +2,126 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 mod feed;
+#[cfg(feature = "streaming")]
 mod unfold;
 
 /// Definitions for retrieval of market data bars.
 pub mod bars;
+/// Definitions for looking up the meaning of trade and quote
+/// condition codes.
+pub mod conditions;
 /// Functionality for retrieval of most recent quotes.
 pub mod last_quotes;
 /// Functionality for retrieving historic quotes.
 pub mod quotes;
+/// Definitions for snapshots of the most recent market data.
+pub mod snapshot;
 /// Definitions for real-time streaming of market data.
+#[cfg(feature = "streaming")]
 pub mod stream;
 /// Definitions for retrieval of market data trades.
 pub mod trades;
 
 pub use feed::Feed;
+
+use url::form_urlencoded::byte_serialize;
+
+use crate::data::Version;
+
+/// The version of the Data API that all endpoints in this module use.
+pub(crate) const VERSION: Version = Version::V2;
+
+/// The maximum `limit` accepted by the historical data endpoints in
+/// this module.
+pub(crate) const MAX_LIMIT: usize = 10000;
+
+/// The default maximum length of the encoded list of symbols in a
+/// single request, as used by [`last_quotes::get_batched`] and
+/// [`snapshot::list_batched`].
+///
+/// The value leaves plenty of headroom below the URL length limits
+/// commonly enforced by servers and proxies.
+pub const DEFAULT_MAX_SYMBOLS_LEN: usize = 4096;
+
+
+/// Split `symbols` into consecutive chunks whose percent encoded,
+/// comma-separated representation does not exceed `max_len` bytes.
+///
+/// Each chunk contains at least one symbol, even if that symbol on its
+/// own exceeds `max_len`.
+pub(crate) fn chunk_symbols(symbols: &[String], max_len: usize) -> Vec<&[String]> {
+  let mut chunks = Vec::new();
+  let mut start = 0;
+  let mut len = 0;
+
+  for (i, symbol) in symbols.iter().enumerate() {
+    let encoded_len = byte_serialize(symbol.as_bytes())
+      .map(str::len)
+      .sum::<usize>();
+    // Symbols are separated by a comma, which is percent encoded in the
+    // query string and so occupies three bytes.
+    let symbol_len = if i == start {
+      encoded_len
+    } else {
+      encoded_len + 3
+    };
+
+    if i > start && len + symbol_len > max_len {
+      chunks.push(&symbols[start..i]);
+      start = i;
+      len = encoded_len;
+    } else {
+      len += symbol_len;
+    }
+  }
+
+  if start < symbols.len() {
+    chunks.push(&symbols[start..]);
+  }
+  chunks
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+
+  /// Check that symbols are split into chunks of bounded length.
+  #[test]
+  fn symbol_chunking() {
+    let symbols = ["A", "BB", "CCC", "DDDD"].map(String::from);
+
+    // Everything fits into a single chunk: "A%2CBB%2CCCC%2CDDDD".
+    assert_eq!(chunk_symbols(&symbols, 19), vec![&symbols[..]]);
+    assert_eq!(
+      chunk_symbols(&symbols, 18),
+      vec![&symbols[..3], &symbols[3..]]
+    );
+    assert_eq!(
+      chunk_symbols(&symbols, 6),
+      vec![&symbols[..2], &symbols[2..3], &symbols[3..]]
+    );
+    // Symbols longer than the limit still end up in a chunk of their
+    // own.
+    assert_eq!(
+      chunk_symbols(&symbols, 1),
+      vec![&symbols[..1], &symbols[1..2], &symbols[2..3], &symbols[3..]]
+    );
+    assert!(chunk_symbols(&[], 1).is_empty());
+  }
+
+  /// Check that we account for percent encoding of symbols when
+  /// chunking them.
+  #[test]
+  fn symbol_chunking_encoded() {
+    let symbols = ["BRK/B", "A"].map(String::from);
+
+    // The encoded symbols are "BRK%2FB%2CA".
+    assert_eq!(chunk_symbols(&symbols, 11), vec![&symbols[..]]);
+    assert_eq!(
+      chunk_symbols(&symbols, 10),
+      vec![&symbols[..1], &symbols[1..]]
+    );
+  }
+}
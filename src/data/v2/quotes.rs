@@ -6,10 +6,15 @@ use chrono::Utc;
 
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::from_slice as from_json;
 use serde_urlencoded::to_string as to_query;
 
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::MAX_LIMIT;
+use crate::data::v2::VERSION;
+use crate::endpoint::BaseUrl;
+use crate::util::clamp_limit;
+use crate::util::option_from_non_empty_str;
 use crate::util::vec_from_str;
 use crate::Str;
 
@@ -29,8 +34,19 @@ pub struct Quotes {
   pub symbol: String,
   /// The token to provide to a request to get the next page of quotes
   /// for this request.
-  #[serde(rename = "next_page_token")]
+  ///
+  /// `None` (reported as either `null` or an empty string) indicates
+  /// that no more data is available.
+  #[serde(
+    rename = "next_page_token",
+    default,
+    deserialize_with = "option_from_non_empty_str"
+  )]
   pub next_page_token: Option<String>,
+  /// The currency in which prices of the quotes are reported, if it
+  /// was reported by the API.
+  #[serde(rename = "currency", default)]
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -47,6 +63,8 @@ pub struct ListReqInit {
   pub feed: Option<Feed>,
   /// See `ListReq::page_token`.
   pub page_token: Option<String>,
+  /// See `ListReq::currency`.
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
@@ -66,6 +84,7 @@ impl ListReqInit {
       limit: self.limit,
       feed: self.feed,
       page_token: self.page_token,
+      currency: self.currency,
       _non_exhaustive: (),
     }
   }
@@ -88,8 +107,9 @@ pub struct ListReq {
   #[serde(rename = "end")]
   pub end: DateTime<Utc>,
   /// Number of quotes to return. Must be in range 1-10000, defaults to
-  /// 1000.
-  #[serde(rename = "limit")]
+  /// 1000. Larger values are clamped to the maximum when issuing the
+  /// request.
+  #[serde(rename = "limit", serialize_with = "clamp_limit::<MAX_LIMIT, _>")]
   pub limit: Option<usize>,
   /// The data feed to use.
   #[serde(rename = "feed")]
@@ -97,6 +117,10 @@ pub struct ListReq {
   /// Pagination token to continue from.
   #[serde(rename = "page_token")]
   pub page_token: Option<String>,
+  /// The currency in which prices are to be reported, as an ISO 4217
+  /// code (e.g., "JPY"). Defaults to USD.
+  #[serde(rename = "currency", skip_serializing_if = "Option::is_none")]
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -118,12 +142,12 @@ Endpoint! {
   ]
 
   fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+    BaseUrl::Data.into_str()
   }
 
   #[inline]
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/stocks/{}/quotes", input.symbol).into()
+    VERSION.path(&format!("stocks/{}/quotes", input.symbol)).into()
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -132,21 +156,202 @@ Endpoint! {
 }
 
 
+/// A GET request to be issued to the /v2/stocks/{symbol}/quotes/latest
+/// endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct GetLatestReq {
+  /// The symbol for which to retrieve the latest quote.
+  #[serde(skip)]
+  pub symbol: String,
+  /// The data feed to use.
+  #[serde(rename = "feed", skip_serializing_if = "Option::is_none")]
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`GetLatestReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct GetLatestReqInit {
+  /// See `GetLatestReq::feed`.
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl GetLatestReqInit {
+  /// Create a [`GetLatestReq`] from a `GetLatestReqInit`.
+  #[inline]
+  pub fn init<S>(self, symbol: S) -> GetLatestReq
+  where
+    S: Into<String>,
+  {
+    GetLatestReq {
+      symbol: symbol.into(),
+      feed: self.feed,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/quotes/latest endpoint.
+  pub GetLatest(GetLatestReq),
+  Ok => Quote, [
+    /// The latest quote was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetLatestError, [
+    /// The provided symbol was invalid or not found or the data feed is
+    /// not supported.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn base_url() -> Option<Str> {
+    BaseUrl::Data.into_str()
+  }
+
+  fn path(input: &Self::Input) -> Str {
+    VERSION.path(&format!("stocks/{}/quotes/latest", input.symbol)).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    let query = to_query(input)?;
+    if query.is_empty() {
+      Ok(None)
+    } else {
+      Ok(Some(query.into()))
+    }
+  }
+
+  fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    /// A helper object for parsing the response to a `GetLatest`
+    /// request.
+    #[derive(Deserialize)]
+    struct Response {
+      /// The latest quote.
+      quote: Quote,
+    }
+
+    from_json::<Response>(body)
+      .map(|response| response.quote)
+      .map_err(|err| Self::ConversionError::invalid_response(err, body))
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    Self::ApiError::parse(body)
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use std::str::FromStr as _;
 
+  use http::StatusCode;
+
   use num_decimal::Num;
 
   use test_log::test;
 
   use crate::api_info::ApiInfo;
+  use crate::data::v2::conditions::Tape;
+  use crate::mock::Server;
   use crate::Client;
   use crate::RequestError;
 
 
+  /// Check that we request quotes in the desired currency and report the
+  /// currency the API used.
+  #[test(tokio::test)]
+  async fn request_currency() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "quotes": [{
+          "t": "2021-11-05T13:35:08.946977536Z",
+          "ax": "C",
+          "ap": 187.7,
+          "as": 1,
+          "bx": "N",
+          "bp": 187.67,
+          "bs": 1,
+          "c": ["R"],
+          "z": "C"
+        }],
+        "symbol": "SAP",
+        "next_page_token": null,
+        "currency": "EUR"
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let start = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-11-06T00:00:00Z").unwrap();
+    let request = ListReqInit {
+      currency: Some("EUR".to_string()),
+      ..Default::default()
+    }
+    .init("SAP", start, end);
+    let quotes = client.issue::<List>(&request).await.unwrap();
+    assert_eq!(quotes.currency.as_deref(), Some("EUR"));
+    assert_eq!(quotes.quotes.len(), 1);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].contains("currency=EUR"), "{}", requests[0]);
+  }
+
+  /// Check that we can retrieve the latest quote for a symbol.
+  #[test(tokio::test)]
+  async fn request_latest_quote() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "symbol": "AAPL",
+        "quote": {
+          "t": "2021-02-06T13:35:08.946977536Z",
+          "ax": "C",
+          "ap": 387.7,
+          "as": 1,
+          "bx": "N",
+          "bp": 387.67,
+          "bs": 1,
+          "c": ["R"],
+          "z": "C"
+        }
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = GetLatestReqInit::default().init("AAPL");
+    let quote = client.issue::<GetLatest>(&request).await.unwrap();
+    assert_eq!(
+      quote.time,
+      DateTime::<Utc>::from_str("2021-02-06T13:35:08.946977536Z").unwrap()
+    );
+    assert_eq!(quote.ask_price, Num::new(3877, 10));
+    assert_eq!(quote.ask_size, 1);
+    assert_eq!(quote.bid_price, Num::new(38767, 100));
+    assert_eq!(quote.bid_size, 1);
+    assert_eq!(quote.conditions, ["R"]);
+    assert_eq!(quote.tape, Some(Tape::C));
+
+    let requests = server.requests();
+    assert_eq!(requests, ["GET /v2/stocks/AAPL/quotes/latest"]);
+  }
+
   /// Check that we can retrieve quotes for a specific time frame.
   #[test(tokio::test)]
   async fn request_quotes() {
@@ -0,0 +1,594 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::from_slice as from_json;
+use serde_urlencoded::to_string as to_query;
+
+use thiserror::Error;
+
+use crate::api::v2::watchlist;
+use crate::data::v2::bars::Bar;
+use crate::data::v2::chunk_symbols;
+use crate::data::v2::quotes::Quote;
+use crate::data::v2::trades::Trade;
+use crate::data::v2::Feed;
+use crate::data::v2::DEFAULT_MAX_SYMBOLS_LEN;
+use crate::data::v2::VERSION;
+use crate::endpoint::BaseUrl;
+use crate::util::string_slice_to_str;
+use crate::Client;
+use crate::RequestError;
+use crate::Str;
+
+
+/// A snapshot of the most recent market data of a symbol.
+///
+/// Any of the members may be `None`, for example for thinly traded
+/// symbols or before the first trade of the day.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Snapshot {
+  /// The latest trade.
+  #[serde(rename = "latestTrade", default)]
+  pub latest_trade: Option<Trade>,
+  /// The latest quote.
+  #[serde(rename = "latestQuote", default)]
+  pub latest_quote: Option<Quote>,
+  /// The most recent minute bar.
+  #[serde(rename = "minuteBar", default)]
+  pub minute_bar: Option<Bar>,
+  /// The bar of the current trading day.
+  #[serde(rename = "dailyBar", default)]
+  pub daily_bar: Option<Bar>,
+  /// The bar of the previous trading day.
+  #[serde(rename = "prevDailyBar", default)]
+  pub prev_daily_bar: Option<Bar>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A GET request to be made to the /v2/stocks/{symbol}/snapshot
+/// endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct GetReq {
+  /// The symbol to retrieve the snapshot for.
+  #[serde(skip)]
+  pub symbol: String,
+  /// The data feed to use.
+  #[serde(rename = "feed", skip_serializing_if = "Option::is_none")]
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`GetReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct GetReqInit {
+  /// See `GetReq::feed`.
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl GetReqInit {
+  /// Create a [`GetReq`] from a `GetReqInit`.
+  #[inline]
+  pub fn init<S>(self, symbol: S) -> GetReq
+  where
+    S: Into<String>,
+  {
+    GetReq {
+      symbol: symbol.into(),
+      feed: self.feed,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/snapshot endpoint.
+  pub Get(GetReq),
+  Ok => Snapshot, [
+    /// The snapshot was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetError, [
+    /// The provided symbol was invalid or not found or the data feed is
+    /// not supported.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+    /// No snapshot is available for the provided symbol.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn base_url() -> Option<Str> {
+    BaseUrl::Data.into_str()
+  }
+
+  fn path(input: &Self::Input) -> Str {
+    VERSION.path(&format!("stocks/{}/snapshot", input.symbol)).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    let query = to_query(input)?;
+    if query.is_empty() {
+      Ok(None)
+    } else {
+      Ok(Some(query.into()))
+    }
+  }
+}
+
+
+/// A GET request to be made to the /v2/stocks/snapshots endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// The symbols to retrieve snapshots for.
+  #[serde(rename = "symbols", serialize_with = "string_slice_to_str")]
+  pub symbols: Vec<String>,
+  /// The data feed to use.
+  #[serde(rename = "feed", skip_serializing_if = "Option::is_none")]
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`ListReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct ListReqInit {
+  /// See `ListReq::feed`.
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl ListReqInit {
+  /// Create a [`ListReq`] from a `ListReqInit`.
+  #[inline]
+  pub fn init<I, S>(self, symbols: I) -> ListReq
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    ListReq {
+      symbols: symbols.into_iter().map(S::into).collect(),
+      feed: self.feed,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a GET request to the /v2/stocks/snapshots
+  /// endpoint.
+  ///
+  /// The result maps symbols to their snapshots. Symbols for which no
+  /// snapshot is available are not included.
+  pub List(ListReq),
+  Ok => HashMap<String, Snapshot>, [
+    /// The snapshots were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// One of the provided symbols was invalid or the data feed is not
+    /// supported.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn base_url() -> Option<Str> {
+    BaseUrl::Data.into_str()
+  }
+
+  fn path(_input: &Self::Input) -> Str {
+    VERSION.path("stocks/snapshots").into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+
+  fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    // Symbols without data are reported as `null`; we just skip them.
+    from_json::<HashMap<String, Option<Snapshot>>>(body)
+      .map(|snapshots| {
+        snapshots
+          .into_iter()
+          .filter_map(|(symbol, snapshot)| snapshot.map(|snapshot| (symbol, snapshot)))
+          .collect()
+      })
+      .map_err(|err| Self::ConversionError::invalid_response(err, body))
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    Self::ApiError::parse(body)
+  }
+}
+
+
+/// Retrieve the snapshots for the symbols of `request`, splitting the
+/// symbols into multiple [`List`] requests as necessary to keep the
+/// encoded list of symbols in each of them within `max_symbols_len`
+/// bytes (see [`DEFAULT_MAX_SYMBOLS_LEN`]).
+///
+/// The requests are issued one after the other and their results are
+/// merged.
+pub async fn list_batched(
+  client: &Client,
+  request: &ListReq,
+  max_symbols_len: usize,
+) -> Result<HashMap<String, Snapshot>, RequestError<ListError>> {
+  let mut snapshots = HashMap::with_capacity(request.symbols.len());
+
+  for symbols in chunk_symbols(&request.symbols, max_symbols_len) {
+    let request = ListReq {
+      symbols: symbols.to_vec(),
+      ..request.clone()
+    };
+    snapshots.extend(client.issue::<List>(&request).await?);
+  }
+  Ok(snapshots)
+}
+
+
+/// An error as reported by [`watchlist_snapshots`].
+#[derive(Debug, Error)]
+pub enum WatchlistSnapshotsError {
+  /// Failed to retrieve the watchlist.
+  #[error("failed to retrieve watchlist")]
+  GetWatchlist(#[source] RequestError<watchlist::GetError>),
+  /// Failed to retrieve the snapshots of the watchlist's symbols.
+  #[error("failed to retrieve snapshots")]
+  ListSnapshots(#[source] RequestError<ListError>),
+}
+
+
+/// Retrieve the snapshots of all symbols on the watchlist with the
+/// given ID.
+///
+/// The snapshots are retrieved using [`list_batched`], with requests
+/// configured by `init`. Symbols for which no snapshot is available
+/// are not included in the result.
+pub async fn watchlist_snapshots(
+  client: &Client,
+  id: &watchlist::Id,
+  init: ListReqInit,
+) -> Result<HashMap<String, Snapshot>, WatchlistSnapshotsError> {
+  let watchlist = client
+    .issue::<watchlist::Get>(id)
+    .await
+    .map_err(WatchlistSnapshotsError::GetWatchlist)?;
+
+  if watchlist.assets.is_empty() {
+    return Ok(HashMap::new())
+  }
+
+  let request = init.init(watchlist.assets.into_iter().map(|asset| asset.symbol));
+  list_batched(client, &request, DEFAULT_MAX_SYMBOLS_LEN)
+    .await
+    .map_err(WatchlistSnapshotsError::ListSnapshots)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::str::FromStr as _;
+
+  use chrono::DateTime;
+  use chrono::Utc;
+
+  use http::StatusCode;
+
+  use num_decimal::Num;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use uuid::Uuid;
+
+  use crate::mock::Server;
+  use crate::Client;
+
+
+  /// Check that we can parse a snapshot with all members present.
+  #[test]
+  fn parse_snapshot() {
+    let response = r#"{
+  "latestTrade": {
+    "t": "2024-03-04T20:59:59.848Z",
+    "x": "V",
+    "p": 175.1,
+    "s": 100,
+    "c": ["@"],
+    "i": 12345,
+    "z": "C"
+  },
+  "latestQuote": {
+    "t": "2024-03-04T20:59:59.9Z",
+    "ax": "V",
+    "ap": 175.12,
+    "as": 2,
+    "bx": "V",
+    "bp": 175.08,
+    "bs": 1,
+    "c": ["R"],
+    "z": "C"
+  },
+  "minuteBar": {
+    "t": "2024-03-04T20:59:00Z",
+    "o": 175.0,
+    "h": 175.2,
+    "l": 174.9,
+    "c": 175.1,
+    "v": 1200,
+    "n": 30,
+    "vw": 175.05
+  },
+  "dailyBar": {
+    "t": "2024-03-04T05:00:00Z",
+    "o": 176.0,
+    "h": 177.5,
+    "l": 174.5,
+    "c": 175.1,
+    "v": 100000,
+    "n": 2000,
+    "vw": 175.9
+  },
+  "prevDailyBar": {
+    "t": "2024-03-01T05:00:00Z",
+    "o": 178.0,
+    "h": 179.0,
+    "l": 177.0,
+    "c": 178.5,
+    "v": 90000,
+    "n": 1800,
+    "vw": 178.2
+  }
+}"#;
+
+    let snapshot = from_json::<Snapshot>(response).unwrap();
+    let trade = snapshot.latest_trade.unwrap();
+    assert_eq!(
+      trade.timestamp,
+      DateTime::<Utc>::from_str("2024-03-04T20:59:59.848Z").unwrap()
+    );
+    assert_eq!(trade.price, Num::new(1751, 10));
+    assert_eq!(trade.size, 100);
+
+    let quote = snapshot.latest_quote.unwrap();
+    assert_eq!(quote.ask_price, Num::new(17512, 100));
+    assert_eq!(quote.bid_size, 1);
+
+    assert_eq!(snapshot.minute_bar.unwrap().volume, 1200);
+    assert_eq!(snapshot.daily_bar.unwrap().open, Num::from(176));
+    assert_eq!(snapshot.prev_daily_bar.unwrap().close, Num::new(1785, 10));
+  }
+
+  /// Check that we can parse a snapshot with `null` and missing
+  /// members.
+  #[test]
+  fn parse_snapshot_with_null_members() {
+    let response = r#"{
+  "latestTrade": null,
+  "latestQuote": {
+    "t": "2024-03-05T09:00:00Z",
+    "ap": 1.5,
+    "as": 1,
+    "bp": 1.25,
+    "bs": 3
+  },
+  "minuteBar": null,
+  "prevDailyBar": {
+    "t": "2024-03-04T05:00:00Z",
+    "o": 1.3,
+    "h": 1.5,
+    "l": 1.2,
+    "c": 1.4,
+    "v": 1000,
+    "vw": 1.35
+  }
+}"#;
+
+    let snapshot = from_json::<Snapshot>(response).unwrap();
+    assert_eq!(snapshot.latest_trade, None);
+    assert_eq!(snapshot.latest_quote.unwrap().bid_price, Num::new(125, 100));
+    assert_eq!(snapshot.minute_bar, None);
+    assert_eq!(snapshot.daily_bar, None);
+    assert_eq!(snapshot.prev_daily_bar.unwrap().close, Num::new(14, 10));
+
+    let snapshot = from_json::<Snapshot>("{}").unwrap();
+    assert_eq!(snapshot.latest_trade, None);
+    assert_eq!(snapshot.latest_quote, None);
+    assert_eq!(snapshot.prev_daily_bar, None);
+  }
+
+  /// Check that we can retrieve the snapshot of a single symbol.
+  #[test(tokio::test)]
+  async fn request_snapshot() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "symbol": "AAPL",
+        "latestTrade": {"t": "2024-03-04T20:59:59.848Z", "p": 175.1, "s": 100},
+        "latestQuote": null,
+        "minuteBar": null,
+        "dailyBar": null,
+        "prevDailyBar": null
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = GetReqInit {
+      feed: Some(Feed::SIP),
+      ..Default::default()
+    }
+    .init("AAPL");
+    let snapshot = client.issue::<Get>(&request).await.unwrap();
+    assert_eq!(snapshot.latest_trade.unwrap().price, Num::new(1751, 10));
+    assert_eq!(snapshot.latest_quote, None);
+
+    let request = GetReqInit::default().init("AAPL");
+    let _snapshot = client.issue::<Get>(&request).await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+      requests,
+      [
+        "GET /v2/stocks/AAPL/snapshot?feed=sip",
+        "GET /v2/stocks/AAPL/snapshot",
+      ]
+    );
+  }
+
+  /// Check that we can retrieve the snapshots of multiple symbols at
+  /// once.
+  #[test(tokio::test)]
+  async fn request_snapshots() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "AAPL": {
+          "latestTrade": {"t": "2024-03-04T20:59:59.848Z", "p": 175.1, "s": 100}
+        },
+        "MSFT": {
+          "latestTrade": {"t": "2024-03-04T20:59:59.912Z", "p": 414.92, "s": 5}
+        },
+        "XYZ": null
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = ListReqInit::default().init(["AAPL", "MSFT", "XYZ"]);
+    let snapshots = client.issue::<List>(&request).await.unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(
+      snapshots["AAPL"].latest_trade.as_ref().unwrap().price,
+      Num::new(1751, 10)
+    );
+    assert_eq!(snapshots["MSFT"].latest_trade.as_ref().unwrap().size, 5);
+
+    let requests = server.requests();
+    assert_eq!(
+      requests,
+      ["GET /v2/stocks/snapshots?symbols=AAPL%2CMSFT%2CXYZ"]
+    );
+  }
+
+  /// Check that a snapshot request for many symbols is split into
+  /// multiple requests and that the results are merged.
+  #[test(tokio::test)]
+  async fn request_snapshots_batched() {
+    let server = Server::serve(|request| {
+      let query = request.split_once('?').unwrap().1;
+      let symbols = query
+        .strip_prefix("symbols=")
+        .unwrap()
+        .split("%2C")
+        .map(|symbol| {
+          format!(r#""{symbol}":{{"latestTrade":{{"t":"2024-03-04T20:59:59Z","p":1,"s":5}}}}"#)
+        })
+        .collect::<Vec<_>>();
+      (StatusCode::OK, format!("{{{}}}", symbols.join(",")))
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let symbols = (0..10).map(|i| format!("SYM{i}")).collect::<Vec<_>>();
+    let request = ListReqInit::default().init(symbols.clone());
+    // Each symbol is four bytes long, so at most five of them fit into
+    // 32 bytes.
+    let snapshots = list_batched(&client, &request, 32).await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+    assert_eq!(snapshots.len(), 10);
+    assert!(
+      symbols
+        .iter()
+        .all(|symbol| snapshots[symbol].latest_trade.as_ref().unwrap().size == 5)
+    );
+  }
+
+  /// Check that we can retrieve the snapshots of all symbols on a
+  /// watchlist.
+  #[test(tokio::test)]
+  async fn request_watchlist_snapshots() {
+    const ID: &str = "fb306e55-16d3-4118-8c3d-c1615fcd4c03";
+
+    let server = Server::serve(|request| {
+      let body = if request.starts_with("GET /v2/watchlists/") {
+        format!(
+          r#"{{
+          "id": "{ID}",
+          "account_id": "bbd63e1e-5dd6-4a82-a6e2-a3c53fc0b4e5",
+          "created_at": "2026-01-05T12:00:00Z",
+          "updated_at": "2026-01-06T12:00:00Z",
+          "name": "tech",
+          "assets": [
+            {{"id":"b0b6dd9d-8b9b-48a9-ba46-b9d54906e415","class":"us_equity",
+             "exchange":"NASDAQ","symbol":"AAPL","status":"active","tradable":true,
+             "marginable":true,"shortable":true,"easy_to_borrow":true,"fractionable":true}},
+            {{"id":"b6d1aa75-5c9c-4353-a305-9e2caa1925ab","class":"us_equity",
+             "exchange":"NASDAQ","symbol":"MSFT","status":"active","tradable":true,
+             "marginable":true,"shortable":true,"easy_to_borrow":true,"fractionable":true}}
+          ]
+        }}"#
+        )
+      } else {
+        r#"{
+          "AAPL": {
+            "latestTrade": {"t": "2024-03-04T20:59:59.848Z", "p": 175.1, "s": 100}
+          },
+          "MSFT": {
+            "latestTrade": {"t": "2024-03-04T20:59:59.912Z", "p": 414.92, "s": 5}
+          }
+        }"#
+          .to_string()
+      };
+      (StatusCode::OK, body)
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let id = watchlist::Id(Uuid::parse_str(ID).unwrap());
+    let init = ListReqInit {
+      feed: Some(Feed::IEX),
+      ..Default::default()
+    };
+    let snapshots = watchlist_snapshots(&client, &id, init).await.unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(
+      snapshots["AAPL"].latest_trade.as_ref().unwrap().price,
+      Num::new(1751, 10)
+    );
+    assert_eq!(snapshots["MSFT"].latest_trade.as_ref().unwrap().size, 5);
+
+    assert_eq!(
+      server.requests(),
+      [
+        format!("GET /v2/watchlists/{}", id.as_simple()),
+        "GET /v2/stocks/snapshots?symbols=AAPL%2CMSFT&feed=iex".to_string(),
+      ]
+    );
+  }
+}
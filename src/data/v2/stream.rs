@@ -7,6 +7,8 @@ use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use async_trait::async_trait;
 
@@ -52,7 +54,10 @@ use websocket_util::wrap;
 use websocket_util::wrap::Wrapper;
 
 use super::unfold::Unfold;
+use super::VERSION;
 
+use crate::backpressure::Backpressure;
+use crate::subscribable::Resubscribable;
 use crate::subscribable::Subscribable;
 use crate::websocket::connect;
 use crate::websocket::MessageResult;
@@ -321,6 +326,68 @@ pub struct Trade {
 }
 
 
+/// A trading status update (e.g., a trading halt or resumption) for an
+/// equity.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TradingStatus {
+  /// The symbol the status update applies to.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The status code (e.g., "H" for a trading halt or "T" for
+  /// trading).
+  #[serde(rename = "sc")]
+  pub status_code: String,
+  /// A message describing the status.
+  #[serde(rename = "sm")]
+  pub status_message: String,
+  /// The reason code.
+  #[serde(rename = "rc")]
+  pub reason_code: String,
+  /// A message describing the reason for the status.
+  #[serde(rename = "rm")]
+  pub reason_message: String,
+  /// The status update's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  /// The tape.
+  #[serde(rename = "z")]
+  pub tape: String,
+}
+
+impl TradingStatus {
+  /// Check whether the status indicates that trading in the symbol is
+  /// halted or paused.
+  #[inline]
+  pub fn is_halted(&self) -> bool {
+    matches!(self.status_code.as_str(), "H" | "P")
+  }
+}
+
+
+/// Limit up/limit down (LULD) price bands for an equity.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Luld {
+  /// The symbol the bands apply to.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The upper limit price band.
+  #[serde(rename = "u")]
+  pub limit_up_price: Num,
+  /// The lower limit price band.
+  #[serde(rename = "d")]
+  pub limit_down_price: Num,
+  /// The indicator.
+  #[serde(rename = "i")]
+  pub indicator: String,
+  /// The bands' time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  /// The tape.
+  #[serde(rename = "z")]
+  pub tape: String,
+}
+
+
 /// An error as reported by the Alpaca Stream API.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, ThisError)]
 #[error("{message} ({code})")]
@@ -350,6 +417,14 @@ pub enum DataMessage<B = Bar, Q = Quote, T = Trade> {
   /// A variant representing a trade for a given symbol.
   #[serde(rename = "t")]
   Trade(T),
+  /// A variant representing a trading status update for a given
+  /// symbol.
+  #[serde(rename = "s")]
+  Status(TradingStatus),
+  /// A variant representing limit up/limit down bands for a given
+  /// symbol.
+  #[serde(rename = "l")]
+  Luld(Luld),
   /// A control message describing the current list of subscriptions.
   #[serde(rename = "subscription")]
   Subscription(MarketData),
@@ -373,6 +448,12 @@ pub enum Data<B = Bar, Q = Quote, T = Trade> {
   Quote(Q),
   /// A variant representing trade data for a given symbol.
   Trade(T),
+  /// A variant representing a trading status update (such as a halt)
+  /// for a given symbol.
+  Status(TradingStatus),
+  /// A variant representing limit up/limit down bands for a given
+  /// symbol.
+  Luld(Luld),
 }
 
 impl<B, Q, T> Data<B, Q, T> {
@@ -393,6 +474,18 @@ impl<B, Q, T> Data<B, Q, T> {
   pub fn is_trade(&self) -> bool {
     matches!(self, Self::Trade(..))
   }
+
+  /// Check whether this object is of the `Status` variant.
+  #[inline]
+  pub fn is_status(&self) -> bool {
+    matches!(self, Self::Status(..))
+  }
+
+  /// Check whether this object is of the `Luld` variant.
+  #[inline]
+  pub fn is_luld(&self) -> bool {
+    matches!(self, Self::Luld(..))
+  }
 }
 
 
@@ -428,6 +521,10 @@ impl<B, Q, T> subscribe::Message for ParsedMessage<B, Q, T> {
         DataMessage::Trade(trade) => {
           subscribe::Classification::UserMessage(Ok(Ok(Data::Trade(trade))))
         },
+        DataMessage::Status(status) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::Status(status))))
+        },
+        DataMessage::Luld(luld) => subscribe::Classification::UserMessage(Ok(Ok(Data::Luld(luld)))),
         DataMessage::Subscription(data) => {
           subscribe::Classification::ControlMessage(ControlMessage::Subscription(data))
         },
@@ -587,6 +684,12 @@ pub struct MarketData {
   /// The trades to subscribe to.
   #[serde(default)]
   pub trades: Symbols,
+  /// The trading status updates to subscribe to.
+  #[serde(default, skip_serializing_if = "Symbols::is_empty")]
+  pub statuses: Symbols,
+  /// The limit up/limit down bands to subscribe to.
+  #[serde(default, skip_serializing_if = "Symbols::is_empty")]
+  pub lulds: Symbols,
 }
 
 impl MarketData {
@@ -619,6 +722,26 @@ impl MarketData {
   {
     self.trades = Symbols::List(symbols.into());
   }
+
+  /// A convenience function for setting the
+  /// [`statuses`][MarketData::statuses] member.
+  #[inline]
+  pub fn set_statuses<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.statuses = Symbols::List(symbols.into());
+  }
+
+  /// A convenience function for setting the [`lulds`][MarketData::lulds]
+  /// member.
+  #[inline]
+  pub fn set_lulds<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.lulds = Symbols::List(symbols.into());
+  }
 }
 
 
@@ -662,6 +785,9 @@ pub struct Subscription<S, B, Q, T> {
   subscription: subscribe::Subscription<S, ParsedMessage<B, Q, T>, wrap::Message>,
   /// The currently active individual market data subscriptions.
   subscriptions: MarketData,
+  /// A copy of `subscriptions` shared with subscriptions of
+  /// subsequent connections, used for restoring it when reconnecting.
+  recorded: Arc<Mutex<MarketData>>,
 }
 
 impl<S, B, Q, T> Subscription<S, B, Q, T> {
@@ -671,6 +797,7 @@ impl<S, B, Q, T> Subscription<S, B, Q, T> {
     Self {
       subscription,
       subscriptions: MarketData::default(),
+      recorded: Arc::default(),
     }
   }
 }
@@ -732,6 +859,7 @@ where
     match response {
       Some(response) => match response {
         Ok(ControlMessage::Subscription(data)) => {
+          *self.recorded.lock().unwrap() = data.clone();
           self.subscriptions = data;
           Ok(Ok(()))
         },
@@ -819,7 +947,8 @@ where
 {
   type Input = ApiInfo;
   type Subscription = Subscription<SplitSink<Stream<B, Q, T>, wrap::Message>, B, Q, T>;
-  type Stream = Fuse<MessageStream<SplitStream<Stream<B, Q, T>>, ParsedMessage<B, Q, T>>>;
+  type Stream =
+    Backpressure<Fuse<MessageStream<SplitStream<Stream<B, Q, T>>, ParsedMessage<B, Q, T>>>>;
 
   async fn connect(api_info: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error> {
     fn parse<B, Q, T>(
@@ -838,22 +967,24 @@ where
 
     let ApiInfo {
       data_stream_base_url: url,
-      key_id,
-      secret,
+      auth,
+      stream_buffer_size,
+      stream_backpressure,
       ..
     } = api_info;
+    let (key_id, secret) = auth.stream_credentials()?;
 
     let url = match S::source() {
       SourceVariant::PathComponent(component) => {
         let mut url = url.clone();
-        url.set_path(&format!("v2/{}", component));
+        url.set_path(&format!("{}/{}", VERSION.as_str(), component));
         url
       },
       SourceVariant::Url(url) => Url::parse(&url)?,
     };
 
     let stream = Unfold::new(
-      connect(&url)
+      connect(&url, *stream_buffer_size)
         .await?
         .map(parse::<B, Q, T> as ParseFn<_, _, _>),
     )
@@ -867,7 +998,7 @@ where
     let message = drive(connect, &mut stream).await.map_err(|result| {
       result
         .map(|result| Error::Json(result.unwrap_err()))
-        .map_err(Error::WebSocket)
+        .map_err(Error::from)
         .unwrap_or_else(|err| err)
     })?;
 
@@ -890,14 +1021,54 @@ where
     let () = drive(authenticate, &mut stream).await.map_err(|result| {
       result
         .map(|result| Error::Json(result.unwrap_err()))
-        .map_err(Error::WebSocket)
+        .map_err(Error::from)
         .unwrap_or_else(|err| err)
     })???;
 
+    let stream = Backpressure::new(stream, *stream_backpressure);
     Ok((stream, subscription))
   }
 }
 
+#[async_trait]
+impl<S, B, Q, T> Resubscribable for RealtimeData<S, B, Q, T>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  type State = Arc<Mutex<MarketData>>;
+
+  fn state(subscription: &Self::Subscription) -> Self::State {
+    subscription.recorded.clone()
+  }
+
+  async fn resubscribe(
+    state: &Self::State,
+    stream: &mut Self::Stream,
+    subscription: &mut Self::Subscription,
+  ) -> Result<(), Error> {
+    subscription.recorded = state.clone();
+
+    let subscriptions = state.lock().unwrap().clone();
+    if subscriptions == MarketData::default() {
+      return Ok(())
+    }
+
+    let subscribe = subscription.subscribe(&subscriptions).boxed();
+    drive(subscribe, stream).await.map_err(|result| {
+      result
+        .map(|result| match result {
+          Ok(..) => Error::Str("received unexpected data while resubscribing".into()),
+          Err(err) => Error::Json(err),
+        })
+        .map_err(Error::from)
+        .unwrap_or_else(|err| err)
+    })??
+  }
+}
+
 
 #[allow(clippy::to_string_trait_impl)]
 #[cfg(test)]
@@ -909,6 +1080,7 @@ mod tests {
 
   use chrono::DateTime;
 
+  use futures::future::join;
   use futures::SinkExt as _;
   use futures::TryStreamExt as _;
 
@@ -918,8 +1090,10 @@ mod tests {
 
   use test_log::test;
 
+  use tokio::net::TcpListener;
   use tokio::time::timeout;
 
+  use tungstenite::accept_async;
   use tungstenite::tungstenite::Utf8Bytes;
 
   use websocket_util::test::WebSocketStream;
@@ -927,7 +1101,12 @@ mod tests {
 
   use crate::api::API_BASE_URL;
   use crate::websocket::test::mock_stream;
+  use crate::websocket::test::mock_stream_with;
+  use crate::websocket::test::KEY_ID;
+  use crate::websocket::test::SECRET;
   use crate::Client;
+  use crate::Resilient;
+  use crate::RetryConfig;
 
 
   const CONN_RESP: &str = r#"[{"T":"success","msg":"connected"}]"#;
@@ -957,6 +1136,73 @@ mod tests {
     assert!(Data::<Bar, Quote, ()>::Trade(()).is_trade());
   }
 
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::Status`] variant for a trading halt.
+  #[test]
+  fn serialize_deserialize_halt_status() {
+    let json = r#"{
+  "T": "s",
+  "S": "AAPL",
+  "sc": "H",
+  "sm": "Trading Halt",
+  "rc": "T12",
+  "rm": "Trading Halted; For information requested by NASDAQ",
+  "t": "2021-02-22T19:15:00Z",
+  "z": "C"
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let status = match &message {
+      DataMessage::Status(status) => status,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(status.symbol, "AAPL");
+    assert_eq!(status.status_code, "H");
+    assert_eq!(status.status_message, "Trading Halt");
+    assert_eq!(status.reason_code, "T12");
+    assert_eq!(
+      status.reason_message,
+      "Trading Halted; For information requested by NASDAQ"
+    );
+    assert_eq!(status.tape, "C");
+    assert!(status.is_halted());
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+  }
+
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::Luld`] variant.
+  #[test]
+  fn serialize_deserialize_luld() {
+    let json = r#"{
+  "T": "l",
+  "S": "AAPL",
+  "u": 100.28,
+  "d": 97.59,
+  "i": "B",
+  "t": "2021-02-22T19:15:00Z",
+  "z": "C"
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let luld = match &message {
+      DataMessage::Luld(luld) => luld,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(luld.symbol, "AAPL");
+    assert_eq!(luld.limit_up_price, Num::new(10028, 100));
+    assert_eq!(luld.limit_down_price, Num::new(9759, 100));
+    assert_eq!(luld.indicator, "B");
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+  }
+
   /// Test that the [`Symbols::is_empty`] method works as expected.
   #[test]
   fn symbols_is_empty() {
@@ -1413,12 +1659,89 @@ mod tests {
       .unwrap();
 
     stream
-      .map_err(Error::WebSocket)
+      .map_err(Error::from)
       .try_for_each(|result| async { result.map(|_data| ()).map_err(Error::Json) })
       .await
       .unwrap();
   }
 
+  /// Check that no messages are lost when the server floods us with
+  /// data while we use a small read buffer.
+  #[test(tokio::test)]
+  async fn small_buffer_flood() {
+    use futures::channel::oneshot::channel;
+    use futures::channel::oneshot::Receiver;
+
+    const COUNT: usize = 1000;
+    const BAR: &str = r#"[{"T":"b","S":"AAPL","o":388.985,"h":389.13,"l":388.975,"c":389.12,"v":49378,"t":"2021-02-22T19:15:00Z"}]"#;
+
+    async fn test(
+      mut stream: WebSocketStream,
+      subscribed: Receiver<()>,
+    ) -> Result<(), WebSocketError> {
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      // Authentication.
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+
+      // Subscription.
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(SUB_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(SUB_RESP)))
+        .await?;
+
+      // Only start the flood once the client has completed the
+      // subscription, so that all bars end up on the stream.
+      let () = subscribed.await.unwrap();
+      for _ in 0..COUNT {
+        let () = stream
+          .feed(Message::Text(Utf8Bytes::from_static(BAR)))
+          .await?;
+      }
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    }
+
+    let (sender, receiver) = channel();
+    let (mut stream, mut subscription) = mock_stream_with::<RealtimeData<IEX>, _, _, _>(
+      |stream| test(stream, receiver),
+      |api_info| api_info.stream_buffer_size = Some(64),
+    )
+    .await
+    .unwrap();
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "VOO"]);
+
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+    let () = sender.send(()).unwrap();
+
+    let bars = stream
+      .map_err(Error::from)
+      .and_then(|result| async { result.map_err(Error::Json) })
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    assert_eq!(bars.len(), COUNT);
+    assert!(bars.iter().all(Data::is_bar));
+  }
+
   /// Check that we correctly handle errors reported as part of
   /// subscription.
   #[test(tokio::test)]
@@ -1527,15 +1850,13 @@ mod tests {
 
     assert_eq!(subscription.subscriptions(), &data);
 
-    let read = stream
-      .map_err(Error::WebSocket)
-      .try_for_each(|result| async {
-        result
-          .map(|data| {
-            assert!(data.is_bar());
-          })
-          .map_err(Error::Json)
-      });
+    let read = stream.map_err(Error::from).try_for_each(|result| async {
+      result
+        .map(|data| {
+          assert!(data.is_bar());
+        })
+        .map_err(Error::Json)
+    });
 
     if timeout(Duration::from_millis(100), read).await.is_ok() {
       panic!("realtime data stream got exhausted unexpectedly")
@@ -1568,15 +1889,13 @@ mod tests {
         .unwrap()
         .unwrap();
 
-      let read = stream
-        .map_err(Error::WebSocket)
-        .try_for_each(|result| async {
-          result
-            .map(|data| {
-              assert!(data.is_quote());
-            })
-            .map_err(Error::Json)
-        });
+      let read = stream.map_err(Error::from).try_for_each(|result| async {
+        result
+          .map(|data| {
+            assert!(data.is_quote());
+          })
+          .map_err(Error::Json)
+      });
 
       if timeout(Duration::from_millis(100), read).await.is_ok() {
         panic!("realtime data stream got exhausted unexpectedly")
@@ -1615,15 +1934,13 @@ mod tests {
       .unwrap()
       .unwrap();
 
-    let read = stream
-      .map_err(Error::WebSocket)
-      .try_for_each(|result| async {
-        result
-          .map(|data| {
-            assert!(data.is_trade());
-          })
-          .map_err(Error::Json)
-      });
+    let read = stream.map_err(Error::from).try_for_each(|result| async {
+      result
+        .map(|data| {
+          assert!(data.is_trade());
+        })
+        .map_err(Error::Json)
+    });
 
     if timeout(Duration::from_millis(100), read).await.is_ok() {
       panic!("realtime data stream got exhausted unexpectedly")
@@ -1692,4 +2009,82 @@ mod tests {
       _ => panic!("Received unexpected error: {err:?}"),
     };
   }
+
+  /// Check that a resilient subscription restores the market data
+  /// subscribed to when reconnecting.
+  #[test(tokio::test)]
+  async fn resilient_resubscribe() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // Serve two connections, expecting the same subscription request
+    // on each, and close them afterwards. For the second connection
+    // the request has to be replayed on our behalf.
+    let _handle = tokio::spawn(async move {
+      for _ in 0..2 {
+        let (stream, _addr) = listener.accept().await.unwrap();
+        let mut stream = accept_async(stream).await.unwrap();
+
+        let message = Message::Text(Utf8Bytes::from_static(CONN_RESP));
+        let () = stream.send(message).await.unwrap();
+
+        let message = stream.next().await.unwrap().unwrap();
+        assert_eq!(message, Message::Text(Utf8Bytes::from_static(AUTH_REQ)));
+        let message = Message::Text(Utf8Bytes::from_static(AUTH_RESP));
+        let () = stream.send(message).await.unwrap();
+
+        let message = stream.next().await.unwrap().unwrap();
+        assert_eq!(message, Message::Text(Utf8Bytes::from_static(SUB_REQ)));
+        let message = Message::Text(Utf8Bytes::from_static(SUB_RESP));
+        let () = stream.send(message).await.unwrap();
+        let () = stream.send(Message::Close(None)).await.unwrap();
+      }
+    });
+
+    let mut api_info = ApiInfo::from_parts(API_BASE_URL, KEY_ID, SECRET).unwrap();
+    api_info.data_stream_base_url = Url::parse(&format!("ws://{addr}")).unwrap();
+    let client = Client::new(api_info);
+
+    let config = RetryConfig {
+      max_attempts: 1,
+      base_delay: Duration::from_millis(10),
+      ..Default::default()
+    };
+    let (mut stream, mut subscription) = client
+      .subscribe_resilient::<RealtimeData<IEX>>(config)
+      .await
+      .unwrap();
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "VOO"]);
+
+    // Subscribe while the stream is being polled, which is necessary for
+    // the subscription to resolve. Once the server closes the
+    // connection, we are going to reconnect.
+    let subscribe = subscription.subscribe(&data);
+    let (result, event) = join(subscribe, stream.next()).await;
+    let () = result.unwrap().unwrap();
+    assert_eq!(subscription.subscriptions(), &data);
+    assert!(matches!(
+      event,
+      Some(Resilient::Reconnecting { attempt: 1, .. })
+    ));
+
+    match stream.next().await {
+      Some(Resilient::Reconnected(subscription)) => {
+        assert_eq!(subscription.subscriptions(), &data)
+      },
+      event => panic!("received unexpected event: {event:?}"),
+    }
+
+    assert!(matches!(
+      stream.next().await,
+      Some(Resilient::Reconnecting { attempt: 1, .. })
+    ));
+    assert!(matches!(
+      stream.next().await,
+      Some(Resilient::Disconnected(..))
+    ));
+    assert!(stream.next().await.is_none());
+  }
 }
@@ -8,10 +8,16 @@ use num_decimal::Num;
 
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::from_slice as from_json;
 use serde_urlencoded::to_string as to_query;
 
+use crate::data::v2::conditions::Tape;
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::MAX_LIMIT;
+use crate::data::v2::VERSION;
+use crate::endpoint::BaseUrl;
+use crate::util::clamp_limit;
+use crate::util::option_from_non_empty_str;
 use crate::util::vec_from_str;
 use crate::Str;
 
@@ -25,8 +31,9 @@ pub struct ListReq {
   /// The maximum number of trades to be returned for each symbol.
   ///
   /// It can be between 1 and 10000. Defaults to 1000 if the provided
-  /// value is `None`.
-  #[serde(rename = "limit")]
+  /// value is `None`. Larger values are clamped to the maximum when
+  /// issuing the request.
+  #[serde(rename = "limit", serialize_with = "clamp_limit::<MAX_LIMIT, _>")]
   pub limit: Option<usize>,
   /// Filter trades equal to or after this time.
   #[serde(rename = "start")]
@@ -43,6 +50,10 @@ pub struct ListReq {
   /// If provided we will pass a page token to continue where we left off.
   #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
   pub page_token: Option<String>,
+  /// The currency in which prices are to be reported, as an ISO 4217
+  /// code (e.g., "JPY"). Defaults to USD.
+  #[serde(rename = "currency", skip_serializing_if = "Option::is_none")]
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -59,6 +70,8 @@ pub struct ListReqInit {
   pub feed: Option<Feed>,
   /// See `ListReq::page_token`.
   pub page_token: Option<String>,
+  /// See `ListReq::currency`.
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
@@ -78,6 +91,7 @@ impl ListReqInit {
       limit: self.limit,
       feed: self.feed,
       page_token: self.page_token,
+      currency: self.currency,
       _non_exhaustive: (),
     }
   }
@@ -96,6 +110,19 @@ pub struct Trade {
   /// The size of the trade.
   #[serde(rename = "s")]
   pub size: usize,
+  /// The code of the exchange at which the trade happened, if
+  /// reported.
+  #[serde(rename = "x", default)]
+  pub exchange: Option<String>,
+  /// The condition codes of the trade.
+  ///
+  /// Use [`Conditions`][crate::data::v2::conditions::Conditions] to
+  /// look up their descriptions, which depend on the tape.
+  #[serde(rename = "c", default, deserialize_with = "vec_from_str")]
+  pub conditions: Vec<String>,
+  /// The tape on which the trade was reported, if reported.
+  #[serde(rename = "z", default)]
+  pub tape: Option<Tape>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -113,8 +140,19 @@ pub struct Trades {
   #[serde(rename = "symbol")]
   pub symbol: String,
   /// The token to provide to a request to get the next page of trades for this request.
-  #[serde(rename = "next_page_token")]
+  ///
+  /// `None` (reported as either `null` or an empty string) indicates
+  /// that no more data is available.
+  #[serde(
+    rename = "next_page_token",
+    default,
+    deserialize_with = "option_from_non_empty_str"
+  )]
   pub next_page_token: Option<String>,
+  /// The currency in which prices of the trades are reported, if it
+  /// was reported by the API.
+  #[serde(rename = "currency", default)]
+  pub currency: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -135,11 +173,11 @@ Endpoint! {
   ]
 
   fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+    BaseUrl::Data.into_str()
   }
 
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/stocks/{}/trades", input.symbol).into()
+    VERSION.path(&format!("stocks/{}/trades", input.symbol)).into()
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -148,12 +186,108 @@ Endpoint! {
 }
 
 
+/// A GET request to be issued to the /v2/stocks/{symbol}/trades/latest
+/// endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct GetLatestReq {
+  /// The symbol for which to retrieve the latest trade.
+  #[serde(skip)]
+  pub symbol: String,
+  /// The data feed to use.
+  #[serde(rename = "feed", skip_serializing_if = "Option::is_none")]
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`GetLatestReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct GetLatestReqInit {
+  /// See `GetLatestReq::feed`.
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl GetLatestReqInit {
+  /// Create a [`GetLatestReq`] from a `GetLatestReqInit`.
+  #[inline]
+  pub fn init<S>(self, symbol: S) -> GetLatestReq
+  where
+    S: Into<String>,
+  {
+    GetLatestReq {
+      symbol: symbol.into(),
+      feed: self.feed,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/trades/latest endpoint.
+  pub GetLatest(GetLatestReq),
+  Ok => Trade, [
+    /// The latest trade was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetLatestError, [
+    /// The provided symbol was invalid or not found or the data feed is
+    /// not supported.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn base_url() -> Option<Str> {
+    BaseUrl::Data.into_str()
+  }
+
+  fn path(input: &Self::Input) -> Str {
+    VERSION.path(&format!("stocks/{}/trades/latest", input.symbol)).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    let query = to_query(input)?;
+    if query.is_empty() {
+      Ok(None)
+    } else {
+      Ok(Some(query.into()))
+    }
+  }
+
+  fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    /// A helper object for parsing the response to a `GetLatest`
+    /// request.
+    #[derive(Deserialize)]
+    struct Response {
+      /// The latest trade.
+      trade: Trade,
+    }
+
+    from_json::<Response>(body)
+      .map(|response| response.trade)
+      .map_err(|err| Self::ConversionError::invalid_response(err, body))
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    Self::ApiError::parse(body)
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use std::str::FromStr as _;
 
+  use http::StatusCode;
   use http_endpoint::Endpoint;
 
   use serde_json::from_str as from_json;
@@ -161,10 +295,51 @@ mod tests {
   use test_log::test;
 
   use crate::api_info::ApiInfo;
+  use crate::mock::Server;
   use crate::Client;
   use crate::RequestError;
 
 
+  /// Check that we request trades in the desired currency and report the
+  /// currency the API used.
+  #[test(tokio::test)]
+  async fn request_currency() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "trades": [{
+          "t": "2021-11-05T13:04:56.334320128Z",
+          "x": "C",
+          "p": 187.62,
+          "s": 100,
+          "c": ["@"],
+          "i": 52983525029461,
+          "z": "C"
+        }],
+        "symbol": "SAP",
+        "next_page_token": null,
+        "currency": "EUR"
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let start = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-11-06T00:00:00Z").unwrap();
+    let request = ListReqInit {
+      currency: Some("EUR".to_string()),
+      ..Default::default()
+    }
+    .init("SAP", start, end);
+    let trades = client.issue::<List>(&request).await.unwrap();
+    assert_eq!(trades.currency.as_deref(), Some("EUR"));
+    assert_eq!(trades.trades.len(), 1);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].contains("currency=EUR"), "{}", requests[0]);
+  }
+
   /// Verify that we can properly parse a reference trades response.
   #[test]
   fn parse_reference_trades() {
@@ -207,10 +382,77 @@ mod tests {
     assert!(timestamp.starts_with(expected_time), "{timestamp}");
     assert_eq!(trades[0].price, Num::new(38762, 100));
     assert_eq!(trades[0].size, 100);
+    assert_eq!(trades[0].conditions, [" ", "T"]);
+    assert_eq!(trades[0].tape, Some(Tape::B));
     assert_eq!(res.symbol, "SPY".to_string());
     assert!(res.next_page_token.is_some())
   }
 
+  /// Check that we can parse trades lacking condition codes and tape.
+  #[test]
+  fn parse_trade_without_conditions() {
+    let response = r#"{
+      "t": "2021-02-06T13:04:56.334320128Z",
+      "p": 387.62,
+      "s": 100,
+      "c": null
+    }"#;
+
+    let trade = from_json::<Trade>(response).unwrap();
+    assert_eq!(trade.conditions, Vec::<String>::new());
+    assert_eq!(trade.tape, None);
+  }
+
+  /// Check that we can retrieve the latest trade for a symbol.
+  #[test(tokio::test)]
+  async fn request_latest_trade() {
+    let server = Server::serve(|_request| {
+      let body = r#"{
+        "symbol": "AAPL",
+        "trade": {
+          "t": "2021-02-06T13:04:56.334320128Z",
+          "x": "V",
+          "p": 136.76,
+          "s": 300,
+          "c": ["@"],
+          "i": 52983525029461,
+          "z": "C"
+        }
+      }"#;
+      (StatusCode::OK, body.to_string())
+    })
+    .await;
+    let client = Client::new(server.api_info());
+
+    let request = GetLatestReqInit {
+      feed: Some(Feed::IEX),
+      ..Default::default()
+    }
+    .init("AAPL");
+    let trade = client.issue::<GetLatest>(&request).await.unwrap();
+    assert_eq!(
+      trade.timestamp,
+      DateTime::<Utc>::from_str("2021-02-06T13:04:56.334320128Z").unwrap()
+    );
+    assert_eq!(trade.price, Num::new(13676, 100));
+    assert_eq!(trade.size, 300);
+    assert_eq!(trade.exchange.as_deref(), Some("V"));
+    assert_eq!(trade.conditions, ["@"]);
+    assert_eq!(trade.tape, Some(Tape::C));
+
+    let request = GetLatestReqInit::default().init("AAPL");
+    let _trade = client.issue::<GetLatest>(&request).await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+      requests,
+      [
+        "GET /v2/stocks/AAPL/trades/latest?feed=iex",
+        "GET /v2/stocks/AAPL/trades/latest",
+      ]
+    );
+  }
+
   /// Check that we can decode a response containing no trades correctly.
   #[test(tokio::test)]
   async fn no_trades() {
@@ -1,12 +1,53 @@
 // Copyright (C) 2019-2023 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::str::from_utf8;
+
 use serde::Deserialize;
+use serde_json::from_slice as from_json;
 use serde_json::Error as JsonError;
 use serde_urlencoded::ser::Error as UrlEncodeError;
 
 use thiserror::Error;
 
+use crate::Str;
+
+
+/// The kind of base URL that requests to an endpoint are directed to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum BaseUrl {
+  /// The Trading API base URL, as per `ApiInfo::api_base_url`.
+  Api,
+  /// The market data API base URL, as per `ApiInfo::data_base_url`.
+  Data,
+}
+
+impl BaseUrl {
+  /// The value reported by `Endpoint::base_url` for data API
+  /// endpoints. It is not an actual URL and only ever interpreted by
+  /// [`BaseUrl::classify`].
+  const DATA: &'static str = "apca:data-base-url";
+
+  /// Convert the kind into a value to report from
+  /// `Endpoint::base_url`.
+  pub(crate) fn into_str(self) -> Option<Str> {
+    match self {
+      Self::Api => None,
+      Self::Data => Some(Self::DATA.into()),
+    }
+  }
+
+  /// Classify the value reported by `Endpoint::base_url`, handing back
+  /// any URL not describing one of our kinds.
+  pub(crate) fn classify(base_url: Option<Str>) -> Result<Self, Str> {
+    match base_url {
+      None => Ok(Self::Api),
+      Some(url) if url == Self::DATA => Ok(Self::Data),
+      Some(url) => Err(url),
+    }
+  }
+}
+
 
 /// An error type comprising various conversion errors we may encounter.
 #[derive(Debug, Error)]
@@ -17,6 +58,29 @@ pub enum ConversionError {
   /// A variant used when we fail to URL-encode a piece of data.
   #[error("failed to URL-encode data")]
   UrlEncode(#[from] UrlEncodeError),
+  /// A variant used when the request to convert is invalid.
+  #[error("the request is invalid: {0}")]
+  InvalidRequest(Str),
+  /// A variant used when a response body could not be parsed.
+  #[error("failed to parse response body")]
+  InvalidResponse {
+    /// The underlying JSON error.
+    #[source]
+    source: JsonError,
+    /// The response body, lossily converted to UTF-8.
+    body: String,
+  },
+}
+
+impl ConversionError {
+  /// Create a [`ConversionError::InvalidResponse`] error for a response
+  /// body that failed to parse.
+  pub(crate) fn invalid_response(source: JsonError, body: &[u8]) -> Self {
+    Self::InvalidResponse {
+      source,
+      body: String::from_utf8_lossy(body).into_owned(),
+    }
+  }
 }
 
 
@@ -25,13 +89,85 @@ pub enum ConversionError {
 // version in question. However, at this point we only support v2, so we
 // luck out here.
 #[derive(Clone, Debug, Deserialize, Error, Eq, PartialEq)]
+#[serde(try_from = "ApiErrorSerde")]
 #[error("{message}")]
 pub struct ApiError {
+  /// The error code as provided by Alpaca, if any.
+  pub code: Option<u64>,
   /// A message as provided by Alpaca.
-  #[serde(rename = "message")]
   pub message: String,
 }
 
+impl ApiError {
+  /// Parse an `ApiError` from a response body.
+  ///
+  /// Bodies that are not JSON but valid UTF-8 are captured verbatim as
+  /// the error message. Only empty or binary bodies are reported as
+  /// raw bytes.
+  pub(crate) fn parse(body: &[u8]) -> Result<Self, Vec<u8>> {
+    if let Ok(error) = from_json::<Self>(body) {
+      return Ok(error)
+    }
+
+    match from_utf8(body).map(str::trim) {
+      Ok(message) if !message.is_empty() => Ok(Self {
+        code: None,
+        message: message.to_string(),
+      }),
+      _ => Err(body.to_vec()),
+    }
+  }
+}
+
+
+/// The message of an error, as reported by Alpaca in one of several
+/// forms.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MessageSerde {
+  /// A plain message.
+  Plain(String),
+  /// A nested object containing the message and possibly a code.
+  Nested {
+    #[serde(rename = "code", default)]
+    code: Option<u64>,
+    #[serde(rename = "message")]
+    message: String,
+  },
+}
+
+
+/// The various shapes of error objects reported by Alpaca:
+/// `{"code": ..., "message": "..."}`, `{"message": {"code": ...,
+/// "message": "..."}}`, and `{"error": "..."}`.
+#[derive(Deserialize)]
+struct ApiErrorSerde {
+  #[serde(rename = "code", default)]
+  code: Option<u64>,
+  #[serde(rename = "message", default)]
+  message: Option<MessageSerde>,
+  #[serde(rename = "error", default)]
+  error: Option<MessageSerde>,
+}
+
+impl TryFrom<ApiErrorSerde> for ApiError {
+  type Error = Str;
+
+  fn try_from(other: ApiErrorSerde) -> Result<Self, Self::Error> {
+    let message = other
+      .message
+      .or(other.error)
+      .ok_or_else(|| Str::from("error object does not contain a message"))?;
+
+    let (code, message) = match message {
+      MessageSerde::Plain(message) => (other.code, message),
+      MessageSerde::Nested { code, message } => (code.or(other.code), message),
+    };
+
+    Ok(Self { code, message })
+  }
+}
+
 
 /// A macro used for defining the properties for a request to a
 /// particular HTTP endpoint, without automated JSON parsing.
@@ -79,12 +215,89 @@ macro_rules! Endpoint {
       $($input)*
 
       fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
-        ::serde_json::from_slice::<Self::Output>(body).map_err(Self::ConversionError::from)
+        ::serde_json::from_slice::<Self::Output>(body)
+          .map_err(|err| crate::endpoint::ConversionError::invalid_response(err, body))
       }
 
       fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
-        ::serde_json::from_slice::<Self::ApiError>(body).map_err(|_| body.to_vec())
+        crate::endpoint::ApiError::parse(body)
       }
     }
   };
 }
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use http_endpoint::Endpoint;
+
+  use crate::api::v2::account;
+
+
+  /// Check that we can round trip the kinds of base URLs through
+  /// `Endpoint::base_url` values.
+  #[test]
+  fn classify_base_url() {
+    for kind in [BaseUrl::Api, BaseUrl::Data] {
+      assert_eq!(BaseUrl::classify(kind.into_str()), Ok(kind));
+    }
+
+    let url = Str::from("https://data.alpaca.markets");
+    assert_eq!(BaseUrl::classify(Some(url.clone())), Err(url));
+  }
+
+  /// Check that we can parse the various shapes of error responses
+  /// reported by Alpaca.
+  #[test]
+  fn parse_error_shapes() {
+    let parse = <account::Get as Endpoint>::parse_err;
+
+    let body = br#"{"code": 40410000, "message": "not found"}"#;
+    let expected = ApiError {
+      code: Some(40410000),
+      message: "not found".to_string(),
+    };
+    assert_eq!(parse(body).unwrap(), expected);
+
+    let body = br#"{"message": {"code": 40410000, "message": "not found"}}"#;
+    assert_eq!(parse(body).unwrap(), expected);
+
+    let body = br#"{"error": "not found"}"#;
+    let expected = ApiError {
+      code: None,
+      message: "not found".to_string(),
+    };
+    assert_eq!(parse(body).unwrap(), expected);
+
+    let body = b"upstream connect error\n";
+    let expected = ApiError {
+      code: None,
+      message: "upstream connect error".to_string(),
+    };
+    assert_eq!(parse(body).unwrap(), expected);
+
+    assert_eq!(parse(b"").unwrap_err(), Vec::<u8>::new());
+    assert_eq!(parse(b"\xff\xfe").unwrap_err(), b"\xff\xfe".to_vec());
+  }
+
+  /// Check that the response body is preserved when it fails to parse.
+  #[test]
+  fn invalid_response_body() {
+    let body = br#"{"id": "904837e3-3b76-47ec-b432-046db621571b", "status": 42}"#;
+    let err = <account::Get as Endpoint>::parse(body).unwrap_err();
+    match err {
+      ConversionError::InvalidResponse { body: response, .. } => {
+        assert_eq!(response.as_bytes(), body)
+      },
+      e => panic!("received unexpected error: {e:?}"),
+    }
+
+    let err = <account::Get as Endpoint>::parse(b"\xffnot json").unwrap_err();
+    match err {
+      ConversionError::InvalidResponse { body, .. } => assert_eq!(body, "\u{fffd}not json"),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+  }
+}
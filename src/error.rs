@@ -14,6 +14,7 @@ use hyper::Error as HyperError;
 use serde_json::Error as JsonError;
 use thiserror::Error;
 use url::ParseError;
+#[cfg(feature = "streaming")]
 use websocket_util::tungstenite::Error as WebSocketError;
 
 use crate::Str;
@@ -84,6 +85,9 @@ pub enum Error {
     #[source]
     JsonError,
   ),
+  /// An I/O error, along with a description of the failed operation.
+  #[error("{0}")]
+  Io(Str, #[source] IoError),
   /// An error directly originating in this crate.
   #[error("{0}")]
   Str(Str),
@@ -95,12 +99,20 @@ pub enum Error {
     ParseError,
   ),
   /// A websocket error.
+  #[cfg(feature = "streaming")]
   #[error("encountered a websocket related error")]
-  WebSocket(
-    #[from]
-    #[source]
-    WebSocketError,
-  ),
+  WebSocket(#[source] Box<WebSocketError>),
+}
+
+#[cfg(feature = "streaming")]
+impl From<WebSocketError> for Error {
+  #[inline]
+  fn from(err: WebSocketError) -> Self {
+    // The websocket error is boxed, as it is much larger than all
+    // other variants and would otherwise bloat every `Result` using
+    // this error type.
+    Self::WebSocket(Box::new(err))
+  }
 }
 
 
@@ -108,6 +120,31 @@ pub enum Error {
 mod tests {
   use super::*;
 
+  use std::error::Error as StdError;
+  use std::io::ErrorKind;
+
+  use serde_json::from_str as from_json;
+
+  use crate::api::v2::account;
+  use crate::endpoint::ConversionError;
+
+
+  /// Walk the chain of sources of the provided error and check whether
+  /// it contains an error of type `T`.
+  fn has_source<T>(err: &(dyn StdError + 'static)) -> bool
+  where
+    T: StdError + 'static,
+  {
+    let mut source = err.source();
+    while let Some(err) = source {
+      if err.is::<T>() {
+        return true
+      }
+      source = err.source();
+    }
+    false
+  }
+
 
   /// Check that we can serialize a [`Side`] object.
   #[test]
@@ -126,4 +163,35 @@ mod tests {
       "encountered an unexpected HTTP status: 404 Not Found: invalid"
     );
   }
+
+  /// Check that errors expose their underlying causes.
+  #[test]
+  fn source_chaining() {
+    let json_err = || from_json::<u64>("foo").unwrap_err();
+
+    let err = RequestError::<account::GetError>::Endpoint(account::GetError::Conversion(
+      ConversionError::Json(json_err()),
+    ));
+    assert!(has_source::<account::GetError>(&err));
+    assert!(has_source::<JsonError>(&err));
+
+    let err = ConversionError::invalid_response(json_err(), b"foo");
+    assert!(has_source::<JsonError>(&err));
+
+    let err = RequestError::<account::GetError>::Io(IoError::from(ErrorKind::ConnectionReset));
+    assert!(has_source::<IoError>(&err));
+
+    let err = Error::from(json_err());
+    assert!(has_source::<JsonError>(&err));
+
+    let err = Error::from(url::Url::parse("foo").unwrap_err());
+    assert!(has_source::<ParseError>(&err));
+
+    let err = Error::Io(
+      "failed to read profiles".into(),
+      IoError::from(ErrorKind::NotFound),
+    );
+    assert_eq!(err.to_string(), "failed to read profiles");
+    assert!(has_source::<IoError>(&err));
+  }
 }
@@ -100,19 +100,50 @@ pub mod api;
 pub mod data;
 
 mod api_info;
+// The stream adapter is only used for websocket streams, but the
+// strategy is part of `ApiInfo` regardless.
+#[cfg_attr(not(feature = "streaming"), allow(dead_code, unreachable_pub))]
+mod backpressure;
+mod cache;
 mod client;
 mod error;
+#[cfg(test)]
+mod mock;
+mod profiles;
+#[cfg(feature = "streaming")]
 mod subscribable;
+mod time_source;
 mod util;
+#[cfg(feature = "streaming")]
 mod websocket;
 
 use std::borrow::Cow;
 
 pub use crate::api_info::ApiInfo;
+pub use crate::api_info::Auth;
+#[cfg(feature = "streaming")]
+pub use crate::backpressure::Backpressure;
+pub use crate::backpressure::BackpressureStrategy;
 pub use crate::client::Client;
+pub use crate::client::HttpVersion;
+pub use crate::client::PreparedRequest;
+pub use crate::client::RateLimit;
+#[cfg(feature = "streaming")]
+pub use crate::client::ReconnectStats;
+#[cfg(feature = "streaming")]
+pub use crate::client::Resilient;
+#[cfg(feature = "streaming")]
+pub use crate::client::ResilientStream;
+pub use crate::client::RetryConfig;
 pub use crate::endpoint::ApiError;
 pub use crate::error::Error;
 pub use crate::error::RequestError;
+pub use crate::profiles::Profiles;
+#[cfg(feature = "streaming")]
+pub use crate::subscribable::Resubscribable;
+#[cfg(feature = "streaming")]
 pub use crate::subscribable::Subscribable;
+pub use crate::time_source::SystemClock;
+pub use crate::time_source::TimeSource;
 
 type Str = Cow<'static, str>;
@@ -0,0 +1,183 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::str::from_utf8;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use http::StatusCode;
+
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+
+use crate::ApiInfo;
+
+
+/// Read a full HTTP request from the given stream, returning its
+/// request line (e.g., "GET /v2/orders?status=open") and body.
+async fn read_request(stream: &TcpStream) -> (String, Vec<u8>) {
+  let mut request = Vec::new();
+  let mut buffer = [0; 4096];
+
+  let header_end = loop {
+    if let Some(idx) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+      break idx + 4
+    }
+
+    let () = stream.readable().await.unwrap();
+    match stream.try_read(&mut buffer) {
+      Ok(0) => panic!("connection closed before request was received"),
+      Ok(count) => request.extend_from_slice(&buffer[..count]),
+      Err(err) if err.kind() == ErrorKind::WouldBlock => continue,
+      Err(err) => panic!("failed to read request: {err}"),
+    }
+  };
+
+  let header = from_utf8(&request[..header_end]).unwrap().to_string();
+  let content_length = header
+    .lines()
+    .filter_map(|line| line.split_once(':'))
+    .find(|(name, _value)| name.eq_ignore_ascii_case("content-length"))
+    .map(|(_name, value)| value.trim().parse::<usize>().unwrap())
+    .unwrap_or(0);
+
+  // Drain the request body, if any.
+  while request.len() < header_end + content_length {
+    let () = stream.readable().await.unwrap();
+    match stream.try_read(&mut buffer) {
+      Ok(0) => panic!("connection closed before request body was received"),
+      Ok(count) => request.extend_from_slice(&buffer[..count]),
+      Err(err) if err.kind() == ErrorKind::WouldBlock => continue,
+      Err(err) => panic!("failed to read request body: {err}"),
+    }
+  }
+
+  // The request line contains the method, the request target, and the
+  // HTTP version. We only care about the former two.
+  let line = header.lines().next().unwrap();
+  let line = line.rsplit_once(' ').unwrap().0.to_string();
+  let body = request[header_end..header_end + content_length].to_vec();
+  (line, body)
+}
+
+
+/// Write an HTTP response with the given status, additional headers,
+/// and body to a stream.
+async fn write_response(
+  stream: &TcpStream,
+  status: StatusCode,
+  headers: &[(String, String)],
+  body: &str,
+) {
+  let headers = headers
+    .iter()
+    .map(|(name, value)| format!("{name}: {value}\r\n"))
+    .collect::<String>();
+  let response = format!(
+    "HTTP/1.1 {} {}\r\ncontent-length: {}\r\nconnection: close\r\n{headers}\r\n{body}",
+    status.as_u16(),
+    status.canonical_reason().unwrap_or(""),
+    body.len(),
+  );
+
+  let mut response = response.as_bytes();
+  while !response.is_empty() {
+    let () = stream.writable().await.unwrap();
+    match stream.try_write(response) {
+      Ok(count) => response = &response[count..],
+      Err(err) if err.kind() == ErrorKind::WouldBlock => continue,
+      Err(err) => panic!("failed to write response: {err}"),
+    }
+  }
+}
+
+
+/// The request lines and bodies of requests received by a [`Server`].
+type Received = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+
+/// A mock HTTP server answering requests with canned responses.
+#[derive(Debug)]
+pub(crate) struct Server {
+  /// The address the server listens on.
+  addr: SocketAddr,
+  /// The request lines and bodies of all requests received so far, in
+  /// order.
+  requests: Received,
+}
+
+impl Server {
+  /// Start a server on a local port, answering each request with the
+  /// status and body returned by `handler` for the request line.
+  pub(crate) async fn serve<F>(mut handler: F) -> Self
+  where
+    F: FnMut(&str) -> (StatusCode, String) + Send + 'static,
+  {
+    Self::serve_with_headers(move |request| {
+      let (status, body) = handler(request);
+      (status, Vec::new(), body)
+    })
+    .await
+  }
+
+  /// Start a server on a local port, answering each request with the
+  /// status, additional headers, and body returned by `handler` for
+  /// the request line.
+  pub(crate) async fn serve_with_headers<F>(mut handler: F) -> Self
+  where
+    F: FnMut(&str) -> (StatusCode, Vec<(String, String)>, String) + Send + 'static,
+  {
+    Self::serve_or_drop(move |request| Some(handler(request))).await
+  }
+
+  /// Start a server on a local port, answering each request with the
+  /// status, additional headers, and body returned by `handler` for
+  /// the request line or, if it returns `None`, closing the connection
+  /// without a response, to simulate a transport level failure.
+  pub(crate) async fn serve_or_drop<F>(mut handler: F) -> Self
+  where
+    F: FnMut(&str) -> Option<(StatusCode, Vec<(String, String)>, String)> + Send + 'static,
+  {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+
+    let _handle = tokio::spawn(async move {
+      loop {
+        let (stream, _addr) = listener.accept().await.unwrap();
+        let (request, request_body) = read_request(&stream).await;
+        let response = handler(&request);
+        let () = received.lock().unwrap().push((request, request_body));
+        if let Some((status, headers, body)) = response {
+          let () = write_response(&stream, status, &headers, &body).await;
+        }
+      }
+    });
+
+    Self { addr, requests }
+  }
+
+  /// Create an [`ApiInfo`] object directing requests to this server,
+  /// for both the Trading API and the data API.
+  pub(crate) fn api_info(&self) -> ApiInfo {
+    let mut api_info =
+      ApiInfo::from_parts(format!("http://{}", self.addr), "key", "secret").unwrap();
+    api_info.data_base_url = api_info.api_base_url.clone();
+    api_info
+  }
+
+  /// Retrieve the request lines of all requests received so far.
+  pub(crate) fn requests(&self) -> Vec<String> {
+    let requests = self.requests.lock().unwrap();
+    requests.iter().map(|(line, _body)| line.clone()).collect()
+  }
+
+  /// Retrieve the bodies of all requests received so far.
+  pub(crate) fn bodies(&self) -> Vec<Vec<u8>> {
+    let requests = self.requests.lock().unwrap();
+    requests.iter().map(|(_line, body)| body.clone()).collect()
+  }
+}
@@ -0,0 +1,487 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::env::var_os;
+use std::ffi::OsString;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::str::FromStr;
+
+use url::Url;
+
+use crate::api::API_BASE_URL;
+use crate::api_info::redact_key_id;
+use crate::ApiInfo;
+use crate::Error;
+
+/// The environment variable representing the name of the default
+/// profile.
+const ENV_PROFILE: &str = "APCA_PROFILE";
+/// The name of the profile used if none was selected explicitly.
+const DEFAULT_PROFILE: &str = "default";
+/// The prefix of section headers introducing a profile.
+const SECTION_PREFIX: &str = "profile.";
+
+
+/// The settings of a single profile.
+#[derive(Clone, Default, Eq, PartialEq)]
+struct Profile {
+  /// The base URL for the Trading API.
+  api_base_url: Option<String>,
+  /// The websocket stream URL for the Trading API.
+  api_stream_url: Option<String>,
+  /// The base URL for data retrieval.
+  data_base_url: Option<String>,
+  /// The websocket base URL for streaming of data.
+  data_stream_base_url: Option<String>,
+  /// The key ID to use for authentication.
+  key_id: Option<String>,
+  /// The secret to use for authentication.
+  secret: Option<String>,
+  /// The OAuth access token to use for authentication.
+  oauth_token: Option<String>,
+}
+
+impl Debug for Profile {
+  /// Format the `Profile` object, redacting the credentials contained
+  /// in it.
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let Self {
+      api_base_url,
+      api_stream_url,
+      data_base_url,
+      data_stream_base_url,
+      key_id,
+      secret,
+      oauth_token,
+    } = self;
+
+    f.debug_struct("Profile")
+      .field("api_base_url", api_base_url)
+      .field("api_stream_url", api_stream_url)
+      .field("data_base_url", data_base_url)
+      .field("data_stream_base_url", data_stream_base_url)
+      .field("key_id", &key_id.as_deref().map(redact_key_id))
+      .field("secret", &secret.as_ref().map(|_| "<redacted>"))
+      .field("oauth_token", &oauth_token.as_ref().map(|_| "<redacted>"))
+      .finish()
+  }
+}
+
+
+/// A set of named profiles, each describing how to work with a
+/// different Alpaca account.
+///
+/// Profiles are loaded from a configuration file containing one
+/// `[profile.<name>]` section per profile, for example:
+/// ```text
+/// [profile.paper]
+/// api_base_url = "https://paper-api.alpaca.markets"
+/// key_id = "XXXXXXXXXXXXXXXXXXXX"
+/// secret = "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY"
+///
+/// [profile.live]
+/// api_base_url = "https://api.alpaca.markets"
+/// key_id = "ZZZZZZZZZZZZZZZZZZZZ"
+/// secret = "WWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWW"
+/// ```
+///
+/// The following settings are supported:
+/// - `api_base_url`: the base URL for the Trading API, defaulting to
+///   the paper trading API
+/// - `api_stream_url`: the websocket stream URL for the Trading API,
+///   inferred from `api_base_url` if not present
+/// - `data_base_url`: the base URL for data retrieval, defaulting to
+///   `https://data.alpaca.markets`
+/// - `data_stream_base_url`: the websocket base URL for streaming of
+///   data, defaulting to `wss://stream.data.alpaca.markets`
+/// - `key_id` and `secret`: the credentials to authenticate with
+/// - `oauth_token`: an OAuth access token to authenticate with instead
+///   of `key_id` and `secret`
+///
+/// The format is a restricted subset of TOML: each line is empty, a
+/// comment starting with `#`, a `[profile.<name>]` section header, or
+/// a `<key> = <value>` setting, where `<key>` is a bare key and
+/// `<value>` a single line basic (`"..."`, supporting TOML's escape
+/// sequences) or literal (`'...'`) string. Section headers and
+/// settings may be followed by a comment. Other TOML constructs, such
+/// as quoted keys, dotted keys, or non-string values, are not
+/// supported and rejected.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Profiles {
+  /// The profiles, indexed by name.
+  profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+  /// Load profiles from the configuration file at the given path.
+  pub fn from_file<P>(path: P) -> Result<Self, Error>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let content = read_to_string(path).map_err(|err| {
+      Error::Io(
+        format!("failed to read profiles from {}", path.display()).into(),
+        err,
+      )
+    })?;
+    Self::from_str(&content)
+  }
+
+  /// Retrieve an iterator over the names of all available profiles.
+  #[inline]
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.profiles.keys().map(String::as_str)
+  }
+
+  /// Create an [`ApiInfo`] object from the profile with the given name.
+  pub fn select(&self, name: &str) -> Result<ApiInfo, Error> {
+    let profile = self
+      .profiles
+      .get(name)
+      .ok_or_else(|| Error::Str(format!("profile `{name}` not found").into()))?;
+
+    let missing = |key| Error::Str(format!("profile `{name}` lacks `{key}` setting").into());
+    let api_base_url = profile.api_base_url.as_deref().unwrap_or(API_BASE_URL);
+
+    let mut api_info = match (&profile.oauth_token, &profile.key_id, &profile.secret) {
+      (Some(oauth_token), None, None) => ApiInfo::from_oauth(api_base_url, oauth_token)?,
+      (Some(_), ..) => {
+        return Err(Error::Str(
+          format!("profile `{name}` sets both `oauth_token` and `key_id`/`secret`").into(),
+        ))
+      },
+      (None, key_id, secret) => {
+        let key_id = key_id.as_ref().ok_or_else(|| missing("key_id"))?;
+        let secret = secret.as_ref().ok_or_else(|| missing("secret"))?;
+        ApiInfo::from_parts(api_base_url, key_id, secret)?
+      },
+    };
+
+    if let Some(api_stream_url) = &profile.api_stream_url {
+      api_info.api_stream_url = Url::parse(api_stream_url)?;
+    }
+    if let Some(data_base_url) = &profile.data_base_url {
+      api_info.data_base_url = Url::parse(data_base_url)?;
+    }
+    if let Some(data_stream_base_url) = &profile.data_stream_base_url {
+      api_info.data_stream_base_url = Url::parse(data_stream_base_url)?;
+    }
+    Ok(api_info)
+  }
+
+  /// Create an [`ApiInfo`] object from the default profile.
+  ///
+  /// The default profile is the one named by the `APCA_PROFILE`
+  /// environment variable or, if that is not set, the one named
+  /// `default`.
+  pub fn select_default(&self) -> Result<ApiInfo, Error> {
+    self.select_default_from(var_os(ENV_PROFILE))
+  }
+
+  /// Create an [`ApiInfo`] object from the default profile, given the
+  /// value of the `APCA_PROFILE` environment variable.
+  fn select_default_from(&self, name: Option<OsString>) -> Result<ApiInfo, Error> {
+    let name = name
+      .map(|name| {
+        name.into_string().map_err(|_| {
+          Error::Str(format!("{ENV_PROFILE} environment variable is not a valid string").into())
+        })
+      })
+      .transpose()?;
+    self.select(name.as_deref().unwrap_or(DEFAULT_PROFILE))
+  }
+}
+
+/// Check whether `s` is empty or contains only a comment, as can
+/// follow a section header or setting.
+fn is_trailer(s: &str) -> bool {
+  let s = s.trim_start();
+  s.is_empty() || s.starts_with('#')
+}
+
+
+/// Parse a single line basic or literal TOML string, optionally
+/// followed by a comment, returning `None` if it is malformed.
+fn parse_value(s: &str) -> Option<String> {
+  let (value, rest) = if let Some(s) = s.strip_prefix('\'') {
+    let (value, rest) = s.split_once('\'')?;
+    (value.to_string(), rest)
+  } else {
+    let s = s.strip_prefix('"')?;
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+
+    loop {
+      let (idx, c) = chars.next()?;
+      match c {
+        '"' => break (value, &s[idx + 1..]),
+        '\\' => {
+          let (_idx, c) = chars.next()?;
+          let c = match c {
+            'b' => '\u{8}',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\u{c}',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            'u' | 'U' => {
+              let len = if c == 'u' { 4 } else { 8 };
+              let hex = (0..len)
+                .map(|_| chars.next().map(|(_idx, c)| c))
+                .collect::<Option<String>>()?;
+              char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+            },
+            _ => return None,
+          };
+          value.push(c)
+        },
+        c => value.push(c),
+      }
+    }
+  };
+
+  is_trailer(rest).then_some(value)
+}
+
+
+impl FromStr for Profiles {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut profiles = BTreeMap::new();
+    let mut current = None;
+
+    for (idx, line) in s.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue
+      }
+
+      let invalid = || Error::Str(format!("line {}: invalid profile syntax", idx + 1).into());
+
+      if let Some(section) = line.strip_prefix('[') {
+        let (section, rest) = section.split_once(']').ok_or_else(invalid)?;
+        let name = section
+          .trim()
+          .strip_prefix(SECTION_PREFIX)
+          .ok_or_else(invalid)?;
+        if name.is_empty() || !is_trailer(rest) {
+          return Err(invalid())
+        }
+        let _profile = profiles
+          .entry(name.to_string())
+          .or_insert_with(Profile::default);
+        current = Some(name.to_string());
+      } else {
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        let value = parse_value(value.trim()).ok_or_else(invalid)?;
+        let name = current.as_ref().ok_or_else(invalid)?;
+        // SANITY: We always insert the current profile before setting
+        //         `current`.
+        let profile = profiles.get_mut(name).unwrap();
+
+        match key.trim() {
+          "api_base_url" => profile.api_base_url = Some(value),
+          "api_stream_url" => profile.api_stream_url = Some(value),
+          "data_base_url" => profile.data_base_url = Some(value),
+          "data_stream_base_url" => profile.data_stream_base_url = Some(value),
+          "key_id" => profile.key_id = Some(value),
+          "secret" => profile.secret = Some(value),
+          "oauth_token" => profile.oauth_token = Some(value),
+          key => {
+            return Err(Error::Str(
+              format!("line {}: unknown profile setting `{key}`", idx + 1).into(),
+            ))
+          },
+        }
+      }
+    }
+
+    Ok(Self { profiles })
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::Auth;
+
+
+  /// Check that we can load a configuration with two profiles and
+  /// select each of them.
+  #[test]
+  fn load_and_select() {
+    let config = r#"
+# Paper trading account.
+[profile.paper]
+api_base_url = "https://paper-api.alpaca.markets"
+key_id = "XXXXXXXXXXXXXXXXXXXX"
+secret = "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY"
+
+[profile.mock]
+api_base_url = "http://localhost:8080"
+api_stream_url = "ws://localhost:8081/stream"
+key_id = "key"
+secret = "secret"
+"#;
+
+    let profiles = Profiles::from_str(config).unwrap();
+    assert_eq!(profiles.names().collect::<Vec<_>>(), vec!["mock", "paper"]);
+
+    let paper = profiles.select("paper").unwrap();
+    assert_eq!(
+      paper.api_base_url.as_str(),
+      "https://paper-api.alpaca.markets/"
+    );
+    assert_eq!(
+      paper.api_stream_url.as_str(),
+      "wss://paper-api.alpaca.markets/stream"
+    );
+    assert_eq!(
+      paper.auth,
+      Auth::Keys {
+        key_id: "XXXXXXXXXXXXXXXXXXXX".to_string(),
+        secret: "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY".to_string(),
+      }
+    );
+
+    let mock = profiles.select("mock").unwrap();
+    assert_eq!(mock.api_base_url.as_str(), "http://localhost:8080/");
+    assert_eq!(mock.api_stream_url.as_str(), "ws://localhost:8081/stream");
+    assert_eq!(
+      mock.auth,
+      Auth::Keys {
+        key_id: "key".to_string(),
+        secret: "secret".to_string(),
+      }
+    );
+
+    let err = profiles.select("live").unwrap_err();
+    assert_eq!(err.to_string(), "profile `live` not found");
+  }
+
+  /// Make sure that we report errors for malformed configurations.
+  #[test]
+  fn invalid_config() {
+    let err = Profiles::from_str("key_id = \"key\"").unwrap_err();
+    assert_eq!(err.to_string(), "line 1: invalid profile syntax");
+
+    let err = Profiles::from_str("[profile.a]\nfoo = \"bar\"").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: unknown profile setting `foo`");
+
+    let err = Profiles::from_str("[profile.a]\nkey_id = key").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: invalid profile syntax");
+
+    let err = Profiles::from_str("[profile.a]\nkey_id = \"key\" secret").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: invalid profile syntax");
+
+    let err = Profiles::from_str("[profile.a]\nkey_id = \"\\q\"").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: invalid profile syntax");
+
+    let profiles = Profiles::from_str("[profile.a]\nkey_id = \"key\"").unwrap();
+    let err = profiles.select("a").unwrap_err();
+    assert_eq!(err.to_string(), "profile `a` lacks `secret` setting");
+
+    let config = "[profile.a]\nkey_id = \"key\"\nsecret = \"secret\"\noauth_token = \"token\"";
+    let profiles = Profiles::from_str(config).unwrap();
+    let err = profiles.select("a").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "profile `a` sets both `oauth_token` and `key_id`/`secret`"
+    );
+  }
+
+  /// Check that we parse TOML strings and trailing comments correctly.
+  #[test]
+  fn parse_strings_and_comments() {
+    let config = r#"
+[profile.a] # An account.
+key_id = "key" # A comment.
+secret = "s\"e\\c\u0072et"
+
+[profile.b]
+oauth_token = 'C:\token # not a comment'
+data_base_url = "http://localhost:8080"
+data_stream_base_url = 'ws://localhost:8081'
+"#;
+    let profiles = Profiles::from_str(config).unwrap();
+
+    let a = profiles.select("a").unwrap();
+    assert_eq!(
+      a.auth,
+      Auth::Keys {
+        key_id: "key".to_string(),
+        secret: "s\"e\\cret".to_string(),
+      }
+    );
+
+    let b = profiles.select("b").unwrap();
+    assert_eq!(b.auth, Auth::OAuth("C:\\token # not a comment".to_string()));
+    assert_eq!(b.data_base_url.as_str(), "http://localhost:8080/");
+    assert_eq!(b.data_stream_base_url.as_str(), "ws://localhost:8081/");
+  }
+
+  /// Check that the default profile is selected based on the
+  /// `APCA_PROFILE` environment variable, if set.
+  #[test]
+  fn select_default_profile() {
+    let config = r#"
+[profile.default]
+key_id = "default-key"
+secret = "default-secret"
+
+[profile.live]
+api_base_url = "https://api.alpaca.markets"
+key_id = "live-key"
+secret = "live-secret"
+"#;
+    let profiles = Profiles::from_str(config).unwrap();
+
+    let key_id = |api_info: &ApiInfo| match &api_info.auth {
+      Auth::Keys { key_id, .. } => key_id.clone(),
+      auth => panic!("unexpected authentication: {auth:?}"),
+    };
+
+    let api_info = profiles.select_default_from(None).unwrap();
+    assert_eq!(key_id(&api_info), "default-key");
+
+    let api_info = profiles
+      .select_default_from(Some(OsString::from("live")))
+      .unwrap();
+    assert_eq!(key_id(&api_info), "live-key");
+    assert_eq!(
+      api_info.api_base_url.as_str(),
+      "https://api.alpaca.markets/"
+    );
+
+    let err = profiles
+      .select_default_from(Some(OsString::from("paper")))
+      .unwrap_err();
+    assert_eq!(err.to_string(), "profile `paper` not found");
+  }
+
+  /// Make sure that credentials are redacted when formatting
+  /// [`Profiles`].
+  #[test]
+  fn debug_redacts_credentials() {
+    let key_id = "PKXXXXXXXXXXXXXXXXXX";
+    let secret = "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY";
+    let config = format!("[profile.paper]\nkey_id = \"{key_id}\"\nsecret = \"{secret}\"");
+
+    let profiles = Profiles::from_str(&config).unwrap();
+    let string = format!("{profiles:?}");
+    assert!(!string.contains(secret), "{string}");
+    assert!(!string.contains(key_id), "{string}");
+    assert!(string.contains(r#"key_id: Some("PK****")"#), "{string}");
+    assert!(string.contains(r#"secret: Some("<redacted>")"#), "{string}");
+  }
+}
@@ -21,3 +21,29 @@ pub trait Subscribable {
   /// along with a subscription to control the stream, if applicable.
   async fn connect(input: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error>;
 }
+
+
+/// A trait for [`Subscribable`] types whose subscription state can be
+/// restored on a new connection, as done by
+/// [`Client::subscribe_resilient`][crate::Client::subscribe_resilient].
+#[async_trait]
+pub trait Resubscribable: Subscribable {
+  /// A handle to the state of a subscription.
+  type State: Send + Sync;
+
+  /// Retrieve a handle to the state of the given subscription, which
+  /// reflects all changes subsequently made to it.
+  fn state(subscription: &Self::Subscription) -> Self::State;
+
+  /// Restore the subscription state referenced by `state` on a newly
+  /// established connection, as represented by `stream` and
+  /// `subscription`.
+  ///
+  /// Subsequent changes made to `subscription` are reflected by
+  /// `state`.
+  async fn resubscribe(
+    state: &Self::State,
+    stream: &mut Self::Stream,
+    subscription: &mut Self::Subscription,
+  ) -> Result<(), Error>;
+}
@@ -0,0 +1,39 @@
+// Copyright (C) 2026 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use chrono::DateTime;
+use chrono::Utc;
+
+
+/// A source of the current time.
+///
+/// Time based helpers use a `TimeSource` instead of querying the system
+/// time directly, so that a fixed or otherwise controlled clock can be
+/// provided, e.g., for testing. Any `Fn() -> DateTime<Utc>` can be used
+/// as a time source.
+pub trait TimeSource {
+  /// Retrieve the current time.
+  fn now(&self) -> DateTime<Utc>;
+}
+
+impl<F> TimeSource for F
+where
+  F: Fn() -> DateTime<Utc>,
+{
+  #[inline]
+  fn now(&self) -> DateTime<Utc> {
+    self()
+  }
+}
+
+
+/// A [`TimeSource`] reporting the current system time.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+  #[inline]
+  fn now(&self) -> DateTime<Utc> {
+    Utc::now()
+  }
+}
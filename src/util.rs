@@ -11,6 +11,8 @@ use serde::Serialize;
 use serde::Serializer;
 use serde_variant::to_variant_name;
 
+use tracing::warn;
+
 
 /// Deserialize a `Num` from a string, parsing the value as signed first
 /// and then dropping the sign.
@@ -33,6 +35,17 @@ where
 }
 
 
+/// Deserialize an `Option<String>` from a string that could be `null`
+/// or empty, mapping both to `None`.
+pub(crate) fn option_from_non_empty_str<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let string = Option::<String>::deserialize(deserializer)?;
+  Ok(string.filter(|string| !string.is_empty()))
+}
+
+
 /// Deserialize a `Vec<String>` from a string (that could be `null`)
 /// with comma separated elements.
 pub(crate) fn vec_from_comma_separated_str<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -109,3 +122,54 @@ where
 
   slice_to_str(slice, name_fn, serializer)
 }
+
+
+/// Serialize a `limit` request parameter, clamping it to the maximum
+/// `MAX` accepted by the API and emitting a warning if clamping was
+/// necessary.
+pub(crate) fn clamp_limit<const MAX: usize, S>(
+  limit: &Option<usize>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  S: Serializer,
+{
+  let limit = match *limit {
+    Some(limit) if limit > MAX => {
+      warn!(
+        limit,
+        max = MAX,
+        "requested limit exceeds API maximum; clamping"
+      );
+      Some(MAX)
+    },
+    limit => limit,
+  };
+  limit.serialize(serializer)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::to_string as to_json;
+
+
+  /// Check that `clamp_limit` clamps limits exceeding the maximum and
+  /// passes through all others.
+  #[test]
+  fn clamp_limit_serialization() {
+    #[derive(Serialize)]
+    struct Request {
+      #[serde(serialize_with = "clamp_limit::<10, _>")]
+      limit: Option<usize>,
+    }
+
+    let json = |limit| to_json(&Request { limit }).unwrap();
+    assert_eq!(json(Some(11)), r#"{"limit":10}"#);
+    assert_eq!(json(Some(10)), r#"{"limit":10}"#);
+    assert_eq!(json(Some(0)), r#"{"limit":0}"#);
+    assert_eq!(json(None), r#"{"limit":null}"#);
+  }
+}
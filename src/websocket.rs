@@ -11,7 +11,8 @@ use tracing::trace;
 use tracing::Level;
 use tracing_futures::Instrument;
 
-use tungstenite::connect_async;
+use tungstenite::connect_async_with_config;
+use tungstenite::tungstenite::protocol::WebSocketConfig;
 use tungstenite::MaybeTlsStream;
 use tungstenite::WebSocketStream;
 
@@ -43,16 +44,20 @@ impl<T, E> From<Result<T, E>> for MessageResult<T, E> {
 
 
 /// Internal function to connect to websocket server.
-async fn connect_internal(url: &Url) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, Error> {
+async fn connect_internal(
+  url: &Url,
+  buffer_size: Option<usize>,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, Error> {
   let span = span!(Level::DEBUG, "stream");
 
   async move {
     debug!(message = "connecting", url = display(url));
 
+    let config = buffer_size.map(|size| WebSocketConfig::default().read_buffer_size(size));
     // We just ignore the response & headers that are sent along after
     // the connection is made. Alpaca does not seem to be using them,
     // really.
-    let (stream, response) = connect_async(url).await?;
+    let (stream, response) = connect_async_with_config(url, config, false).await?;
     debug!("connection successful");
     trace!(response = debug(&response));
 
@@ -63,11 +68,13 @@ async fn connect_internal(url: &Url) -> Result<WebSocketStream<MaybeTlsStream<Tc
 }
 
 
-/// Connect to a websocket server.
+/// Connect to a websocket server, optionally using a read buffer of
+/// the given size.
 pub(crate) async fn connect(
   url: &Url,
+  buffer_size: Option<usize>,
 ) -> Result<Wrapper<WebSocketStream<MaybeTlsStream<TcpStream>>>, Error> {
-  connect_internal(url)
+  connect_internal(url, buffer_size)
     .await
     .map(|stream| Wrapper::builder().build(stream))
 }
@@ -83,8 +90,10 @@ pub(crate) mod test {
   use websocket_util::test::WebSocketStream;
   use websocket_util::tungstenite::Error as WebSocketError;
 
+  use crate::backpressure::BackpressureStrategy;
   use crate::subscribable::Subscribable;
   use crate::ApiInfo;
+  use crate::Auth;
 
 
   /// The fake key-id we use.
@@ -101,6 +110,23 @@ pub(crate) mod test {
     S: Subscribable<Input = ApiInfo>,
     F: FnOnce(WebSocketStream) -> R + Send + Sync + 'static,
     R: Future<Output = Result<(), WebSocketError>> + Send + Sync + 'static,
+  {
+    mock_stream_with::<S, _, _, _>(f, |_api_info| ()).await
+  }
+
+  /// Instantiate a dummy websocket server serving messages as per the
+  /// provided function `f` and attempt to connect to it to stream
+  /// messages, allowing for the adjustment of the [`ApiInfo`] object
+  /// used for connecting via `g`.
+  pub(crate) async fn mock_stream_with<S, F, R, G>(
+    f: F,
+    g: G,
+  ) -> Result<(S::Stream, S::Subscription), Error>
+  where
+    S: Subscribable<Input = ApiInfo>,
+    F: FnOnce(WebSocketStream) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<(), WebSocketError>> + Send + Sync + 'static,
+    G: FnOnce(&mut ApiInfo),
   {
     let addr = mock_server(f).await;
     let stream_url = Url::parse(&format!("ws://{addr}")).unwrap();
@@ -108,14 +134,19 @@ pub(crate) mod test {
     // We just set both the API stream URL and the data stream URL to
     // our websocket server. We don't know which one clients are trying
     // to mock, but currently it's only one or the other.
-    let api_info = ApiInfo {
+    let mut api_info = ApiInfo {
       api_base_url: Url::parse("http://example.com").unwrap(),
       api_stream_url: stream_url.clone(),
       data_base_url: Url::parse("http://example.com").unwrap(),
       data_stream_base_url: stream_url.clone(),
-      key_id: KEY_ID.to_string(),
-      secret: SECRET.to_string(),
+      auth: Auth::Keys {
+        key_id: KEY_ID.to_string(),
+        secret: SECRET.to_string(),
+      },
+      stream_buffer_size: None,
+      stream_backpressure: BackpressureStrategy::default(),
     };
+    let () = g(&mut api_info);
 
     S::connect(&api_info).await
   }